@@ -1,4 +1,10 @@
-use crate::coordinate_system::geographic::LLBBox;
+use crate::coordinate_system::crs::Crs;
+use crate::coordinate_system::geographic::{LLBBox, LLPoint};
+use crate::coordinate_system::projection::Projection;
+use crate::elevation_data::Smoothing;
+use crate::mc_version::McVersion;
+use crate::schematic::SchematicFormat;
+use crate::world_editor::WorldFormat;
 use clap::Parser;
 use std::path::PathBuf;
 use std::time::Duration;
@@ -11,7 +17,7 @@ pub struct Args {
     #[arg(long, allow_hyphen_values = true, value_parser = LLBBox::from_str)]
     pub bbox: LLBBox,
 
-    /// JSON file containing OSM data (optional)
+    /// File containing OSM data: Overpass JSON, OSM XML (.osm) or PBF (.osm.pbf) (optional)
     #[arg(long, group = "location")]
     pub file: Option<String>,
 
@@ -27,10 +33,34 @@ pub struct Args {
     #[arg(long, default_value = "requests")]
     pub downloader: String,
 
+    /// Overpass API endpoint(s) to use instead of the public servers, comma separated (optional)
+    #[arg(long, value_delimiter = ',', env = "ARNIS_OVERPASS_URL")]
+    pub overpass_url: Vec<String>,
+
+    /// Number of retries after a failed Overpass API request
+    #[arg(long, default_value_t = 1)]
+    pub overpass_retries: u32,
+
+    /// Seconds to wait before retrying a failed Overpass API request, doubled on every retry
+    #[arg(long, default_value_t = 0)]
+    pub overpass_backoff: u64,
+
+    /// File containing a custom Overpass query, `{bbox}` is replaced by the bounding box (optional)
+    #[arg(long)]
+    pub overpass_query: Option<PathBuf>,
+
     /// World scale to use, in blocks per meter
     #[arg(long, default_value_t = 1.0)]
     pub scale: f64,
 
+    /// Reference point (lat,lng) placed at Minecraft 0,0 instead of the bbox corner, so runs with the same origin and scale line up into one world (optional)
+    #[arg(long, allow_hyphen_values = true, value_parser = LLPoint::from_str)]
+    pub origin: Option<LLPoint>,
+
+    /// Map projection from latitude and longitude to Minecraft coordinates (optional)
+    #[arg(long, value_enum, default_value = "equirectangular")]
+    pub projection: Projection,
+
     /// Ground level to use in the Minecraft world
     #[arg(long, default_value_t = -62)]
     pub ground_level: i32,
@@ -39,6 +69,42 @@ pub struct Args {
     #[arg(long)]
     pub terrain: bool,
 
+    /// Local elevation data for --terrain instead of downloading it: a GeoTIFF (.tif), SRTM (.hgt) or ESRI ASCII grid (.asc) file, a directory of such files, or a directory of Terrarium or Mapbox RGB tiles laid out as {z}/{x}/{y}.png (optional)
+    #[arg(long, requires = "terrain")]
+    pub dem: Option<PathBuf>,
+
+    /// Coordinate system of the --dem files as EPSG code, if the files don't specify it (optional)
+    #[arg(long, requires = "dem", value_parser = Crs::from_str)]
+    pub dem_crs: Option<Crs>,
+
+    /// Y coordinate of sea level for --terrain. Heights then map to Y = sea level + meters * vertical scale instead of being fitted between --ground-level and the build limit, so every run places the same height at the same Y. Heights below --ground-level are raised to it (optional)
+    #[arg(long, requires = "terrain", allow_hyphen_values = true)]
+    pub sea_level: Option<i32>,
+
+    /// Blocks per meter of height with --sea-level, defaults to the nominal vertical scale of relative terrain (optional)
+    #[arg(long, requires = "sea_level")]
+    pub vertical_scale: Option<f64>,
+
+    /// Factor for the height differences of --terrain, e.g. 2 to make hills twice as steep (optional)
+    #[arg(long, default_value_t = 1.0, value_parser = parse_exaggeration)]
+    pub vertical_exaggeration: f64,
+
+    /// Smoothing of --terrain: auto (depending on the area size), none, or the blur radius (sigma) in blocks (optional)
+    #[arg(long, default_value = "auto", value_parser = Smoothing::from_str)]
+    pub smoothing: Smoothing,
+
+    /// Smooth --terrain with an edge-preserving filter, so cliffs and embankments stay sharp (optional)
+    #[arg(long)]
+    pub preserve_edges: bool,
+
+    /// Depth of the ocean on the water side of natural=coastline ways, in blocks below sea level. The sea floor stays above bedrock, so without --terrain deeper seas need a higher --ground-level (optional)
+    #[arg(long, default_value_t = 8)]
+    pub sea_depth: u32,
+
+    /// Use the --terrain heights below sea level as sea floor instead of --sea-depth, for elevation data that includes bathymetry (optional)
+    #[arg(long, requires = "terrain")]
+    pub bathymetry: bool,
+
     /// Enable interior generation (optional)
     #[arg(long, default_value_t = true, action = clap::ArgAction::SetTrue)]
     pub interior: bool,
@@ -51,6 +117,66 @@ pub struct Args {
     #[arg(long, default_value_t = false, action = clap::ArgAction::SetFalse)]
     pub fillground: bool,
 
+    /// Update an existing world in place, keeping everything outside the generated blocks (optional)
+    #[arg(long)]
+    pub update: bool,
+
+    /// Generate in tiles of NxN regions, writing each tile to disk before the next one to bound memory use (optional)
+    #[arg(long, value_name = "REGIONS", value_parser = clap::value_parser!(u32).range(1..))]
+    pub tile_size: Option<u32>,
+
+    /// Generate tiles of regions in parallel on all cores (optional)
+    #[arg(long)]
+    pub parallel: bool,
+
+    /// Keep a checkpoint in the world folder so an interrupted generation can be resumed (optional)
+    #[arg(long)]
+    pub checkpoint: bool,
+
+    /// Resume an interrupted generation from its checkpoint, see --checkpoint (optional)
+    #[arg(long)]
+    pub resume: bool,
+
+    /// World format to write: Java Edition or Bedrock Edition (optional)
+    #[arg(long, value_enum, default_value = "java")]
+    pub format: WorldFormat,
+
+    /// Also export the generated area as Sponge schematic (.schem) or Litematica file (.litematic) (optional)
+    #[arg(long, value_parser = validate_schematic_path)]
+    pub export: Option<PathBuf>,
+
+    /// Also render the generated area from above as PNG image (optional)
+    #[arg(long, value_parser = validate_png_path)]
+    pub render: Option<PathBuf>,
+
+    /// Also render the generated area as zoomable map tiles with an HTML viewer into this directory (optional)
+    #[arg(long)]
+    pub render_tiles: Option<PathBuf>,
+
+    /// Only write the --export file, leave the world at --path untouched (optional)
+    #[arg(long, requires = "export", conflicts_with = "update")]
+    pub export_only: bool,
+
+    /// GeoJSON file with a Polygon or MultiPolygon limiting the generated area within the bounding box (optional)
+    #[arg(long, conflicts_with = "boundary_relation")]
+    pub polygon: Option<PathBuf>,
+
+    /// Id of an OSM boundary relation limiting the generated area within the bounding box, its outline is fetched from the Overpass API (optional)
+    #[arg(long)]
+    pub boundary_relation: Option<u64>,
+
+    /// Minecraft version to write the world for, the world must have been created with this version or newer (optional)
+    #[arg(long, value_enum, default_value = "latest")]
+    pub mc_version: McVersion,
+
+    /// JSON file with additional (e.g. modded) blocks that style files can reference (optional)
+    #[arg(long)]
+    pub blocks: Option<PathBuf>,
+
+    /// JSON style file mapping OSM tags to blocks, see assets/styles/default.json (optional)
+    #[arg(long)]
+    pub style: Option<PathBuf>,
+
     /// Enable debug mode (optional)
     #[arg(long)]
     pub debug: bool,
@@ -72,13 +198,39 @@ fn validate_minecraft_world_path(path: &str) -> Result<PathBuf, String> {
     if !mc_world_path.is_dir() {
         return Err(format!("Path is not a directory: {path}"));
     }
-    let region = mc_world_path.join("region");
-    if !region.is_dir() {
-        return Err(format!("No Minecraft world found at {region:?}"));
-    }
     Ok(mc_world_path)
 }
 
+fn validate_schematic_path(path: &str) -> Result<PathBuf, String> {
+    let schematic_path = PathBuf::from(path);
+    if SchematicFormat::from_path(&schematic_path).is_none() {
+        return Err(format!(
+            "Unknown schematic format, use a .schem or .litematic file: {path}"
+        ));
+    }
+    Ok(schematic_path)
+}
+
+fn validate_png_path(path: &str) -> Result<PathBuf, String> {
+    let png_path = PathBuf::from(path);
+    if !png_path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("png"))
+    {
+        return Err(format!("Renders are saved as PNG, use a .png file: {path}"));
+    }
+    Ok(png_path)
+}
+
+fn parse_exaggeration(arg: &str) -> Result<f64, String> {
+    match arg.parse::<f64>() {
+        Ok(factor) if factor > 0.0 && factor.is_finite() => Ok(factor),
+        _ => Err(format!(
+            "Vertical exaggeration must be a positive number: {arg}"
+        )),
+    }
+}
+
 fn parse_duration(arg: &str) -> Result<std::time::Duration, std::num::ParseIntError> {
     let seconds = arg.parse()?;
     Ok(std::time::Duration::from_secs(seconds))
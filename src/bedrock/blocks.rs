@@ -0,0 +1,166 @@
+//! Mapping of Java Edition block states to Bedrock Edition (1.21).
+//!
+//! Most blocks share their name in both editions by now. Block states mostly differ, the
+//! properties used by Arnis are converted and all others are left to the game's defaults.
+
+use fastnbt::Value;
+use std::collections::HashMap;
+
+/// Java block names whose Bedrock block has a different name
+const NAME_MAPPINGS: &[(&str, &str)] = &[
+    ("bricks", "brick_block"),
+    ("dead_bush", "deadbush"),
+    ("dirt_path", "grass_path"),
+    ("end_stone_bricks", "end_bricks"),
+    ("end_stone_brick_stairs", "end_brick_stairs"),
+    ("nether_bricks", "nether_brick"),
+    ("note_block", "noteblock"),
+    ("oak_door", "wooden_door"),
+    ("oak_pressure_plate", "wooden_pressure_plate"),
+    ("oak_sign", "standing_sign"),
+    ("oak_trapdoor", "trapdoor"),
+    ("oak_wall_sign", "wall_sign"),
+    ("red_bed", "bed"),
+    ("red_nether_bricks", "red_nether_brick"),
+    ("snow", "snow_layer"),
+    ("snow_block", "snow"),
+    ("stone_slab", "normal_stone_slab"),
+    ("terracotta", "hardened_clay"),
+];
+
+/// Returns the Bedrock name and block states of a Java block
+pub fn to_bedrock(java_name: &str, properties: Option<&Value>) -> (String, HashMap<String, Value>) {
+    let (namespace, name) = java_name
+        .split_once(':')
+        .unwrap_or(("minecraft", java_name));
+
+    let bedrock_name = if namespace == "minecraft" {
+        let mapped = NAME_MAPPINGS
+            .iter()
+            .find(|(java, _)| *java == name)
+            .map_or(name, |(_, bedrock)| bedrock);
+        format!("minecraft:{mapped}")
+    } else {
+        java_name.to_string()
+    };
+
+    let mut states = HashMap::new();
+    if let Some(Value::Compound(properties)) = properties {
+        for (key, value) in properties {
+            let Value::String(value) = value else {
+                continue;
+            };
+            if let Some((state, state_value)) = convert_property(name, key, value) {
+                states.insert(state.to_string(), state_value);
+            }
+        }
+    }
+    if name.ends_with("_leaves") {
+        states
+            .entry("update_bit".to_string())
+            .or_insert(Value::Byte(0));
+    }
+
+    (bedrock_name, states)
+}
+
+fn convert_property(name: &str, key: &str, value: &str) -> Option<(&'static str, Value)> {
+    let flag = |set: bool| Value::Byte(i8::from(set));
+
+    Some(match key {
+        "axis" => ("pillar_axis", Value::String(value.to_string())),
+        "persistent" => ("persistent_bit", flag(value == "true")),
+        "open" => ("open_bit", flag(value == "true")),
+        "age" => ("growth", Value::Int(value.parse().ok()?)),
+        "layers" => ("height", Value::Int(value.parse::<i32>().ok()? - 1)),
+        "rotation" => ("ground_sign_direction", Value::Int(value.parse().ok()?)),
+        "part" => ("head_piece_bit", flag(value == "head")),
+        "type" if name.ends_with("_slab") => (
+            "minecraft:vertical_half",
+            Value::String(if value == "top" { "top" } else { "bottom" }.to_string()),
+        ),
+        "half" if name.ends_with("_door") || name == "tall_grass" => {
+            ("upper_block_bit", flag(value == "upper"))
+        }
+        "half" => ("upside_down_bit", flag(value == "top")),
+        "facing" if name.ends_with("_stairs") => {
+            let direction = match value {
+                "east" => 0,
+                "west" => 1,
+                "south" => 2,
+                _ => 3,
+            };
+            ("weirdo_direction", Value::Int(direction))
+        }
+        "facing" if name.ends_with("_bed") || name.ends_with("_door") => {
+            let direction = match value {
+                "south" => 0,
+                "west" => 1,
+                "north" => 2,
+                _ => 3,
+            };
+            ("direction", Value::Int(direction))
+        }
+        "facing" => (
+            "minecraft:cardinal_direction",
+            Value::String(value.to_string()),
+        ),
+        "shape" if name.ends_with("rail") => {
+            let direction = match value {
+                "east_west" => 1,
+                "ascending_east" => 2,
+                "ascending_west" => 3,
+                "ascending_north" => 4,
+                "ascending_south" => 5,
+                "south_east" => 6,
+                "south_west" => 7,
+                "north_west" => 8,
+                "north_east" => 9,
+                _ => 0,
+            };
+            ("rail_direction", Value::Int(direction))
+        }
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn properties(pairs: &[(&str, &str)]) -> Value {
+        Value::Compound(
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), Value::String(v.to_string())))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_to_bedrock() {
+        let (name, states) = to_bedrock("minecraft:stone", None);
+        assert_eq!(name, "minecraft:stone");
+        assert!(states.is_empty());
+
+        let (name, _) = to_bedrock("bricks", None);
+        assert_eq!(name, "minecraft:brick_block");
+
+        let stairs = properties(&[("facing", "north"), ("half", "top"), ("shape", "straight")]);
+        let (name, states) = to_bedrock("minecraft:oak_stairs", Some(&stairs));
+        assert_eq!(name, "minecraft:oak_stairs");
+        assert_eq!(states["weirdo_direction"], Value::Int(3));
+        assert_eq!(states["upside_down_bit"], Value::Byte(1));
+        assert_eq!(states.len(), 2);
+
+        let (_, states) = to_bedrock(
+            "minecraft:oak_leaves",
+            Some(&properties(&[("persistent", "true")])),
+        );
+        assert_eq!(states["persistent_bit"], Value::Byte(1));
+        assert_eq!(states["update_bit"], Value::Byte(0));
+
+        let (name, _) = to_bedrock("testmod:lamp", None);
+        assert_eq!(name, "testmod:lamp");
+    }
+}
@@ -0,0 +1,315 @@
+//! Minimal LevelDB writer for Bedrock worlds.
+//!
+//! All entries are appended as write batches to the write-ahead log of a fresh database. The
+//! manifest only points to that log, so LevelDB replays it into sorted tables the first time
+//! the game opens the world. This avoids implementing the table format while producing a
+//! database every LevelDB implementation can open.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const BLOCK_SIZE: usize = 32768;
+const HEADER_SIZE: usize = 7;
+
+const RECORD_FULL: u8 = 1;
+const RECORD_FIRST: u8 = 2;
+const RECORD_MIDDLE: u8 = 3;
+const RECORD_LAST: u8 = 4;
+
+const MANIFEST_NUMBER: u64 = 2;
+const LOG_NUMBER: u64 = 3;
+
+/// Value type of a put in a write batch
+const TYPE_VALUE: u8 = 1;
+
+pub struct LevelDbWriter {
+    dir: PathBuf,
+    log: LogWriter<BufWriter<File>>,
+    sequence: u64,
+}
+
+impl LevelDbWriter {
+    /// Creates an empty database in a new directory. Fails if the directory exists, so an
+    /// existing world is never overwritten.
+    pub fn create(dir: &Path) -> io::Result<Self> {
+        if let Some(parent) = dir.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::create_dir(dir)?;
+
+        let log_file = File::create(dir.join(format!("{LOG_NUMBER:06}.log")))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            log: LogWriter::new(BufWriter::new(log_file)),
+            sequence: 0,
+        })
+    }
+
+    /// Writes the entries as one atomic batch
+    pub fn put_batch(&mut self, entries: &[(Vec<u8>, Vec<u8>)]) -> io::Result<()> {
+        let mut batch = Vec::new();
+        batch.extend_from_slice(&(self.sequence + 1).to_le_bytes());
+        batch.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+        for (key, value) in entries {
+            batch.push(TYPE_VALUE);
+            put_length_prefixed(&mut batch, key);
+            put_length_prefixed(&mut batch, value);
+        }
+
+        self.log.add_record(&batch)?;
+        self.sequence += entries.len() as u64;
+        Ok(())
+    }
+
+    /// Flushes the log and writes the manifest that makes the database openable
+    pub fn finish(mut self) -> io::Result<()> {
+        self.log.out.flush()?;
+
+        // Version edit with the comparator, log number (1, 2), next file number (3) and
+        // last sequence (4)
+        let mut edit = Vec::new();
+        put_varint(&mut edit, 1);
+        put_length_prefixed(&mut edit, b"leveldb.BytewiseComparator");
+        put_varint(&mut edit, 2);
+        put_varint(&mut edit, LOG_NUMBER);
+        put_varint(&mut edit, 3);
+        put_varint(&mut edit, LOG_NUMBER + 1);
+        put_varint(&mut edit, 4);
+        put_varint(&mut edit, self.sequence);
+
+        let manifest_name = format!("MANIFEST-{MANIFEST_NUMBER:06}");
+        let mut manifest =
+            LogWriter::new(BufWriter::new(File::create(self.dir.join(&manifest_name))?));
+        manifest.add_record(&edit)?;
+        manifest.out.flush()?;
+
+        fs::write(self.dir.join("CURRENT"), format!("{manifest_name}\n"))
+    }
+}
+
+/// Writer of the LevelDB log format: records split into fragments that never cross a block
+struct LogWriter<W: Write> {
+    out: W,
+    block_offset: usize,
+}
+
+impl<W: Write> LogWriter<W> {
+    fn new(out: W) -> Self {
+        Self {
+            out,
+            block_offset: 0,
+        }
+    }
+
+    fn add_record(&mut self, mut data: &[u8]) -> io::Result<()> {
+        let mut begin = true;
+        loop {
+            let leftover = BLOCK_SIZE - self.block_offset;
+            if leftover < HEADER_SIZE {
+                // Too small for another header, pad the rest of the block
+                self.out.write_all(&[0; HEADER_SIZE][..leftover])?;
+                self.block_offset = 0;
+            }
+
+            let available = BLOCK_SIZE - self.block_offset - HEADER_SIZE;
+            let fragment_len = data.len().min(available);
+            let end = fragment_len == data.len();
+            let record_type = match (begin, end) {
+                (true, true) => RECORD_FULL,
+                (true, false) => RECORD_FIRST,
+                (false, true) => RECORD_LAST,
+                (false, false) => RECORD_MIDDLE,
+            };
+
+            self.emit_fragment(record_type, &data[..fragment_len])?;
+            data = &data[fragment_len..];
+            begin = false;
+
+            if end {
+                return Ok(());
+            }
+        }
+    }
+
+    fn emit_fragment(&mut self, record_type: u8, data: &[u8]) -> io::Result<()> {
+        let crc = mask_crc(crc32c(crc32c(0, &[record_type]), data));
+        self.out.write_all(&crc.to_le_bytes())?;
+        self.out.write_all(&(data.len() as u16).to_le_bytes())?;
+        self.out.write_all(&[record_type])?;
+        self.out.write_all(data)?;
+        self.block_offset += HEADER_SIZE + data.len();
+        Ok(())
+    }
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn put_length_prefixed(out: &mut Vec<u8>, data: &[u8]) {
+    put_varint(out, data.len() as u64);
+    out.extend_from_slice(data);
+}
+
+const CRC32C_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = (crc >> 1) ^ (0x82F6_3B78 & (crc & 1).wrapping_neg());
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// CRC-32C (Castagnoli) as used by LevelDB, continuing from a previous checksum
+fn crc32c(crc: u32, data: &[u8]) -> u32 {
+    let mut crc = !crc;
+    for &byte in data {
+        crc = CRC32C_TABLE[((crc ^ u32::from(byte)) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+/// LevelDB stores masked checksums, so checksums of data containing checksums stay robust
+fn mask_crc(crc: u32) -> u32 {
+    crc.rotate_right(15).wrapping_add(0xa282_ead8)
+}
+
+/// Reads all entries of a database written by `LevelDbWriter`
+#[cfg(test)]
+pub fn read_entries(dir: &Path) -> io::Result<std::collections::BTreeMap<Vec<u8>, Vec<u8>>> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+    let current = fs::read_to_string(dir.join("CURRENT"))?;
+    if !dir.join(current.trim_end()).exists() {
+        return Err(invalid("Manifest missing"));
+    }
+
+    let log = fs::read(dir.join(format!("{LOG_NUMBER:06}.log")))?;
+    let mut entries = std::collections::BTreeMap::new();
+    let mut record = Vec::new();
+    let mut pos = 0;
+
+    while pos + HEADER_SIZE <= log.len() {
+        if BLOCK_SIZE - pos % BLOCK_SIZE < HEADER_SIZE {
+            pos += BLOCK_SIZE - pos % BLOCK_SIZE;
+            continue;
+        }
+        let crc = u32::from_le_bytes(log[pos..pos + 4].try_into().unwrap());
+        let len = u16::from_le_bytes(log[pos + 4..pos + 6].try_into().unwrap()) as usize;
+        let record_type = log[pos + 6];
+        let data = &log[pos + HEADER_SIZE..pos + HEADER_SIZE + len];
+        if mask_crc(crc32c(crc32c(0, &[record_type]), data)) != crc {
+            return Err(invalid("Checksum mismatch"));
+        }
+        record.extend_from_slice(data);
+        pos += HEADER_SIZE + len;
+
+        if record_type == RECORD_FULL || record_type == RECORD_LAST {
+            read_batch(&record, &mut entries).ok_or_else(|| invalid("Invalid batch"))?;
+            record.clear();
+        }
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+fn read_batch(
+    batch: &[u8],
+    entries: &mut std::collections::BTreeMap<Vec<u8>, Vec<u8>>,
+) -> Option<()> {
+    fn read_varint(data: &[u8], pos: &mut usize) -> Option<usize> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = *data.get(*pos)?;
+            *pos += 1;
+            value |= usize::from(byte & 0x7f) << shift;
+            if byte < 0x80 {
+                return Some(value);
+            }
+        }
+        None
+    }
+    fn read_slice<'a>(data: &'a [u8], pos: &mut usize) -> Option<&'a [u8]> {
+        let len = read_varint(data, pos)?;
+        let slice = data.get(*pos..*pos + len)?;
+        *pos += len;
+        Some(slice)
+    }
+
+    let count = u32::from_le_bytes(batch.get(8..12)?.try_into().ok()?);
+    let mut pos = 12;
+    for _ in 0..count {
+        if *batch.get(pos)? != TYPE_VALUE {
+            return None;
+        }
+        pos += 1;
+        let key = read_slice(batch, &mut pos)?.to_vec();
+        let value = read_slice(batch, &mut pos)?.to_vec();
+        entries.insert(key, value);
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32c() {
+        // Test vectors from RFC 3720
+        assert_eq!(crc32c(0, &[0; 32]), 0x8a91_36aa);
+        assert_eq!(crc32c(0, &[0xff; 32]), 0x62a8_ab43);
+        assert_eq!(
+            crc32c(crc32c(0, b"1234"), b"56789"),
+            crc32c(0, b"123456789")
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_across_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_dir = dir.path().join("db");
+
+        let mut db = LevelDbWriter::create(&db_dir).unwrap();
+        db.put_batch(&[(b"small".to_vec(), b"value".to_vec())])
+            .unwrap();
+        // Larger than a block, so it is split into several fragments
+        let large: Vec<u8> = (0..100_000).map(|i| i as u8).collect();
+        db.put_batch(&[
+            (b"large".to_vec(), large.clone()),
+            (b"small".to_vec(), b"overwritten".to_vec()),
+        ])
+        .unwrap();
+        db.finish().unwrap();
+
+        let entries = read_entries(&db_dir).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[b"large".as_slice()], large);
+        assert_eq!(entries[b"small".as_slice()], b"overwritten");
+    }
+
+    #[test]
+    fn test_create_keeps_existing_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_dir = dir.path().join("db");
+        fs::create_dir(&db_dir).unwrap();
+        fs::write(db_dir.join("CURRENT"), "MANIFEST-000001\n").unwrap();
+
+        let error = LevelDbWriter::create(&db_dir).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert!(db_dir.join("CURRENT").exists());
+    }
+}
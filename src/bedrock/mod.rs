@@ -0,0 +1,445 @@
+//! Bedrock Edition world output.
+//!
+//! A Bedrock world is a folder with a `level.dat` and a LevelDB database in `db/`, which holds
+//! one entry per chunk record (sub chunks, block entities, ...) keyed by the chunk position.
+//! Chunks not written by Arnis are created by the game's flat generator with the same base
+//! layers Arnis uses for Java worlds.
+
+mod blocks;
+mod leveldb;
+mod nbt;
+
+use crate::biomes;
+use fastnbt::Value;
+use leveldb::LevelDbWriter;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Chunk record tags
+const TAG_DATA_3D: u8 = 0x2b;
+const TAG_VERSION: u8 = 0x2c;
+const TAG_SUB_CHUNK: u8 = 0x2f;
+const TAG_BLOCK_ENTITY: u8 = 0x31;
+const TAG_FINALIZED_STATE: u8 = 0x36;
+
+/// Chunk format version of Bedrock 1.18.30 and newer
+const CHUNK_VERSION: u8 = 40;
+const SUB_CHUNK_VERSION: u8 = 9;
+/// Block state version of Bedrock 1.21.0.3
+const BLOCK_STATE_VERSION: i32 = 18_153_475;
+const STORAGE_VERSION: i32 = 10;
+/// Game version the world claims to be opened with last
+const GAME_VERSION: [i32; 5] = [1, 21, 0, 3, 0];
+
+const MIN_SECTION_Y: i8 = -4;
+const SECTION_COUNT: usize = 24;
+
+/// A 16x16x16 section with the palette layout of Java chunks
+pub struct BedrockSection {
+    pub y: i8,
+    /// Namespaced Java block names and properties
+    pub palette: Vec<(String, Option<Value>)>,
+    /// Palette index of every block, in Java order (YZX)
+    pub indices: Vec<usize>,
+}
+
+pub struct BedrockChunk {
+    pub x: i32,
+    pub z: i32,
+    pub sections: Vec<BedrockSection>,
+    /// Biome ids of the 4x4 block columns, indexed by `biomes::column_index`
+    pub biomes: [i32; 16],
+    /// Java block entity compounds
+    pub block_entities: Vec<Value>,
+}
+
+pub struct BedrockWriter {
+    world_dir: PathBuf,
+    db: LevelDbWriter,
+}
+
+impl BedrockWriter {
+    /// Starts a new world in the folder, which must not contain a database yet
+    pub fn create(world_dir: &Path) -> io::Result<Self> {
+        Ok(Self {
+            world_dir: world_dir.to_path_buf(),
+            db: LevelDbWriter::create(&world_dir.join("db"))?,
+        })
+    }
+
+    pub fn write_chunk(&mut self, entries: &[(Vec<u8>, Vec<u8>)]) -> io::Result<()> {
+        self.db.put_batch(entries)
+    }
+
+    /// Completes the database and writes `level.dat` with the spawn point
+    pub fn finish(self, level_name: &str, spawn: (i32, i32)) -> io::Result<()> {
+        self.db.finish()?;
+
+        std::fs::write(
+            self.world_dir.join("level.dat"),
+            level_dat(level_name, spawn),
+        )?;
+        std::fs::write(self.world_dir.join("levelname.txt"), level_name)
+    }
+}
+
+fn chunk_key(x: i32, z: i32, tag: u8) -> Vec<u8> {
+    let mut key = Vec::with_capacity(10);
+    key.extend_from_slice(&x.to_le_bytes());
+    key.extend_from_slice(&z.to_le_bytes());
+    key.push(tag);
+    key
+}
+
+/// Encodes a chunk to the database entries of its records. Done outside of the writer so
+/// chunks can be encoded in parallel.
+pub fn encode_chunk(chunk: &BedrockChunk) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let mut entries = vec![
+        (
+            chunk_key(chunk.x, chunk.z, TAG_VERSION),
+            vec![CHUNK_VERSION],
+        ),
+        (
+            chunk_key(chunk.x, chunk.z, TAG_FINALIZED_STATE),
+            2i32.to_le_bytes().to_vec(),
+        ),
+        (
+            chunk_key(chunk.x, chunk.z, TAG_DATA_3D),
+            encode_data_3d(chunk),
+        ),
+    ];
+
+    for section in &chunk.sections {
+        let mut key = chunk_key(chunk.x, chunk.z, TAG_SUB_CHUNK);
+        key.push(section.y as u8);
+        entries.push((key, encode_sub_chunk(section)));
+    }
+
+    if !chunk.block_entities.is_empty() {
+        let mut data = Vec::new();
+        for entity in &chunk.block_entities {
+            if let Some(entity) = convert_block_entity(entity) {
+                nbt::write_root(&mut data, &entity);
+            }
+        }
+        entries.push((chunk_key(chunk.x, chunk.z, TAG_BLOCK_ENTITY), data));
+    }
+
+    entries
+}
+
+fn encode_sub_chunk(section: &BedrockSection) -> Vec<u8> {
+    // Version, number of storage layers and the section index
+    let mut data = vec![SUB_CHUNK_VERSION, 1, section.y as u8];
+
+    let bits = [1, 2, 3, 4, 5, 6, 8, 16]
+        .into_iter()
+        .find(|bits| 1usize << bits >= section.palette.len())
+        .unwrap_or(16);
+    let blocks_per_word = 32 / bits;
+
+    // Persistent storage (lowest bit unset) with the palette as NBT
+    data.push((bits << 1) as u8);
+    let mut words = vec![0u32; 4096_usize.div_ceil(blocks_per_word)];
+    for x in 0..16 {
+        for z in 0..16 {
+            for y in 0..16 {
+                // Bedrock orders blocks XZY, Java YZX
+                let index = (x << 8) | (z << 4) | y;
+                let palette_index = section.indices[(y << 8) | (z << 4) | x] as u32;
+                words[index / blocks_per_word] |=
+                    palette_index << ((index % blocks_per_word) * bits);
+            }
+        }
+    }
+    for word in words {
+        data.extend_from_slice(&word.to_le_bytes());
+    }
+
+    data.extend_from_slice(&(section.palette.len() as i32).to_le_bytes());
+    for (name, properties) in &section.palette {
+        let (name, states) = blocks::to_bedrock(name, properties.as_ref());
+        let block = Value::Compound(HashMap::from([
+            ("name".to_string(), Value::String(name)),
+            ("states".to_string(), Value::Compound(states)),
+            ("version".to_string(), Value::Int(BLOCK_STATE_VERSION)),
+        ]));
+        nbt::write_root(&mut data, &block);
+    }
+
+    data
+}
+
+/// Height map and biomes of a chunk
+fn encode_data_3d(chunk: &BedrockChunk) -> Vec<u8> {
+    // Height above the bottom of the world of the first air block over the highest block
+    let mut heights = [0i16; 256];
+    for section in &chunk.sections {
+        let air = section
+            .palette
+            .iter()
+            .position(|(name, _)| name == "minecraft:air");
+        let base = (i16::from(section.y) - i16::from(MIN_SECTION_Y)) * 16;
+        for (index, &palette_index) in section.indices.iter().enumerate() {
+            if Some(palette_index) != air {
+                let column = index & 0xff;
+                let height = base + (index >> 8) as i16 + 1;
+                heights[column] = heights[column].max(height);
+            }
+        }
+    }
+
+    let mut data = Vec::with_capacity(512 + SECTION_COUNT * 5);
+    for height in heights {
+        data.extend_from_slice(&height.to_le_bytes());
+    }
+    // Biomes only vary by column, so all sections share the same biome storage
+    let biomes = encode_biomes(&chunk.biomes);
+    for _ in 0..SECTION_COUNT {
+        data.extend_from_slice(&biomes);
+    }
+    data
+}
+
+/// Biome storage of a section, single-valued when all columns share a biome
+fn encode_biomes(columns: &[i32; 16]) -> Vec<u8> {
+    let mut palette: Vec<i32> = Vec::new();
+    for biome in columns {
+        if !palette.contains(biome) {
+            palette.push(*biome);
+        }
+    }
+
+    if palette.len() == 1 {
+        let mut data = vec![0];
+        data.extend_from_slice(&palette[0].to_le_bytes());
+        return data;
+    }
+
+    let bits = [1, 2, 3, 4]
+        .into_iter()
+        .find(|bits| 1usize << bits >= palette.len())
+        .unwrap_or(4);
+    let per_word = 32 / bits;
+
+    let mut data = vec![(bits << 1) as u8];
+    let mut words = vec![0u32; 4096_usize.div_ceil(per_word)];
+    for x in 0..16 {
+        for z in 0..16 {
+            let column = biomes::column_index(x, z);
+            let palette_index = palette.iter().position(|b| *b == columns[column]).unwrap();
+            for y in 0..16 {
+                // XZY order like the blocks of a sub chunk
+                let index = (x << 8) | (z << 4) | y;
+                words[index / per_word] |= (palette_index as u32) << ((index % per_word) * bits);
+            }
+        }
+    }
+    for word in words {
+        data.extend_from_slice(&word.to_le_bytes());
+    }
+
+    data.extend_from_slice(&(palette.len() as i32).to_le_bytes());
+    for biome in palette {
+        data.extend_from_slice(&biome.to_le_bytes());
+    }
+    data
+}
+
+/// Converts a Java block entity, returns None for unsupported ones
+fn convert_block_entity(entity: &Value) -> Option<Value> {
+    let Value::Compound(entity) = entity else {
+        return None;
+    };
+    let coordinate = |key: &str| match entity.get(key) {
+        Some(Value::Int(v)) => Some(Value::Int(*v)),
+        _ => None,
+    };
+
+    match entity.get("id") {
+        Some(Value::String(id)) if id == "minecraft:sign" => {
+            // Java stores every line as a JSON text component
+            let lines: Vec<String> = match entity.get("front_text") {
+                Some(Value::Compound(front_text)) => match front_text.get("messages") {
+                    Some(Value::List(messages)) => messages
+                        .iter()
+                        .filter_map(|message| match message {
+                            Value::String(json) => {
+                                Some(serde_json::from_str::<String>(json).unwrap_or(json.clone()))
+                            }
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                },
+                _ => Vec::new(),
+            };
+            let text = |text: String| {
+                Value::Compound(HashMap::from([
+                    ("Text".to_string(), Value::String(text)),
+                    // Opaque black
+                    ("SignTextColor".to_string(), Value::Int(-16_777_216)),
+                ]))
+            };
+
+            Some(Value::Compound(HashMap::from([
+                ("id".to_string(), Value::String("Sign".to_string())),
+                ("x".to_string(), coordinate("x")?),
+                ("y".to_string(), coordinate("y")?),
+                ("z".to_string(), coordinate("z")?),
+                ("isMovable".to_string(), Value::Byte(1)),
+                ("FrontText".to_string(), text(lines.join("\n"))),
+                ("BackText".to_string(), text(String::new())),
+                ("IsWaxed".to_string(), Value::Byte(0)),
+            ])))
+        }
+        _ => None,
+    }
+}
+
+/// Bedrock `level.dat`: a header with storage version and length, then little-endian NBT
+fn level_dat(level_name: &str, spawn: (i32, i32)) -> Vec<u8> {
+    let game_version = Value::List(GAME_VERSION.iter().map(|v| Value::Int(*v)).collect());
+    let last_played = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs() as i64);
+    // Flat generator layers matching the base layers of generated chunks
+    let flat_layers = r#"{"biome_id":1,"block_layers":[{"block_name":"minecraft:bedrock","count":1},{"block_name":"minecraft:dirt","count":1},{"block_name":"minecraft:grass_block","count":1}],"encoding_version":6,"structure_options":null,"world_version":"version.post_1_18"}"#;
+
+    let level = Value::Compound(HashMap::from([
+        (
+            "LevelName".to_string(),
+            Value::String(level_name.to_string()),
+        ),
+        ("StorageVersion".to_string(), Value::Int(STORAGE_VERSION)),
+        ("lastOpenedWithVersion".to_string(), game_version.clone()),
+        ("MinimumCompatibleClientVersion".to_string(), game_version),
+        // Flat world in creative mode with cheats
+        ("Generator".to_string(), Value::Int(2)),
+        (
+            "FlatWorldLayers".to_string(),
+            Value::String(flat_layers.to_string()),
+        ),
+        ("GameType".to_string(), Value::Int(1)),
+        ("Difficulty".to_string(), Value::Int(0)),
+        ("commandsEnabled".to_string(), Value::Byte(1)),
+        ("hasBeenLoadedInCreative".to_string(), Value::Byte(1)),
+        ("SpawnX".to_string(), Value::Int(spawn.0)),
+        // Lets the game pick the surface at the spawn position
+        ("SpawnY".to_string(), Value::Int(32767)),
+        ("SpawnZ".to_string(), Value::Int(spawn.1)),
+        ("LastPlayed".to_string(), Value::Long(last_played)),
+        ("RandomSeed".to_string(), Value::Long(0)),
+    ]));
+
+    let mut body = Vec::new();
+    nbt::write_root(&mut body, &level);
+
+    let mut data = Vec::with_capacity(body.len() + 8);
+    data.extend_from_slice(&STORAGE_VERSION.to_le_bytes());
+    data.extend_from_slice(&(body.len() as i32).to_le_bytes());
+    data.extend(body);
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodes the blocks of a single layer sub chunk to Bedrock names in Java order
+    fn decode_sub_chunk(data: &[u8]) -> Vec<String> {
+        assert_eq!(data[0], SUB_CHUNK_VERSION);
+        assert_eq!(data[1], 1);
+        let bits = usize::from(data[3] >> 1);
+        let blocks_per_word = 32 / bits;
+        let word_count = 4096_usize.div_ceil(blocks_per_word);
+        let words: Vec<u32> = data[4..4 + word_count * 4]
+            .chunks(4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+            .collect();
+
+        let mut pos = 4 + word_count * 4;
+        let palette_len = i32::from_le_bytes(data[pos..pos + 4].try_into().unwrap());
+        pos += 4;
+        let mut palette = Vec::new();
+        for _ in 0..palette_len {
+            let (block, len) = nbt::read_root(&data[pos..]).unwrap();
+            pos += len;
+            let Value::Compound(block) = block else {
+                panic!("Palette entry is no compound");
+            };
+            let Some(Value::String(name)) = block.get("name") else {
+                panic!("Palette entry without name");
+            };
+            palette.push(name.clone());
+        }
+        assert_eq!(pos, data.len());
+
+        (0..4096)
+            .map(|java_index| {
+                let (y, z, x) = (java_index >> 8, (java_index >> 4) & 15, java_index & 15);
+                let index = (x << 8) | (z << 4) | y;
+                let word = words[index / blocks_per_word];
+                let palette_index =
+                    (word >> ((index % blocks_per_word) * bits)) & ((1 << bits) - 1);
+                palette[palette_index as usize].clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_write_and_read_back() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut indices = vec![0; 4096];
+        indices[3] = 1; // x 3, y 0, z 0
+        indices[(5 << 8) | (2 << 4) | 1] = 2; // x 1, y 5, z 2
+        let chunk = BedrockChunk {
+            x: -1,
+            z: 2,
+            sections: vec![BedrockSection {
+                y: -4,
+                palette: vec![
+                    ("minecraft:air".to_string(), None),
+                    ("minecraft:bricks".to_string(), None),
+                    ("minecraft:stone".to_string(), None),
+                ],
+                indices,
+            }],
+            biomes: [1; 16],
+            block_entities: Vec::new(),
+        };
+
+        let mut writer = BedrockWriter::create(dir.path()).unwrap();
+        writer.write_chunk(&encode_chunk(&chunk)).unwrap();
+        writer.finish("Test", (0, 0)).unwrap();
+
+        let entries = leveldb::read_entries(&dir.path().join("db")).unwrap();
+        assert_eq!(entries[&chunk_key(-1, 2, TAG_VERSION)], vec![CHUNK_VERSION]);
+
+        let mut sub_chunk_key = chunk_key(-1, 2, TAG_SUB_CHUNK);
+        sub_chunk_key.push(-4i8 as u8);
+        let blocks = decode_sub_chunk(&entries[&sub_chunk_key]);
+        assert_eq!(blocks[3], "minecraft:brick_block");
+        assert_eq!(blocks[(5 << 8) | (2 << 4) | 1], "minecraft:stone");
+        assert_eq!(
+            blocks.iter().filter(|b| *b == "minecraft:air").count(),
+            4094
+        );
+
+        let data_3d = &entries[&chunk_key(-1, 2, TAG_DATA_3D)];
+        let height =
+            |column: usize| i16::from_le_bytes([data_3d[column * 2], data_3d[column * 2 + 1]]);
+        assert_eq!(height(3), 1);
+        assert_eq!(height((2 << 4) | 1), 6);
+
+        let level_dat = std::fs::read(dir.path().join("level.dat")).unwrap();
+        let (level, len) = nbt::read_root(&level_dat[8..]).unwrap();
+        assert_eq!(len + 8, level_dat.len());
+        let Value::Compound(level) = level else {
+            panic!("level.dat is no compound");
+        };
+        assert_eq!(level["LevelName"], Value::String("Test".to_string()));
+    }
+}
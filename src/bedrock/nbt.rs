@@ -0,0 +1,188 @@
+//! Little-endian NBT as used by Bedrock Edition. Java Edition NBT (fastnbt) is big-endian,
+//! but the tag layout is the same, so `fastnbt::Value` is reused for the data.
+
+use fastnbt::Value;
+
+fn tag_id(value: &Value) -> u8 {
+    match value {
+        Value::Byte(_) => 1,
+        Value::Short(_) => 2,
+        Value::Int(_) => 3,
+        Value::Long(_) => 4,
+        Value::Float(_) => 5,
+        Value::Double(_) => 6,
+        Value::ByteArray(_) => 7,
+        Value::String(_) => 8,
+        Value::List(_) => 9,
+        Value::Compound(_) => 10,
+        Value::IntArray(_) => 11,
+        Value::LongArray(_) => 12,
+    }
+}
+
+/// Appends a named root tag
+pub fn write_root(out: &mut Vec<u8>, value: &Value) {
+    out.push(tag_id(value));
+    write_string(out, "");
+    write_payload(out, value);
+}
+
+fn write_string(out: &mut Vec<u8>, string: &str) {
+    out.extend_from_slice(&(string.len() as u16).to_le_bytes());
+    out.extend_from_slice(string.as_bytes());
+}
+
+fn write_payload(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Byte(v) => out.push(*v as u8),
+        Value::Short(v) => out.extend_from_slice(&v.to_le_bytes()),
+        Value::Int(v) => out.extend_from_slice(&v.to_le_bytes()),
+        Value::Long(v) => out.extend_from_slice(&v.to_le_bytes()),
+        Value::Float(v) => out.extend_from_slice(&v.to_le_bytes()),
+        Value::Double(v) => out.extend_from_slice(&v.to_le_bytes()),
+        Value::ByteArray(array) => {
+            out.extend_from_slice(&(array.len() as i32).to_le_bytes());
+            out.extend(array.iter().map(|v| *v as u8));
+        }
+        Value::String(string) => write_string(out, string),
+        Value::List(list) => {
+            out.push(list.first().map_or(0, tag_id));
+            out.extend_from_slice(&(list.len() as i32).to_le_bytes());
+            for item in list {
+                write_payload(out, item);
+            }
+        }
+        Value::Compound(compound) => {
+            // Sorted keys keep the output deterministic
+            let mut keys: Vec<&String> = compound.keys().collect();
+            keys.sort();
+            for key in keys {
+                let item = &compound[key];
+                out.push(tag_id(item));
+                write_string(out, key);
+                write_payload(out, item);
+            }
+            out.push(0);
+        }
+        Value::IntArray(array) => {
+            out.extend_from_slice(&(array.len() as i32).to_le_bytes());
+            for v in array.iter() {
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+        Value::LongArray(array) => {
+            out.extend_from_slice(&(array.len() as i32).to_le_bytes());
+            for v in array.iter() {
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+    }
+}
+
+/// Reads a named root tag, returning it and the number of bytes read
+#[cfg(test)]
+pub fn read_root(data: &[u8]) -> Option<(Value, usize)> {
+    let mut pos = 0;
+    let tag = *data.first()?;
+    pos += 1;
+    read_string(data, &mut pos)?;
+    let value = read_payload(data, &mut pos, tag)?;
+    Some((value, pos))
+}
+
+#[cfg(test)]
+fn take<'a>(data: &'a [u8], pos: &mut usize, len: usize) -> Option<&'a [u8]> {
+    let slice = data.get(*pos..*pos + len)?;
+    *pos += len;
+    Some(slice)
+}
+
+#[cfg(test)]
+fn read_string(data: &[u8], pos: &mut usize) -> Option<String> {
+    let len = u16::from_le_bytes(take(data, pos, 2)?.try_into().ok()?) as usize;
+    String::from_utf8(take(data, pos, len)?.to_vec()).ok()
+}
+
+#[cfg(test)]
+fn read_payload(data: &[u8], pos: &mut usize, tag: u8) -> Option<Value> {
+    macro_rules! read_le {
+        ($ty:ty) => {
+            <$ty>::from_le_bytes(
+                take(data, pos, std::mem::size_of::<$ty>())?
+                    .try_into()
+                    .ok()?,
+            )
+        };
+    }
+
+    Some(match tag {
+        1 => Value::Byte(read_le!(i8)),
+        2 => Value::Short(read_le!(i16)),
+        3 => Value::Int(read_le!(i32)),
+        4 => Value::Long(read_le!(i64)),
+        5 => Value::Float(read_le!(f32)),
+        6 => Value::Double(read_le!(f64)),
+        8 => Value::String(read_string(data, pos)?),
+        9 => {
+            let item_tag = *take(data, pos, 1)?.first()?;
+            let len = read_le!(i32);
+            let mut list = Vec::new();
+            for _ in 0..len {
+                list.push(read_payload(data, pos, item_tag)?);
+            }
+            Value::List(list)
+        }
+        10 => {
+            let mut compound = std::collections::HashMap::new();
+            loop {
+                let item_tag = *take(data, pos, 1)?.first()?;
+                if item_tag == 0 {
+                    break;
+                }
+                let key = read_string(data, pos)?;
+                compound.insert(key, read_payload(data, pos, item_tag)?);
+            }
+            Value::Compound(compound)
+        }
+        11 => {
+            let len = read_le!(i32);
+            let mut values = Vec::new();
+            for _ in 0..len {
+                values.push(read_le!(i32));
+            }
+            Value::IntArray(fastnbt::IntArray::new(values))
+        }
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_roundtrip() {
+        let value = Value::Compound(HashMap::from([
+            (
+                "name".to_string(),
+                Value::String("minecraft:stone".to_string()),
+            ),
+            ("version".to_string(), Value::Int(18_153_475)),
+            (
+                "states".to_string(),
+                Value::Compound(HashMap::from([("open_bit".to_string(), Value::Byte(1))])),
+            ),
+            ("empty".to_string(), Value::List(Vec::new())),
+        ]));
+
+        let mut data = Vec::new();
+        write_root(&mut data, &value);
+        // Little-endian length of the first key `empty`
+        assert_eq!(&data[3..6], &[9, 5, 0]);
+
+        let (read, len) = read_root(&data).unwrap();
+        assert_eq!(len, data.len());
+        assert_eq!(read, value);
+    }
+}
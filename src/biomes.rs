@@ -0,0 +1,222 @@
+//! Biomes of generated areas.
+//!
+//! Biomes are assigned per 4x4 block column from the `biome` category of the style, so
+//! forests, wetlands or water get their own grass, foliage and water colors. Columns without
+//! a biome are plains. Java Edition stores biomes per 4x4x4 cell since 1.18 (by name) and per
+//! cell by numeric id before, Bedrock Edition per block by numeric id.
+
+use fastnbt::{LongArray, Value};
+use std::collections::HashMap;
+
+/// Name, numeric id of Java Edition before 1.18 and Bedrock Edition id
+const BIOMES: &[(&str, i32, i32)] = &[
+    ("plains", 1, 1),
+    ("ocean", 0, 0),
+    ("desert", 2, 2),
+    ("forest", 4, 4),
+    ("taiga", 5, 5),
+    ("swamp", 6, 6),
+    ("river", 7, 7),
+    ("frozen_river", 11, 11),
+    ("snowy_plains", 12, 12),
+    ("beach", 16, 16),
+    ("jungle", 21, 21),
+    ("stony_shore", 25, 25),
+    ("birch_forest", 27, 27),
+    ("dark_forest", 29, 29),
+    ("savanna", 35, 35),
+    ("sunflower_plains", 129, 129),
+    ("flower_forest", 132, 132),
+    // Added in 1.18, older versions use plains
+    ("meadow", 1, 186),
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Biome(u8);
+
+impl Biome {
+    pub const PLAINS: Biome = Biome(0);
+    pub const OCEAN: Biome = Biome(1);
+
+    /// Looks up a biome by its name, with or without `minecraft:` namespace
+    pub fn from_name(name: &str) -> Option<Biome> {
+        let name = name.strip_prefix("minecraft:").unwrap_or(name);
+        BIOMES
+            .iter()
+            .position(|(biome, _, _)| *biome == name)
+            .map(|index| Biome(index as u8))
+    }
+
+    /// Namespaced name as stored in chunks since 1.18
+    pub fn qualified_name(self) -> String {
+        format!("minecraft:{}", BIOMES[usize::from(self.0)].0)
+    }
+
+    /// Numeric id stored in chunks before 1.18
+    pub fn legacy_id(self) -> i32 {
+        BIOMES[usize::from(self.0)].1
+    }
+
+    pub fn bedrock_id(self) -> i32 {
+        BIOMES[usize::from(self.0)].2
+    }
+}
+
+/// Biomes of the 4x4 block columns of a chunk, indexed by `column_index`. `None` where no
+/// area assigned a biome.
+pub type ChunkBiomes = [Option<Biome>; 16];
+
+/// Index of the 4x4 column containing the block at the chunk-relative coordinates
+pub fn column_index(x: usize, z: usize) -> usize {
+    (z >> 2) * 4 + (x >> 2)
+}
+
+/// The biome of a column, plains where none was assigned
+pub fn column_biome(biomes: &ChunkBiomes, column: usize) -> Biome {
+    biomes[column].unwrap_or(Biome::PLAINS)
+}
+
+/// The `biomes` compound of a section: a palette of names and the packed palette indices of
+/// the 4x4x4 cells, ordered by Y, then Z, then X
+pub fn section_biomes_nbt(biomes: &ChunkBiomes) -> Value {
+    let cells: Vec<String> = (0..64)
+        .map(|cell| column_biome(biomes, cell & 15).qualified_name())
+        .collect();
+    biomes_nbt(&cells)
+}
+
+/// Replaces the assigned columns in the `biomes` compound of a section saved by the game,
+/// keeping the existing biomes of all other cells
+pub fn merge_section_biomes(existing: Option<&Value>, biomes: &ChunkBiomes) -> Value {
+    let mut cells = existing
+        .and_then(read_biomes_nbt)
+        .unwrap_or_else(|| vec![Biome::PLAINS.qualified_name(); 64]);
+    for (cell, name) in cells.iter_mut().enumerate() {
+        if let Some(biome) = biomes[cell & 15] {
+            *name = biome.qualified_name();
+        }
+    }
+    biomes_nbt(&cells)
+}
+
+/// Numeric biome ids of the 4x4x4 cells of a chunk before 1.18, 256 blocks high
+pub fn legacy_biomes(biomes: &ChunkBiomes) -> Vec<i32> {
+    (0..1024)
+        .map(|cell| column_biome(biomes, cell & 15).legacy_id())
+        .collect()
+}
+
+/// Bits per palette index of section biomes, no data is stored for a single biome
+fn bits_per_biome(palette_len: usize) -> usize {
+    (usize::BITS - palette_len.saturating_sub(1).leading_zeros()) as usize
+}
+
+fn biomes_nbt(cells: &[String]) -> Value {
+    let mut palette: Vec<&String> = Vec::new();
+    let indices: Vec<usize> = cells
+        .iter()
+        .map(|name| {
+            palette.iter().position(|p| *p == name).unwrap_or_else(|| {
+                palette.push(name);
+                palette.len() - 1
+            })
+        })
+        .collect();
+
+    let mut nbt = HashMap::from([(
+        "palette".to_string(),
+        Value::List(
+            palette
+                .iter()
+                .map(|name| Value::String(name.to_string()))
+                .collect(),
+        ),
+    )]);
+
+    let bits = bits_per_biome(palette.len());
+    if let Some(per_long) = 64usize.checked_div(bits) {
+        let data = indices
+            .chunks(per_long)
+            .map(|indices| {
+                indices.iter().enumerate().fold(0i64, |long, (i, &index)| {
+                    long | ((index as i64) << (i * bits))
+                })
+            })
+            .collect();
+        nbt.insert("data".to_string(), Value::LongArray(LongArray::new(data)));
+    }
+
+    Value::Compound(nbt)
+}
+
+/// Reads the biome names of the 64 cells of a section `biomes` compound
+fn read_biomes_nbt(value: &Value) -> Option<Vec<String>> {
+    let Value::Compound(biomes) = value else {
+        return None;
+    };
+    let Some(Value::List(palette)) = biomes.get("palette") else {
+        return None;
+    };
+    let palette: Vec<String> = palette
+        .iter()
+        .filter_map(|name| match name {
+            Value::String(name) => Some(name.clone()),
+            _ => None,
+        })
+        .collect();
+
+    let bits = bits_per_biome(palette.len());
+    let Some(Value::LongArray(data)) = biomes.get("data").filter(|_| bits > 0) else {
+        return Some(vec![palette.first()?.clone(); 64]);
+    };
+
+    let per_long = 64 / bits;
+    let mask = (1u64 << bits) - 1;
+    (0..64)
+        .map(|cell| {
+            let long = *data.get(cell / per_long)? as u64;
+            let index = (long >> ((cell % per_long) * bits)) & mask;
+            palette.get(index as usize).cloned()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let forest = Biome::from_name("minecraft:forest").unwrap();
+        assert_eq!(Biome::from_name("forest"), Some(forest));
+        assert_eq!(forest.qualified_name(), "minecraft:forest");
+        assert_eq!(forest.legacy_id(), 4);
+        assert_eq!(Biome::from_name("meadow").unwrap().legacy_id(), 1);
+        assert_eq!(Biome::from_name("unknown"), None);
+        assert_eq!(Biome::default(), Biome::PLAINS);
+    }
+
+    #[test]
+    fn test_section_biomes() {
+        let mut biomes: ChunkBiomes = [None; 16];
+        let single = section_biomes_nbt(&biomes);
+        let Value::Compound(single) = single else {
+            unreachable!()
+        };
+        assert!(!single.contains_key("data"));
+
+        biomes[column_index(5, 9)] = Biome::from_name("swamp");
+        let nbt = section_biomes_nbt(&biomes);
+        let cells = read_biomes_nbt(&nbt).unwrap();
+        assert_eq!(cells[9], "minecraft:swamp");
+        assert_eq!(cells[16 + 9], "minecraft:swamp");
+        assert_eq!(cells[0], "minecraft:plains");
+
+        // Merging keeps the cells of the existing section that were not assigned
+        let mut other: ChunkBiomes = [None; 16];
+        other[0] = Biome::from_name("beach");
+        let merged = read_biomes_nbt(&merge_section_biomes(Some(&nbt), &other)).unwrap();
+        assert_eq!(merged[0], "minecraft:beach");
+        assert_eq!(merged[9], "minecraft:swamp");
+    }
+}
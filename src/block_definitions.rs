@@ -4,6 +4,8 @@ use fastnbt::Value;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::RwLock;
 
 use crate::colors::RGBTuple;
 
@@ -57,7 +59,7 @@ type ColorBlockMapping = (ColorTuple, BlockOptions);
 
 #[derive(Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Debug)]
 pub struct Block {
-    id: u8,
+    id: u16,
 }
 
 // Extended block with dynamic properties
@@ -82,397 +84,552 @@ impl BlockWithProperties {
 
 impl Block {
     #[inline(always)]
-    const fn new(id: u8) -> Self {
+    const fn new(id: u16) -> Self {
         Self { id }
     }
 
     #[inline(always)]
-    pub fn id(&self) -> u8 {
+    pub fn id(&self) -> u16 {
         self.id
     }
 
-    #[inline(always)]
-    pub fn namespace(&self) -> &str {
-        "minecraft"
+    pub fn namespace(&self) -> &'static str {
+        if self.id < BUILTIN_BLOCK_COUNT {
+            return "minecraft";
+        }
+        REGISTRY.read().unwrap().entry(*self).namespace
     }
 
-    pub fn name(&self) -> &str {
-        match self.id {
-            0 => "acacia_planks",
-            1 => "air",
-            2 => "andesite",
-            3 => "birch_leaves",
-            4 => "birch_log",
-            5 => "black_concrete",
-            6 => "blackstone",
-            7 => "blue_orchid",
-            8 => "blue_terracotta",
-            9 => "bricks",
-            10 => "cauldron",
-            11 => "chiseled_stone_bricks",
-            12 => "cobblestone_wall",
-            13 => "cobblestone",
-            14 => "polished_blackstone_bricks",
-            15 => "cracked_stone_bricks",
-            16 => "crimson_planks",
-            17 => "cut_sandstone",
-            18 => "cyan_concrete",
-            19 => "dark_oak_planks",
-            20 => "deepslate_bricks",
-            21 => "diorite",
-            22 => "dirt",
-            23 => "end_stone_bricks",
-            24 => "farmland",
-            25 => "glass",
-            26 => "glowstone",
-            27 => "granite",
-            28 => "grass_block",
-            29 => "short_grass",
-            30 => "gravel",
-            31 => "gray_concrete",
-            32 => "gray_terracotta",
-            33 => "green_terracotta",
-            34 => "green_wool",
-            35 => "hay_block",
-            36 => "iron_bars",
-            37 => "iron_block",
-            38 => "jungle_planks",
-            39 => "ladder",
-            40 => "light_blue_concrete",
-            41 => "light_blue_terracotta",
-            42 => "light_gray_concrete",
-            43 => "moss_block",
-            44 => "mossy_cobblestone",
-            45 => "mud_bricks",
-            46 => "nether_bricks",
-            47 => "netherite_block",
-            48 => "oak_fence",
-            49 => "oak_leaves",
-            50 => "oak_log",
-            51 => "oak_planks",
-            52 => "oak_slab",
-            53 => "orange_terracotta",
-            54 => "podzol",
-            55 => "polished_andesite",
-            56 => "polished_basalt",
-            57 => "quartz_block",
-            58 => "polished_blackstone",
-            59 => "polished_deepslate",
-            60 => "polished_diorite",
-            61 => "polished_granite",
-            62 => "prismarine",
-            63 => "purpur_block",
-            64 => "purpur_pillar",
-            65 => "quartz_bricks",
-            66 => "rail",
-            67 => "poppy",
-            68 => "red_nether_bricks",
-            69 => "red_terracotta",
-            70 => "red_wool",
-            71 => "sand",
-            72 => "sandstone",
-            73 => "scaffolding",
-            74 => "smooth_quartz",
-            75 => "smooth_red_sandstone",
-            76 => "smooth_sandstone",
-            77 => "smooth_stone",
-            78 => "sponge",
-            79 => "spruce_log",
-            80 => "spruce_planks",
-            81 => "stone_slab",
-            82 => "stone_brick_slab",
-            83 => "stone_bricks",
-            84 => "stone",
-            85 => "terracotta",
-            86 => "warped_planks",
-            87 => "water",
-            88 => "white_concrete",
-            89 => "azure_bluet",
-            90 => "white_stained_glass",
-            91 => "white_terracotta",
-            92 => "white_wool",
-            93 => "yellow_concrete",
-            94 => "dandelion",
-            95 => "yellow_wool",
-            96 => "lime_concrete",
-            97 => "cyan_wool",
-            98 => "blue_concrete",
-            99 => "purple_concrete",
-            100 => "red_concrete",
-            101 => "magenta_concrete",
-            102 => "brown_wool",
-            103 => "oxidized_copper",
-            104 => "yellow_terracotta",
-            105 => "carrots",
-            106 => "dark_oak_door",
-            107 => "dark_oak_door",
-            108 => "potatoes",
-            109 => "wheat",
-            110 => "bedrock",
-            111 => "snow_block",
-            112 => "snow",
-            113 => "oak_sign",
-            114 => "andesite_wall",
-            115 => "stone_brick_wall",
-            116..=125 => "rail",
-            126 => "coarse_dirt",
-            127 => "iron_ore",
-            128 => "coal_ore",
-            129 => "gold_ore",
-            130 => "copper_ore",
-            131 => "clay",
-            132 => "dirt_path",
-            133 => "ice",
-            134 => "packed_ice",
-            135 => "mud",
-            136 => "dead_bush",
-            137..=138 => "tall_grass",
-            139 => "crafting_table",
-            140 => "furnace",
-            141 => "white_carpet",
-            142 => "bookshelf",
-            143 => "oak_pressure_plate",
-            144 => "oak_stairs",
-            155 => "chest",
-            156 => "red_carpet",
-            157 => "anvil",
-            158 => "note_block",
-            159 => "oak_door",
-            160 => "brewing_stand",
-            161 => "red_bed", // North head
-            162 => "red_bed", // North foot
-            163 => "red_bed", // East head
-            164 => "red_bed", // East foot
-            165 => "red_bed", // South head
-            166 => "red_bed", // South foot
-            167 => "red_bed", // West head
-            168 => "red_bed", // West foot
-            169 => "gray_stained_glass",
-            170 => "light_gray_stained_glass",
-            171 => "brown_stained_glass",
-            172 => "tinted_glass",
-            173 => "oak_trapdoor",
-            174 => "brown_concrete",
-            175 => "black_terracotta",
-            176 => "brown_terracotta",
-            177 => "stone_brick_stairs",
-            178 => "mud_brick_stairs",
-            179 => "polished_blackstone_brick_stairs",
-            180 => "brick_stairs",
-            181 => "polished_granite_stairs",
-            182 => "end_stone_brick_stairs",
-            183 => "polished_diorite_stairs",
-            184 => "smooth_sandstone_stairs",
-            185 => "quartz_stairs",
-            186 => "polished_andesite_stairs",
-            187 => "nether_brick_stairs",
-            _ => panic!("Invalid id"),
+    /// Looks up a block by its name. Names without namespace refer to `minecraft:` blocks.
+    pub fn from_name(name: &str) -> Option<Block> {
+        let (namespace, name) = split_block_name(name);
+        REGISTRY.read().unwrap().lookup(namespace, name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        if self.id < BUILTIN_BLOCK_COUNT {
+            return name_of(self.id).expect("Invalid id");
         }
+        REGISTRY.read().unwrap().entry(*self).name
+    }
+
+    /// Namespaced block id as stored in block palettes, e.g. `minecraft:stone`
+    pub fn qualified_name(&self) -> String {
+        format!("{}:{}", self.namespace(), self.name())
     }
 
     pub fn properties(&self) -> Option<Value> {
-        match self.id {
-            3 => Some(Value::Compound({
-                let mut map: HashMap<String, Value> = HashMap::new();
-                map.insert("persistent".to_string(), Value::String("true".to_string()));
-                map
-            })),
-
-            49 => Some(Value::Compound({
-                let mut map: HashMap<String, Value> = HashMap::new();
-                map.insert("persistent".to_string(), Value::String("true".to_string()));
-                map
-            })),
-
-            105 => Some(Value::Compound({
-                let mut map: HashMap<String, Value> = HashMap::new();
-                map.insert("age".to_string(), Value::String("7".to_string()));
-                map
-            })),
-
-            106 => Some(Value::Compound({
-                let mut map: HashMap<String, Value> = HashMap::new();
-                map.insert("half".to_string(), Value::String("lower".to_string()));
-                map
-            })),
-
-            107 => Some(Value::Compound({
-                let mut map: HashMap<String, Value> = HashMap::new();
-                map.insert("half".to_string(), Value::String("upper".to_string()));
-                map
-            })),
-
-            108 => Some(Value::Compound({
-                let mut map: HashMap<String, Value> = HashMap::new();
-                map.insert("age".to_string(), Value::String("7".to_string()));
-                map
-            })),
-
-            109 => Some(Value::Compound({
-                let mut map: HashMap<String, Value> = HashMap::new();
-                map.insert("age".to_string(), Value::String("7".to_string()));
-                map
-            })),
-
-            113 => Some(Value::Compound({
-                let mut map: HashMap<String, Value> = HashMap::new();
-                map.insert("rotation".to_string(), Value::String("6".to_string()));
-                map.insert(
-                    "waterlogged".to_string(),
-                    Value::String("false".to_string()),
-                );
-                map
-            })),
-
-            116 => Some(Value::Compound({
-                let mut map = HashMap::new();
-                map.insert(
-                    "shape".to_string(),
-                    Value::String("north_south".to_string()),
-                );
-                map
-            })),
-
-            117 => Some(Value::Compound({
-                let mut map = HashMap::new();
-                map.insert("shape".to_string(), Value::String("east_west".to_string()));
-                map
-            })),
-
-            118 => Some(Value::Compound({
-                let mut map = HashMap::new();
-                map.insert(
-                    "shape".to_string(),
-                    Value::String("ascending_east".to_string()),
-                );
-                map
-            })),
-
-            119 => Some(Value::Compound({
-                let mut map = HashMap::new();
-                map.insert(
-                    "shape".to_string(),
-                    Value::String("ascending_west".to_string()),
-                );
-                map
-            })),
-
-            120 => Some(Value::Compound({
-                let mut map = HashMap::new();
-                map.insert(
-                    "shape".to_string(),
-                    Value::String("ascending_north".to_string()),
-                );
-                map
-            })),
-
-            121 => Some(Value::Compound({
-                let mut map = HashMap::new();
-                map.insert(
-                    "shape".to_string(),
-                    Value::String("ascending_south".to_string()),
-                );
-                map
-            })),
-
-            122 => Some(Value::Compound({
-                let mut map = HashMap::new();
-                map.insert("shape".to_string(), Value::String("north_east".to_string()));
-                map
-            })),
-
-            123 => Some(Value::Compound({
-                let mut map = HashMap::new();
-                map.insert("shape".to_string(), Value::String("north_west".to_string()));
-                map
-            })),
-
-            124 => Some(Value::Compound({
-                let mut map = HashMap::new();
-                map.insert("shape".to_string(), Value::String("south_east".to_string()));
-                map
-            })),
-
-            125 => Some(Value::Compound({
-                let mut map = HashMap::new();
-                map.insert("shape".to_string(), Value::String("south_west".to_string()));
-                map
-            })),
-            137 => Some(Value::Compound({
-                let mut map = HashMap::new();
-                map.insert("half".to_string(), Value::String("lower".to_string()));
-                map
-            })),
-            138 => Some(Value::Compound({
-                let mut map = HashMap::new();
-                map.insert("half".to_string(), Value::String("upper".to_string()));
-                map
-            })),
-
-            // Red bed variations by direction and part
-            161 => Some(Value::Compound({
-                let mut map: HashMap<String, Value> = HashMap::new();
-                map.insert("facing".to_string(), Value::String("north".to_string()));
-                map.insert("part".to_string(), Value::String("head".to_string()));
-                map
-            })),
-            162 => Some(Value::Compound({
-                let mut map: HashMap<String, Value> = HashMap::new();
-                map.insert("facing".to_string(), Value::String("north".to_string()));
-                map.insert("part".to_string(), Value::String("foot".to_string()));
-                map
-            })),
-            163 => Some(Value::Compound({
-                let mut map: HashMap<String, Value> = HashMap::new();
-                map.insert("facing".to_string(), Value::String("east".to_string()));
-                map.insert("part".to_string(), Value::String("head".to_string()));
-                map
-            })),
-            164 => Some(Value::Compound({
-                let mut map: HashMap<String, Value> = HashMap::new();
-                map.insert("facing".to_string(), Value::String("east".to_string()));
-                map.insert("part".to_string(), Value::String("foot".to_string()));
-                map
-            })),
-            165 => Some(Value::Compound({
-                let mut map: HashMap<String, Value> = HashMap::new();
-                map.insert("facing".to_string(), Value::String("south".to_string()));
-                map.insert("part".to_string(), Value::String("head".to_string()));
-                map
-            })),
-            166 => Some(Value::Compound({
-                let mut map: HashMap<String, Value> = HashMap::new();
-                map.insert("facing".to_string(), Value::String("south".to_string()));
-                map.insert("part".to_string(), Value::String("foot".to_string()));
-                map
-            })),
-            167 => Some(Value::Compound({
-                let mut map: HashMap<String, Value> = HashMap::new();
-                map.insert("facing".to_string(), Value::String("west".to_string()));
-                map.insert("part".to_string(), Value::String("head".to_string()));
-                map
-            })),
-            168 => Some(Value::Compound({
-                let mut map: HashMap<String, Value> = HashMap::new();
-                map.insert("facing".to_string(), Value::String("west".to_string()));
-                map.insert("part".to_string(), Value::String("foot".to_string()));
-                map
-            })),
-            173 => Some(Value::Compound({
-                let mut map = HashMap::new();
-                map.insert("half".to_string(), Value::String("top".to_string()));
-                map
-            })),
-            _ => None,
+        if self.id < BUILTIN_BLOCK_COUNT {
+            return builtin_properties(self.id);
         }
+        REGISTRY.read().unwrap().entry(*self).properties.clone()
+    }
+}
+
+/// Default block states of the built-in blocks
+fn builtin_properties(id: u16) -> Option<Value> {
+    match id {
+        3 => Some(Value::Compound({
+            let mut map: HashMap<String, Value> = HashMap::new();
+            map.insert("persistent".to_string(), Value::String("true".to_string()));
+            map
+        })),
+
+        49 => Some(Value::Compound({
+            let mut map: HashMap<String, Value> = HashMap::new();
+            map.insert("persistent".to_string(), Value::String("true".to_string()));
+            map
+        })),
+
+        105 => Some(Value::Compound({
+            let mut map: HashMap<String, Value> = HashMap::new();
+            map.insert("age".to_string(), Value::String("7".to_string()));
+            map
+        })),
+
+        106 => Some(Value::Compound({
+            let mut map: HashMap<String, Value> = HashMap::new();
+            map.insert("half".to_string(), Value::String("lower".to_string()));
+            map
+        })),
+
+        107 => Some(Value::Compound({
+            let mut map: HashMap<String, Value> = HashMap::new();
+            map.insert("half".to_string(), Value::String("upper".to_string()));
+            map
+        })),
+
+        108 => Some(Value::Compound({
+            let mut map: HashMap<String, Value> = HashMap::new();
+            map.insert("age".to_string(), Value::String("7".to_string()));
+            map
+        })),
+
+        109 => Some(Value::Compound({
+            let mut map: HashMap<String, Value> = HashMap::new();
+            map.insert("age".to_string(), Value::String("7".to_string()));
+            map
+        })),
+
+        113 => Some(Value::Compound({
+            let mut map: HashMap<String, Value> = HashMap::new();
+            map.insert("rotation".to_string(), Value::String("6".to_string()));
+            map.insert(
+                "waterlogged".to_string(),
+                Value::String("false".to_string()),
+            );
+            map
+        })),
+
+        116 => Some(Value::Compound({
+            let mut map = HashMap::new();
+            map.insert(
+                "shape".to_string(),
+                Value::String("north_south".to_string()),
+            );
+            map
+        })),
+
+        117 => Some(Value::Compound({
+            let mut map = HashMap::new();
+            map.insert("shape".to_string(), Value::String("east_west".to_string()));
+            map
+        })),
+
+        118 => Some(Value::Compound({
+            let mut map = HashMap::new();
+            map.insert(
+                "shape".to_string(),
+                Value::String("ascending_east".to_string()),
+            );
+            map
+        })),
+
+        119 => Some(Value::Compound({
+            let mut map = HashMap::new();
+            map.insert(
+                "shape".to_string(),
+                Value::String("ascending_west".to_string()),
+            );
+            map
+        })),
+
+        120 => Some(Value::Compound({
+            let mut map = HashMap::new();
+            map.insert(
+                "shape".to_string(),
+                Value::String("ascending_north".to_string()),
+            );
+            map
+        })),
+
+        121 => Some(Value::Compound({
+            let mut map = HashMap::new();
+            map.insert(
+                "shape".to_string(),
+                Value::String("ascending_south".to_string()),
+            );
+            map
+        })),
+
+        122 => Some(Value::Compound({
+            let mut map = HashMap::new();
+            map.insert("shape".to_string(), Value::String("north_east".to_string()));
+            map
+        })),
+
+        123 => Some(Value::Compound({
+            let mut map = HashMap::new();
+            map.insert("shape".to_string(), Value::String("north_west".to_string()));
+            map
+        })),
+
+        124 => Some(Value::Compound({
+            let mut map = HashMap::new();
+            map.insert("shape".to_string(), Value::String("south_east".to_string()));
+            map
+        })),
+
+        125 => Some(Value::Compound({
+            let mut map = HashMap::new();
+            map.insert("shape".to_string(), Value::String("south_west".to_string()));
+            map
+        })),
+        137 => Some(Value::Compound({
+            let mut map = HashMap::new();
+            map.insert("half".to_string(), Value::String("lower".to_string()));
+            map
+        })),
+        138 => Some(Value::Compound({
+            let mut map = HashMap::new();
+            map.insert("half".to_string(), Value::String("upper".to_string()));
+            map
+        })),
+
+        // Red bed variations by direction and part
+        161 => Some(Value::Compound({
+            let mut map: HashMap<String, Value> = HashMap::new();
+            map.insert("facing".to_string(), Value::String("north".to_string()));
+            map.insert("part".to_string(), Value::String("head".to_string()));
+            map
+        })),
+        162 => Some(Value::Compound({
+            let mut map: HashMap<String, Value> = HashMap::new();
+            map.insert("facing".to_string(), Value::String("north".to_string()));
+            map.insert("part".to_string(), Value::String("foot".to_string()));
+            map
+        })),
+        163 => Some(Value::Compound({
+            let mut map: HashMap<String, Value> = HashMap::new();
+            map.insert("facing".to_string(), Value::String("east".to_string()));
+            map.insert("part".to_string(), Value::String("head".to_string()));
+            map
+        })),
+        164 => Some(Value::Compound({
+            let mut map: HashMap<String, Value> = HashMap::new();
+            map.insert("facing".to_string(), Value::String("east".to_string()));
+            map.insert("part".to_string(), Value::String("foot".to_string()));
+            map
+        })),
+        165 => Some(Value::Compound({
+            let mut map: HashMap<String, Value> = HashMap::new();
+            map.insert("facing".to_string(), Value::String("south".to_string()));
+            map.insert("part".to_string(), Value::String("head".to_string()));
+            map
+        })),
+        166 => Some(Value::Compound({
+            let mut map: HashMap<String, Value> = HashMap::new();
+            map.insert("facing".to_string(), Value::String("south".to_string()));
+            map.insert("part".to_string(), Value::String("foot".to_string()));
+            map
+        })),
+        167 => Some(Value::Compound({
+            let mut map: HashMap<String, Value> = HashMap::new();
+            map.insert("facing".to_string(), Value::String("west".to_string()));
+            map.insert("part".to_string(), Value::String("head".to_string()));
+            map
+        })),
+        168 => Some(Value::Compound({
+            let mut map: HashMap<String, Value> = HashMap::new();
+            map.insert("facing".to_string(), Value::String("west".to_string()));
+            map.insert("part".to_string(), Value::String("foot".to_string()));
+            map
+        })),
+        173 => Some(Value::Compound({
+            let mut map = HashMap::new();
+            map.insert("half".to_string(), Value::String("top".to_string()));
+            map
+        })),
+        _ => None,
     }
 }
 
+/// Returns the name of a built-in block id, or None for ids that are not assigned
+fn name_of(id: u16) -> Option<&'static str> {
+    match id {
+        0 => Some("acacia_planks"),
+        1 => Some("air"),
+        2 => Some("andesite"),
+        3 => Some("birch_leaves"),
+        4 => Some("birch_log"),
+        5 => Some("black_concrete"),
+        6 => Some("blackstone"),
+        7 => Some("blue_orchid"),
+        8 => Some("blue_terracotta"),
+        9 => Some("bricks"),
+        10 => Some("cauldron"),
+        11 => Some("chiseled_stone_bricks"),
+        12 => Some("cobblestone_wall"),
+        13 => Some("cobblestone"),
+        14 => Some("polished_blackstone_bricks"),
+        15 => Some("cracked_stone_bricks"),
+        16 => Some("crimson_planks"),
+        17 => Some("cut_sandstone"),
+        18 => Some("cyan_concrete"),
+        19 => Some("dark_oak_planks"),
+        20 => Some("deepslate_bricks"),
+        21 => Some("diorite"),
+        22 => Some("dirt"),
+        23 => Some("end_stone_bricks"),
+        24 => Some("farmland"),
+        25 => Some("glass"),
+        26 => Some("glowstone"),
+        27 => Some("granite"),
+        28 => Some("grass_block"),
+        29 => Some("short_grass"),
+        30 => Some("gravel"),
+        31 => Some("gray_concrete"),
+        32 => Some("gray_terracotta"),
+        33 => Some("green_terracotta"),
+        34 => Some("green_wool"),
+        35 => Some("hay_block"),
+        36 => Some("iron_bars"),
+        37 => Some("iron_block"),
+        38 => Some("jungle_planks"),
+        39 => Some("ladder"),
+        40 => Some("light_blue_concrete"),
+        41 => Some("light_blue_terracotta"),
+        42 => Some("light_gray_concrete"),
+        43 => Some("moss_block"),
+        44 => Some("mossy_cobblestone"),
+        45 => Some("mud_bricks"),
+        46 => Some("nether_bricks"),
+        47 => Some("netherite_block"),
+        48 => Some("oak_fence"),
+        49 => Some("oak_leaves"),
+        50 => Some("oak_log"),
+        51 => Some("oak_planks"),
+        52 => Some("oak_slab"),
+        53 => Some("orange_terracotta"),
+        54 => Some("podzol"),
+        55 => Some("polished_andesite"),
+        56 => Some("polished_basalt"),
+        57 => Some("quartz_block"),
+        58 => Some("polished_blackstone"),
+        59 => Some("polished_deepslate"),
+        60 => Some("polished_diorite"),
+        61 => Some("polished_granite"),
+        62 => Some("prismarine"),
+        63 => Some("purpur_block"),
+        64 => Some("purpur_pillar"),
+        65 => Some("quartz_bricks"),
+        66 => Some("rail"),
+        67 => Some("poppy"),
+        68 => Some("red_nether_bricks"),
+        69 => Some("red_terracotta"),
+        70 => Some("red_wool"),
+        71 => Some("sand"),
+        72 => Some("sandstone"),
+        73 => Some("scaffolding"),
+        74 => Some("smooth_quartz"),
+        75 => Some("smooth_red_sandstone"),
+        76 => Some("smooth_sandstone"),
+        77 => Some("smooth_stone"),
+        78 => Some("sponge"),
+        79 => Some("spruce_log"),
+        80 => Some("spruce_planks"),
+        81 => Some("stone_slab"),
+        82 => Some("stone_brick_slab"),
+        83 => Some("stone_bricks"),
+        84 => Some("stone"),
+        85 => Some("terracotta"),
+        86 => Some("warped_planks"),
+        87 => Some("water"),
+        88 => Some("white_concrete"),
+        89 => Some("azure_bluet"),
+        90 => Some("white_stained_glass"),
+        91 => Some("white_terracotta"),
+        92 => Some("white_wool"),
+        93 => Some("yellow_concrete"),
+        94 => Some("dandelion"),
+        95 => Some("yellow_wool"),
+        96 => Some("lime_concrete"),
+        97 => Some("cyan_wool"),
+        98 => Some("blue_concrete"),
+        99 => Some("purple_concrete"),
+        100 => Some("red_concrete"),
+        101 => Some("magenta_concrete"),
+        102 => Some("brown_wool"),
+        103 => Some("oxidized_copper"),
+        104 => Some("yellow_terracotta"),
+        105 => Some("carrots"),
+        106 => Some("dark_oak_door"),
+        107 => Some("dark_oak_door"),
+        108 => Some("potatoes"),
+        109 => Some("wheat"),
+        110 => Some("bedrock"),
+        111 => Some("snow_block"),
+        112 => Some("snow"),
+        113 => Some("oak_sign"),
+        114 => Some("andesite_wall"),
+        115 => Some("stone_brick_wall"),
+        116..=125 => Some("rail"),
+        126 => Some("coarse_dirt"),
+        127 => Some("iron_ore"),
+        128 => Some("coal_ore"),
+        129 => Some("gold_ore"),
+        130 => Some("copper_ore"),
+        131 => Some("clay"),
+        132 => Some("dirt_path"),
+        133 => Some("ice"),
+        134 => Some("packed_ice"),
+        135 => Some("mud"),
+        136 => Some("dead_bush"),
+        137..=138 => Some("tall_grass"),
+        139 => Some("crafting_table"),
+        140 => Some("furnace"),
+        141 => Some("white_carpet"),
+        142 => Some("bookshelf"),
+        143 => Some("oak_pressure_plate"),
+        144 => Some("oak_stairs"),
+        155 => Some("chest"),
+        156 => Some("red_carpet"),
+        157 => Some("anvil"),
+        158 => Some("note_block"),
+        159 => Some("oak_door"),
+        160 => Some("brewing_stand"),
+        161 => Some("red_bed"), // North head
+        162 => Some("red_bed"), // North foot
+        163 => Some("red_bed"), // East head
+        164 => Some("red_bed"), // East foot
+        165 => Some("red_bed"), // South head
+        166 => Some("red_bed"), // South foot
+        167 => Some("red_bed"), // West head
+        168 => Some("red_bed"), // West foot
+        169 => Some("gray_stained_glass"),
+        170 => Some("light_gray_stained_glass"),
+        171 => Some("brown_stained_glass"),
+        172 => Some("tinted_glass"),
+        173 => Some("oak_trapdoor"),
+        174 => Some("brown_concrete"),
+        175 => Some("black_terracotta"),
+        176 => Some("brown_terracotta"),
+        177 => Some("stone_brick_stairs"),
+        178 => Some("mud_brick_stairs"),
+        179 => Some("polished_blackstone_brick_stairs"),
+        180 => Some("brick_stairs"),
+        181 => Some("polished_granite_stairs"),
+        182 => Some("end_stone_brick_stairs"),
+        183 => Some("polished_diorite_stairs"),
+        184 => Some("smooth_sandstone_stairs"),
+        185 => Some("quartz_stairs"),
+        186 => Some("polished_andesite_stairs"),
+        187 => Some("nether_brick_stairs"),
+        _ => None,
+    }
+}
+
+/// Number of built-in block ids, must be kept in sync with the constants below
+const BUILTIN_BLOCK_COUNT: u16 = 188;
+
+/// A block registered at runtime, e.g. from a block data file
+struct BlockEntry {
+    namespace: &'static str,
+    name: &'static str,
+    properties: Option<Value>,
+}
+
+/// Registry of all known blocks. The built-in blocks occupy the first ids, registered blocks
+/// are appended after them.
+struct BlockRegistry {
+    entries: Vec<BlockEntry>,
+    by_name: HashMap<(&'static str, &'static str), Block>,
+}
+
+impl BlockRegistry {
+    fn new() -> Self {
+        let mut by_name = HashMap::new();
+        for id in 0..BUILTIN_BLOCK_COUNT {
+            // Where several ids share a name, the first id is used
+            if let Some(name) = name_of(id) {
+                by_name.entry(("minecraft", name)).or_insert(Block::new(id));
+            }
+        }
+        Self {
+            entries: Vec::new(),
+            by_name,
+        }
+    }
+
+    fn entry(&self, block: Block) -> &BlockEntry {
+        self.entries
+            .get((block.id - BUILTIN_BLOCK_COUNT) as usize)
+            .expect("Invalid id")
+    }
+
+    fn lookup(&self, namespace: &str, name: &str) -> Option<Block> {
+        self.by_name.get(&(namespace, name)).copied()
+    }
+
+    fn register(
+        &mut self,
+        namespace: &str,
+        name: &str,
+        properties: Option<Value>,
+    ) -> Result<Block, String> {
+        if self.lookup(namespace, name).is_some() {
+            return Err(format!("Block '{namespace}:{name}' is already registered"));
+        }
+        let id = u16::try_from(self.entries.len())
+            .ok()
+            .and_then(|index| index.checked_add(BUILTIN_BLOCK_COUNT))
+            .ok_or("Too many registered blocks")?;
+
+        // Registered blocks live until the program exits, leaking their names lets
+        // `Block::name` hand out static strings just like for the built-in blocks
+        let namespace: &'static str = Box::leak(namespace.to_string().into_boxed_str());
+        let name: &'static str = Box::leak(name.to_string().into_boxed_str());
+
+        let block = Block::new(id);
+        self.entries.push(BlockEntry {
+            namespace,
+            name,
+            properties,
+        });
+        self.by_name.insert((namespace, name), block);
+        Ok(block)
+    }
+}
+
+static REGISTRY: Lazy<RwLock<BlockRegistry>> = Lazy::new(|| RwLock::new(BlockRegistry::new()));
+
+/// Splits a block id like `mymod:lamp` into namespace and name, defaulting to `minecraft`
+fn split_block_name(name: &str) -> (&str, &str) {
+    name.split_once(':').unwrap_or(("minecraft", name))
+}
+
+/// Registers a block so it can be referenced by name, e.g. from style files.
+/// The properties are written as the block state whenever the block is placed.
+pub fn register_block(name: &str, properties: Option<Value>) -> Result<Block, String> {
+    let (namespace, name) = split_block_name(name);
+    if namespace.is_empty() || name.is_empty() {
+        return Err(format!("Invalid block id '{namespace}:{name}'"));
+    }
+    REGISTRY
+        .write()
+        .unwrap()
+        .register(namespace, name, properties)
+}
+
+/// Entry of a block data file
+#[derive(Deserialize)]
+struct BlockDefinition {
+    /// Namespaced block id, `minecraft:` is assumed when the namespace is omitted
+    name: String,
+    /// Block state properties
+    #[serde(default)]
+    properties: HashMap<String, String>,
+}
+
+/// Registers all blocks of a JSON block data file and returns how many were added. The file
+/// holds a list of `{"name": "mymod:lamp", "properties": {"lit": "true"}}` entries.
+pub fn load_blocks(path: &Path) -> Result<usize, String> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read block file {}: {e}", path.display()))?;
+    let definitions: Vec<BlockDefinition> =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid block file: {e}"))?;
+
+    for definition in &definitions {
+        let properties = (!definition.properties.is_empty()).then(|| {
+            Value::Compound(
+                definition
+                    .properties
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+                    .collect(),
+            )
+        });
+        register_block(&definition.name, properties)?;
+    }
+
+    Ok(definitions.len())
+}
+
 // Cache for stair blocks with properties
 use std::sync::Mutex;
 
 #[allow(clippy::type_complexity)]
-static STAIR_CACHE: Lazy<Mutex<HashMap<(u8, StairFacing, StairShape), BlockWithProperties>>> =
+static STAIR_CACHE: Lazy<Mutex<HashMap<(u16, StairFacing, StairShape), BlockWithProperties>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
 // General function to create any stair block with facing and shape properties
@@ -1005,3 +1162,33 @@ pub fn get_castle_wall_block() -> Block {
     ];
     castle_wall_options[rng.gen_range(0..castle_wall_options.len())]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_lookup() {
+        assert_eq!(Block::from_name("oak_log"), Some(OAK_LOG));
+        assert_eq!(Block::from_name("minecraft:oak_log"), Some(OAK_LOG));
+        assert_eq!(Block::from_name("othermod:oak_log"), None);
+        assert_eq!(OAK_LOG.namespace(), "minecraft");
+        assert_eq!(OAK_LOG.name(), "oak_log");
+    }
+
+    #[test]
+    fn test_register_block() {
+        let mut properties = HashMap::new();
+        properties.insert("lit".to_string(), Value::String("true".to_string()));
+        let lamp = register_block("testmod:lamp", Some(Value::Compound(properties))).unwrap();
+
+        assert!(lamp.id() >= BUILTIN_BLOCK_COUNT);
+        assert_eq!(lamp.namespace(), "testmod");
+        assert_eq!(lamp.name(), "lamp");
+        assert!(lamp.properties().is_some());
+        assert_eq!(Block::from_name("testmod:lamp"), Some(lamp));
+
+        assert!(register_block("testmod:lamp", None).is_err());
+        assert!(register_block("oak_log", None).is_err());
+    }
+}
@@ -0,0 +1,402 @@
+//! On-disk checkpoints that allow resuming an interrupted generation.
+//!
+//! The checkpoint lives in a directory inside the world folder and holds the fetched OSM
+//! data, the parsed and transformed elements together with the ground (elevation grid), and
+//! the indices of the tiles that were already written to disk. With `--resume` every stage
+//! that has a checkpoint is skipped. Progress within the element list can only be resumed
+//! for tiled generation (`--tile-size`), as other runs keep all blocks in memory until saving.
+
+use crate::args::Args;
+use crate::coordinate_system::cartesian::XZBBox;
+use crate::coordinate_system::crs::Crs;
+use crate::coordinate_system::projection::Projection;
+use crate::elevation_data::Smoothing;
+use crate::ground::Ground;
+use crate::mc_version::McVersion;
+use crate::osm_parser::ProcessedElement;
+use crate::world_editor::WorldFormat;
+use fnv::FnvHasher;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::hash::Hasher;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const CHECKPOINT_DIR: &str = "arnis_checkpoint";
+const SETTINGS_FILE: &str = "settings.json";
+const OSM_DATA_FILE: &str = "osm_data.json";
+const PREPARED_DATA_FILE: &str = "prepared_data.json";
+const PROGRESS_FILE: &str = "progress.json";
+
+/// Settings that change the generated output. A checkpoint is only resumed if they match.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct CheckpointSettings {
+    bbox: [f64; 4],
+    scale: f64,
+    origin: Option<(f64, f64)>,
+    projection: Projection,
+    ground_level: i32,
+    terrain: bool,
+    dem: Option<InputFile>,
+    dem_crs: Option<Crs>,
+    sea_level: Option<i32>,
+    vertical_scale: Option<f64>,
+    vertical_exaggeration: f64,
+    smoothing: Smoothing,
+    preserve_edges: bool,
+    sea_depth: u32,
+    bathymetry: bool,
+    interior: bool,
+    roof: bool,
+    fillground: bool,
+    update: bool,
+    tile_size: Option<u32>,
+    format: WorldFormat,
+    file: Option<InputFile>,
+    downloader: String,
+    overpass_url: Vec<String>,
+    overpass_query: Option<InputFile>,
+    polygon: Option<InputFile>,
+    boundary_relation: Option<u64>,
+    mc_version: McVersion,
+    blocks: Option<InputFile>,
+    style: Option<InputFile>,
+}
+
+/// An input file given on the command line. Its contents are hashed, so a checkpoint isn't
+/// resumed after the file was edited in place.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct InputFile {
+    path: PathBuf,
+    hash: Option<u64>,
+}
+
+impl InputFile {
+    fn new(path: &Path) -> Self {
+        // An unreadable file is reported by the stage that reads it
+        let hash = fs::read(path).ok().map(|contents| {
+            let mut hasher = FnvHasher::default();
+            hasher.write(&contents);
+            hasher.finish()
+        });
+        Self {
+            path: path.to_path_buf(),
+            hash,
+        }
+    }
+}
+
+impl CheckpointSettings {
+    fn from_args(args: &Args) -> Self {
+        Self {
+            bbox: [
+                args.bbox.min().lat(),
+                args.bbox.min().lng(),
+                args.bbox.max().lat(),
+                args.bbox.max().lng(),
+            ],
+            scale: args.scale,
+            origin: args.origin.map(|origin| (origin.lat(), origin.lng())),
+            projection: args.projection,
+            ground_level: args.ground_level,
+            terrain: args.terrain,
+            dem: args.dem.as_deref().map(InputFile::new),
+            dem_crs: args.dem_crs,
+            sea_level: args.sea_level,
+            vertical_scale: args.vertical_scale,
+            vertical_exaggeration: args.vertical_exaggeration,
+            smoothing: args.smoothing,
+            preserve_edges: args.preserve_edges,
+            sea_depth: args.sea_depth,
+            bathymetry: args.bathymetry,
+            interior: args.interior,
+            roof: args.roof,
+            fillground: args.fillground,
+            update: args.update,
+            tile_size: args.tile_size,
+            format: args.format,
+            file: args
+                .file
+                .as_deref()
+                .map(|file| InputFile::new(Path::new(file))),
+            downloader: args.downloader.clone(),
+            overpass_url: args.overpass_url.clone(),
+            overpass_query: args.overpass_query.as_deref().map(InputFile::new),
+            polygon: args.polygon.as_deref().map(InputFile::new),
+            boundary_relation: args.boundary_relation,
+            mc_version: args.mc_version,
+            blocks: args.blocks.as_deref().map(InputFile::new),
+            style: args.style.as_deref().map(InputFile::new),
+        }
+    }
+}
+
+/// Elements, bbox and ground as they are passed to the world generation
+#[derive(Serialize, Deserialize)]
+pub struct PreparedData {
+    pub elements: Vec<ProcessedElement>,
+    pub xzbbox: XZBBox,
+    pub ground: Ground,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Progress {
+    completed_tiles: BTreeSet<usize>,
+}
+
+pub struct Checkpoint {
+    dir: PathBuf,
+    progress: Mutex<Progress>,
+}
+
+impl Checkpoint {
+    /// Opens the checkpoint of the world. When resuming, an existing checkpoint created with
+    /// the same settings is reused, otherwise a fresh checkpoint is started.
+    pub fn open(args: &Args) -> Result<Self, Box<dyn std::error::Error>> {
+        let dir = args.path.join(CHECKPOINT_DIR);
+        let settings = CheckpointSettings::from_args(args);
+
+        if args.resume {
+            match read_json::<CheckpointSettings>(&dir.join(SETTINGS_FILE)) {
+                Some(existing) if existing == settings => {
+                    let progress = read_json(&dir.join(PROGRESS_FILE)).unwrap_or_default();
+                    return Ok(Self {
+                        dir,
+                        progress: Mutex::new(progress),
+                    });
+                }
+                Some(_) => {
+                    eprintln!(
+                        "Warning: The checkpoint was created with different settings, starting over"
+                    );
+                }
+                None => {
+                    eprintln!("Warning: No checkpoint found, starting from the beginning");
+                }
+            }
+        }
+
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        fs::create_dir_all(&dir)?;
+        write_json(&dir.join(SETTINGS_FILE), &settings)?;
+
+        Ok(Self {
+            dir,
+            progress: Mutex::new(Progress::default()),
+        })
+    }
+
+    /// Returns the fetched OSM data if it was saved before
+    pub fn load_osm_data(&self) -> Option<Value> {
+        read_json(&self.dir.join(OSM_DATA_FILE))
+    }
+
+    pub fn save_osm_data(&self, data: &Value) -> Result<(), Box<dyn std::error::Error>> {
+        write_json(&self.dir.join(OSM_DATA_FILE), data)
+    }
+
+    /// Returns the parsed elements, bbox and ground if they were saved before
+    pub fn load_prepared_data(&self) -> Option<PreparedData> {
+        read_json(&self.dir.join(PREPARED_DATA_FILE))
+    }
+
+    pub fn save_prepared_data(
+        &self,
+        elements: &[ProcessedElement],
+        xzbbox: &XZBBox,
+        ground: &Ground,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        #[derive(Serialize)]
+        struct PreparedDataRef<'a> {
+            elements: &'a [ProcessedElement],
+            xzbbox: &'a XZBBox,
+            ground: &'a Ground,
+        }
+
+        write_json(
+            &self.dir.join(PREPARED_DATA_FILE),
+            &PreparedDataRef {
+                elements,
+                xzbbox,
+                ground,
+            },
+        )
+    }
+
+    pub fn is_tile_completed(&self, tile_index: usize) -> bool {
+        self.progress
+            .lock()
+            .unwrap()
+            .completed_tiles
+            .contains(&tile_index)
+    }
+
+    pub fn completed_tiles_count(&self) -> usize {
+        self.progress.lock().unwrap().completed_tiles.len()
+    }
+
+    /// Records a tile whose regions have been written to disk
+    pub fn complete_tile(&self, tile_index: usize) -> Result<(), Box<dyn std::error::Error>> {
+        let mut progress = self.progress.lock().unwrap();
+        progress.completed_tiles.insert(tile_index);
+        write_json(&self.dir.join(PROGRESS_FILE), &*progress)
+    }
+
+    /// Removes the checkpoint after the generation completed
+    pub fn remove(self) -> Result<(), Box<dyn std::error::Error>> {
+        fs::remove_dir_all(&self.dir)?;
+        Ok(())
+    }
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Option<T> {
+    let file = File::open(path).ok()?;
+    match serde_json::from_reader(BufReader::new(file)) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            eprintln!(
+                "Warning: Ignoring unreadable checkpoint file {}: {e}",
+                path.display()
+            );
+            None
+        }
+    }
+}
+
+/// Writes to a temporary file first, so an interruption never leaves a truncated checkpoint
+fn write_json<T: Serialize + ?Sized>(
+    path: &Path,
+    value: &T,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_path = path.with_extension("json.tmp");
+    let mut writer = BufWriter::new(File::create(&tmp_path)?);
+    serde_json::to_writer(&mut writer, value)?;
+    writer
+        .into_inner()
+        .map_err(|e| e.into_error())?
+        .sync_all()?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinate_system::cartesian::XZPoint;
+    use crate::osm_parser::ProcessedNode;
+    use clap::Parser;
+    use std::collections::HashMap;
+
+    fn minecraft_tmpdir() -> tempfile::TempDir {
+        let tmpdir = tempfile::tempdir().unwrap();
+        fs::create_dir(tmpdir.path().join("region")).unwrap();
+        tmpdir
+    }
+
+    fn args(path: &Path, extra: &[&str]) -> Args {
+        let mut cmd = vec!["arnis", "--path", path.to_str().unwrap()];
+        cmd.extend_from_slice(&["--bbox", "48.1,11.5,48.2,11.6", "--checkpoint"]);
+        cmd.extend_from_slice(extra);
+        Args::parse_from(cmd)
+    }
+
+    #[test]
+    fn test_resume_skips_completed_tiles() {
+        let tmpdir = minecraft_tmpdir();
+
+        let checkpoint = Checkpoint::open(&args(tmpdir.path(), &[])).unwrap();
+        checkpoint.complete_tile(0).unwrap();
+        checkpoint.complete_tile(2).unwrap();
+
+        let checkpoint = Checkpoint::open(&args(tmpdir.path(), &["--resume"])).unwrap();
+        assert_eq!(checkpoint.completed_tiles_count(), 2);
+        assert!(checkpoint.is_tile_completed(0));
+        assert!(!checkpoint.is_tile_completed(1));
+        assert!(checkpoint.is_tile_completed(2));
+
+        // Without --resume the checkpoint is started over
+        let checkpoint = Checkpoint::open(&args(tmpdir.path(), &[])).unwrap();
+        assert_eq!(checkpoint.completed_tiles_count(), 0);
+    }
+
+    #[test]
+    fn test_resume_discards_checkpoint_of_other_settings() {
+        let tmpdir = minecraft_tmpdir();
+
+        let checkpoint = Checkpoint::open(&args(tmpdir.path(), &[])).unwrap();
+        checkpoint.complete_tile(0).unwrap();
+        checkpoint.save_osm_data(&Value::Null).unwrap();
+
+        let checkpoint =
+            Checkpoint::open(&args(tmpdir.path(), &["--resume", "--scale", "2"])).unwrap();
+        assert_eq!(checkpoint.completed_tiles_count(), 0);
+        assert!(checkpoint.load_osm_data().is_none());
+    }
+
+    #[test]
+    fn test_resume_discards_checkpoint_of_edited_input_file() {
+        let tmpdir = minecraft_tmpdir();
+        let osm_file = tmpdir.path().join("map.osm");
+        fs::write(&osm_file, "<osm></osm>").unwrap();
+        let file_args = |extra: &[&str]| {
+            let mut cmd = vec!["--file", osm_file.to_str().unwrap()];
+            cmd.extend_from_slice(extra);
+            args(tmpdir.path(), &cmd)
+        };
+
+        let checkpoint = Checkpoint::open(&file_args(&[])).unwrap();
+        checkpoint.complete_tile(0).unwrap();
+
+        // The same file resumes the checkpoint
+        let checkpoint = Checkpoint::open(&file_args(&["--resume"])).unwrap();
+        assert!(checkpoint.is_tile_completed(0));
+
+        // An edited file at the same path starts over
+        fs::write(&osm_file, "<osm><node/></osm>").unwrap();
+        let checkpoint = Checkpoint::open(&file_args(&["--resume"])).unwrap();
+        assert!(!checkpoint.is_tile_completed(0));
+    }
+
+    #[test]
+    fn test_prepared_data_round_trip() {
+        let tmpdir = minecraft_tmpdir();
+        let checkpoint = Checkpoint::open(&args(tmpdir.path(), &[])).unwrap();
+
+        let node = ProcessedNode {
+            id: 7,
+            tags: HashMap::from([("amenity".to_string(), "bench".to_string())]),
+            x: 3,
+            z: 5,
+        };
+        let elements = vec![ProcessedElement::Node(node.clone())];
+        let xzbbox = XZBBox::rect_from_xz_lengths(20.0, 10.0).unwrap();
+        let ground = Ground::from_heights(vec![vec![-60.0, -58.0], vec![-55.0, -50.0]]);
+        checkpoint
+            .save_prepared_data(&elements, &xzbbox, &ground)
+            .unwrap();
+
+        let prepared = checkpoint.load_prepared_data().unwrap();
+        assert!(matches!(&prepared.elements[..], [ProcessedElement::Node(n)] if *n == node));
+        assert_eq!(prepared.xzbbox.bounding_rect(), xzbbox.bounding_rect());
+        for (x, z) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let coord = XZPoint::new(x, z);
+            assert_eq!(prepared.ground.level(coord), ground.level(coord));
+        }
+    }
+
+    #[test]
+    fn test_remove_deletes_checkpoint() {
+        let tmpdir = minecraft_tmpdir();
+        let checkpoint = Checkpoint::open(&args(tmpdir.path(), &[])).unwrap();
+        checkpoint.complete_tile(0).unwrap();
+        assert!(tmpdir.path().join(CHECKPOINT_DIR).exists());
+
+        checkpoint.remove().unwrap();
+        assert!(!tmpdir.path().join(CHECKPOINT_DIR).exists());
+    }
+}
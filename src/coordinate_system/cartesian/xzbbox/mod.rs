@@ -1,3 +1,4 @@
+mod polygon;
 mod rectangle;
 mod xzbbox_enum;
 
@@ -0,0 +1,150 @@
+use super::rectangle::XZBBoxRect;
+use crate::coordinate_system::cartesian::{XZPoint, XZVector};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+
+/// An underlying shape of XZBBox enum.
+/// Covers the blocks inside one or more rings by the even-odd rule, so rings inside other
+/// rings are holes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct XZBBoxPolygon {
+    rings: Vec<Vec<XZPoint>>,
+
+    /// The rectangle the rings were clipped to. Blocks keep their position relative to it, e.g.
+    /// on the elevation grid, like in rectangle shaped bboxes.
+    bounds: XZBBoxRect,
+
+    /// Covered x ranges (inclusive) of each row of `bounds`, relative to `bounds.min().x`, so
+    /// the shape can be moved without recomputing them
+    spans: Vec<Vec<(i32, i32)>>,
+}
+
+impl XZBBoxPolygon {
+    /// Creates the polygon of the blocks inside the rings that are also inside `clip`.
+    /// Rings do not need to repeat their first point at the end.
+    pub fn new(rings: Vec<Vec<XZPoint>>, clip: XZBBoxRect) -> Result<Self, String> {
+        let (clip_min, clip_max) = (clip.min(), clip.max());
+
+        // X coordinates where the edges cross each row of the clip rectangle
+        let mut crossings: Vec<Vec<f64>> = vec![Vec::new(); clip.total_blocks_z() as usize];
+        // Horizontal edges, which are covered but cross no row
+        let mut edges: Vec<Vec<(i32, i32)>> = vec![Vec::new(); crossings.len()];
+        for ring in &rings {
+            for (i, a) in ring.iter().enumerate() {
+                let b = ring[(i + 1) % ring.len()];
+                if a.z == b.z {
+                    if (clip_min.z..=clip_max.z).contains(&a.z) {
+                        edges[(a.z - clip_min.z) as usize].push((a.x.min(b.x), a.x.max(b.x)));
+                    }
+                    continue;
+                }
+                // Rows at or above the lower end and below the upper end, so an edge
+                // passing through a vertex counts once
+                let (low, high) = (a.z.min(b.z), a.z.max(b.z));
+                for z in low.max(clip_min.z)..high.min(clip_max.z + 1) {
+                    let t = f64::from(z - a.z) / f64::from(b.z - a.z);
+                    let x = f64::from(a.x) + t * f64::from(b.x - a.x);
+                    crossings[(z - clip_min.z) as usize].push(x);
+                }
+            }
+        }
+
+        let spans: Vec<Vec<(i32, i32)>> = crossings
+            .into_iter()
+            .zip(edges)
+            .map(|(mut xs, edges)| {
+                xs.sort_by(f64::total_cmp);
+                xs.chunks_exact(2)
+                    .map(|pair| (pair[0].ceil() as i32, pair[1].floor() as i32))
+                    .chain(edges)
+                    .map(|(start, end)| (start.max(clip_min.x), end.min(clip_max.x)))
+                    .filter(|(start, end)| start <= end)
+                    .map(|(start, end)| (start - clip_min.x, end - clip_min.x))
+                    .collect()
+            })
+            .collect();
+
+        if spans.iter().all(Vec::is_empty) {
+            return Err("Invalid XZBBox::Polygon: covers no blocks within the bounds".to_string());
+        }
+
+        Ok(Self {
+            rings,
+            bounds: clip,
+            spans,
+        })
+    }
+
+    pub fn bounds(&self) -> XZBBoxRect {
+        self.bounds
+    }
+
+    /// The covered block with the lowest z, and the lowest x in its row
+    pub fn first_block(&self) -> XZPoint {
+        let (row, spans) = self
+            .spans
+            .iter()
+            .enumerate()
+            .find(|(_, spans)| !spans.is_empty())
+            .unwrap();
+        let start = spans.iter().map(|&(start, _)| start).min().unwrap();
+        XZPoint::new(
+            self.bounds.min().x + start,
+            self.bounds.min().z + row as i32,
+        )
+    }
+
+    /// Check whether an XZPoint is covered
+    pub fn contains(&self, xzpoint: &XZPoint) -> bool {
+        if !self.bounds.contains(xzpoint) {
+            return false;
+        }
+        let row = &self.spans[(xzpoint.z - self.bounds.min().z) as usize];
+        let dx = xzpoint.x - self.bounds.min().x;
+        row.iter().any(|&(start, end)| start <= dx && dx <= end)
+    }
+}
+
+impl fmt::Display for XZBBoxPolygon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Polygon({} rings in {})", self.rings.len(), self.bounds)
+    }
+}
+
+// below are associated +- operators
+impl Add<XZVector> for XZBBoxPolygon {
+    type Output = XZBBoxPolygon;
+
+    fn add(mut self, other: XZVector) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl AddAssign<XZVector> for XZBBoxPolygon {
+    fn add_assign(&mut self, other: XZVector) {
+        for point in self.rings.iter_mut().flatten() {
+            *point += other;
+        }
+        self.bounds += other;
+    }
+}
+
+impl Sub<XZVector> for XZBBoxPolygon {
+    type Output = XZBBoxPolygon;
+
+    fn sub(mut self, other: XZVector) -> Self {
+        self -= other;
+        self
+    }
+}
+
+impl SubAssign<XZVector> for XZBBoxPolygon {
+    fn sub_assign(&mut self, other: XZVector) {
+        for point in self.rings.iter_mut().flatten() {
+            *point -= other;
+        }
+        self.bounds -= other;
+    }
+}
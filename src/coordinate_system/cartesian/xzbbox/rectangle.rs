@@ -1,9 +1,10 @@
 use crate::coordinate_system::cartesian::{XZPoint, XZVector};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::ops::{Add, AddAssign, Sub, SubAssign};
 
 /// An underlying shape of XZBBox enum.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct XZBBoxRect {
     /// The "bottom-left" vertex of the rectangle
     min: XZPoint,
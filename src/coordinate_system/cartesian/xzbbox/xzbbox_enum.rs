@@ -1,12 +1,15 @@
+use super::polygon::XZBBoxPolygon;
 use super::rectangle::XZBBoxRect;
 use crate::coordinate_system::cartesian::{XZPoint, XZVector};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::ops::{Add, AddAssign, Sub, SubAssign};
 
 /// Bounding Box in minecraft XZ space with varied shapes.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum XZBBox {
     Rect(XZBBoxRect),
+    Polygon(XZBBoxPolygon),
 }
 
 impl XZBBox {
@@ -50,17 +53,42 @@ impl XZBBox {
         )?))
     }
 
+    /// Construct rectangle shape bbox from its min and max vertices (both inclusive)
+    pub fn rect_from_min_max(min: XZPoint, max: XZPoint) -> Result<Self, String> {
+        Ok(Self::Rect(XZBBoxRect::new(min, max)?))
+    }
+
+    /// Construct polygon shape bbox covering the blocks inside the rings (even-odd rule) that
+    /// are also covered by `clip`
+    pub fn polygon_from_rings(rings: Vec<Vec<XZPoint>>, clip: &XZBBox) -> Result<Self, String> {
+        Ok(Self::Polygon(XZBBoxPolygon::new(
+            rings,
+            clip.bounding_rect(),
+        )?))
+    }
+
     /// Check whether an XZPoint is covered
     pub fn contains(&self, xzpoint: &XZPoint) -> bool {
         match self {
             Self::Rect(r) => r.contains(xzpoint),
+            Self::Polygon(p) => p.contains(xzpoint),
+        }
+    }
+
+    /// Return a covered block, the one with the lowest z and the lowest x in its row
+    pub fn first_block(&self) -> XZPoint {
+        match self {
+            Self::Rect(r) => r.min(),
+            Self::Polygon(p) => p.first_block(),
         }
     }
 
-    /// Return the circumscribed rectangle of the current XZBBox shape
+    /// Return the circumscribed rectangle of the current XZBBox shape, for polygons the
+    /// rectangle they were clipped to
     pub fn bounding_rect(&self) -> XZBBoxRect {
         match self {
             Self::Rect(r) => *r,
+            Self::Polygon(p) => p.bounds(),
         }
     }
 
@@ -89,6 +117,7 @@ impl fmt::Display for XZBBox {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Rect(r) => write!(f, "XZBBox::{r}"),
+            Self::Polygon(p) => write!(f, "XZBBox::{p}"),
         }
     }
 }
@@ -100,6 +129,7 @@ impl Add<XZVector> for XZBBox {
     fn add(self, other: XZVector) -> XZBBox {
         match self {
             Self::Rect(r) => Self::Rect(r + other),
+            Self::Polygon(p) => Self::Polygon(p + other),
         }
     }
 }
@@ -108,6 +138,7 @@ impl AddAssign<XZVector> for XZBBox {
     fn add_assign(&mut self, other: XZVector) {
         match self {
             Self::Rect(r) => *r += other,
+            Self::Polygon(p) => *p += other,
         }
     }
 }
@@ -118,6 +149,7 @@ impl Sub<XZVector> for XZBBox {
     fn sub(self, other: XZVector) -> XZBBox {
         match self {
             Self::Rect(r) => Self::Rect(r - other),
+            Self::Polygon(p) => Self::Polygon(p - other),
         }
     }
 }
@@ -126,6 +158,7 @@ impl SubAssign<XZVector> for XZBBox {
     fn sub_assign(&mut self, other: XZVector) {
         match self {
             Self::Rect(r) => *r -= other,
+            Self::Polygon(p) => *p -= other,
         }
     }
 }
@@ -199,4 +232,40 @@ mod test {
         assert!(XZBBox::rect_from_xz_lengths(i32::MAX as f64 + 10.0, -0.5).is_err());
         assert!(XZBBox::rect_from_xz_lengths(0.2, i32::MAX as f64 + 10.0).is_err());
     }
+
+    #[test]
+    fn test_polygon() {
+        let clip = XZBBox::rect_from_xz_lengths(20.0, 20.0).unwrap();
+        let ring = |points: &[(i32, i32)]| {
+            points
+                .iter()
+                .map(|&(x, z)| XZPoint::new(x, z))
+                .collect::<Vec<_>>()
+        };
+
+        // L-shape with a square hole, partly outside of the clip rectangle
+        let outer = ring(&[(-5, 1), (10, 1), (10, 5), (5, 5), (5, 10), (-5, 10)]);
+        let hole = ring(&[(0, 2), (2, 2), (2, 4), (0, 4)]);
+        let obj = XZBBox::polygon_from_rings(vec![outer, hole], &clip).unwrap();
+
+        // Blocks keep their position within the clip rectangle
+        assert_eq!(obj.bounding_rect(), clip.bounding_rect());
+        assert!(obj.contains(&XZPoint::new(8, 1)));
+        assert!(obj.contains(&XZPoint::new(3, 8)));
+        assert!(obj.contains(&XZPoint::new(0, 10)));
+        assert!(!obj.contains(&XZPoint::new(8, 8)));
+        assert!(!obj.contains(&XZPoint::new(1, 3)));
+        assert!(!obj.contains(&XZPoint::new(-1, 3)));
+        assert!(!obj.contains(&XZPoint::new(8, 0)));
+        assert_eq!(obj.first_block(), XZPoint::new(0, 1));
+
+        // Moving keeps the shape
+        let moved = obj + XZVector { dx: 100, dz: -50 };
+        assert!(moved.contains(&XZPoint::new(108, -49)));
+        assert!(!moved.contains(&XZPoint::new(108, -42)));
+
+        // Polygons outside of the clip rectangle cover nothing
+        let outside = ring(&[(30, 30), (40, 30), (40, 40)]);
+        assert!(XZBBox::polygon_from_rings(vec![outside], &clip).is_err());
+    }
 }
@@ -0,0 +1,228 @@
+use super::geographic::LLPoint;
+use serde::{Deserialize, Serialize};
+
+/// WGS84 semi-major axis in meters
+const WGS84_A: f64 = 6_378_137.0;
+/// WGS84 flattening
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+/// Scale on the central meridian of UTM zones
+const UTM_K0: f64 = 0.9996;
+const UTM_FALSE_EASTING: f64 = 500_000.0;
+const UTM_FALSE_NORTHING_SOUTH: f64 = 10_000_000.0;
+
+/// Coordinate reference systems of external data, such as elevation models, identified by
+/// their EPSG code. Datums close to WGS84 (ETRS89, NAD83) are treated as WGS84.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Crs {
+    /// Longitude (x) and latitude (y) in degrees
+    Geographic,
+    /// Easting (x) and northing (y) in meters within a UTM zone
+    Utm { zone: u8, north: bool },
+}
+
+impl Crs {
+    pub fn from_epsg(code: u32) -> Result<Self, String> {
+        match code {
+            4326 | 4258 | 4269 => Ok(Self::Geographic),
+            32601..=32660 => Ok(Self::Utm {
+                zone: (code - 32600) as u8,
+                north: true,
+            }),
+            32701..=32760 => Ok(Self::Utm {
+                zone: (code - 32700) as u8,
+                north: false,
+            }),
+            25828..=25838 => Ok(Self::Utm {
+                zone: (code - 25800) as u8,
+                north: true,
+            }),
+            26901..=26923 => Ok(Self::Utm {
+                zone: (code - 26900) as u8,
+                north: true,
+            }),
+            _ => Err(format!(
+                "Unsupported coordinate system EPSG:{code}, only geographic WGS84/ETRS89/NAD83 \
+                 and their UTM zones are supported. Reproject the data, e.g. with gdalwarp."
+            )),
+        }
+    }
+
+    /// Parses an EPSG code like "EPSG:32632" or "32632"
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        let code = s.trim();
+        let code = code
+            .strip_prefix("EPSG:")
+            .or_else(|| code.strip_prefix("epsg:"))
+            .unwrap_or(code);
+        Self::from_epsg(
+            code.parse()
+                .map_err(|_| format!("Invalid EPSG code: {s}"))?,
+        )
+    }
+
+    /// Converts coordinates of this system to latitude and longitude
+    pub fn to_llpoint(self, x: f64, y: f64) -> Result<LLPoint, String> {
+        match self {
+            Self::Geographic => LLPoint::new(y, x),
+            Self::Utm { zone, north } => {
+                let (lat, lng) = utm_inverse(zone, north, x, y);
+                LLPoint::new(lat, lng)
+            }
+        }
+    }
+
+    /// Converts latitude and longitude to coordinates of this system
+    pub fn to_xy(self, llpoint: LLPoint) -> (f64, f64) {
+        match self {
+            Self::Geographic => (llpoint.lng(), llpoint.lat()),
+            Self::Utm { zone, north } => utm_forward(zone, north, llpoint.lat(), llpoint.lng()),
+        }
+    }
+}
+
+/// Coefficients of the Krüger series for the transverse Mercator projection, see
+/// Karney (2011), "Transverse Mercator with an accuracy of a few nanometers"
+struct Kruger {
+    /// Rectifying radius
+    a: f64,
+    alpha: [f64; 3],
+    beta: [f64; 3],
+    delta: [f64; 3],
+    /// Factor of the conformal latitude
+    e: f64,
+}
+
+fn kruger() -> Kruger {
+    let n = WGS84_F / (2.0 - WGS84_F);
+    let (n2, n3) = (n * n, n * n * n);
+    Kruger {
+        a: WGS84_A / (1.0 + n) * (1.0 + n2 / 4.0 + n2 * n2 / 64.0),
+        alpha: [
+            n / 2.0 - 2.0 * n2 / 3.0 + 5.0 * n3 / 16.0,
+            13.0 * n2 / 48.0 - 3.0 * n3 / 5.0,
+            61.0 * n3 / 240.0,
+        ],
+        beta: [
+            n / 2.0 - 2.0 * n2 / 3.0 + 37.0 * n3 / 96.0,
+            n2 / 48.0 + n3 / 15.0,
+            17.0 * n3 / 480.0,
+        ],
+        delta: [
+            2.0 * n - 2.0 * n2 / 3.0 - 2.0 * n3,
+            7.0 * n2 / 3.0 - 8.0 * n3 / 5.0,
+            56.0 * n3 / 15.0,
+        ],
+        e: 2.0 * n.sqrt() / (1.0 + n),
+    }
+}
+
+fn central_meridian(zone: u8) -> f64 {
+    (f64::from(zone) * 6.0 - 183.0).to_radians()
+}
+
+/// Returns easting and northing of a point in the given UTM zone
+fn utm_forward(zone: u8, north: bool, lat: f64, lng: f64) -> (f64, f64) {
+    let k = kruger();
+    let (lat, d_lng) = (lat.to_radians(), lng.to_radians() - central_meridian(zone));
+
+    let t = (lat.sin().atanh() - k.e * (k.e * lat.sin()).atanh()).sinh();
+    let xi = t.atan2(d_lng.cos());
+    let eta = (d_lng.sin() / (1.0 + t * t).sqrt()).atanh();
+
+    let (mut x, mut y) = (eta, xi);
+    for (j, alpha) in k.alpha.iter().enumerate() {
+        let j = 2.0 * (j + 1) as f64;
+        x += alpha * (j * xi).cos() * (j * eta).sinh();
+        y += alpha * (j * xi).sin() * (j * eta).cosh();
+    }
+
+    let false_northing = if north { 0.0 } else { UTM_FALSE_NORTHING_SOUTH };
+    (
+        UTM_FALSE_EASTING + UTM_K0 * k.a * x,
+        false_northing + UTM_K0 * k.a * y,
+    )
+}
+
+/// Returns latitude and longitude in degrees of a point in the given UTM zone
+fn utm_inverse(zone: u8, north: bool, easting: f64, northing: f64) -> (f64, f64) {
+    let k = kruger();
+    let false_northing = if north { 0.0 } else { UTM_FALSE_NORTHING_SOUTH };
+    let xi = (northing - false_northing) / (UTM_K0 * k.a);
+    let eta = (easting - UTM_FALSE_EASTING) / (UTM_K0 * k.a);
+
+    let (mut xi_prime, mut eta_prime) = (xi, eta);
+    for (j, beta) in k.beta.iter().enumerate() {
+        let j = 2.0 * (j + 1) as f64;
+        xi_prime -= beta * (j * xi).sin() * (j * eta).cosh();
+        eta_prime -= beta * (j * xi).cos() * (j * eta).sinh();
+    }
+
+    let chi = (xi_prime.sin() / eta_prime.cosh()).asin();
+    let mut lat = chi;
+    for (j, delta) in k.delta.iter().enumerate() {
+        lat += delta * (2.0 * (j + 1) as f64 * chi).sin();
+    }
+    let lng = central_meridian(zone) + eta_prime.sinh().atan2(xi_prime.cos());
+
+    (lat.to_degrees(), lng.to_degrees())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_epsg() {
+        assert_eq!(Crs::from_str("EPSG:4326"), Ok(Crs::Geographic));
+        assert_eq!(
+            Crs::from_str("25832"),
+            Ok(Crs::Utm {
+                zone: 32,
+                north: true
+            })
+        );
+        assert_eq!(
+            Crs::from_epsg(32756),
+            Ok(Crs::Utm {
+                zone: 56,
+                north: false
+            })
+        );
+        assert!(Crs::from_epsg(3857).is_err());
+        assert!(Crs::from_str("EPSG:x").is_err());
+    }
+
+    #[test]
+    fn test_utm() {
+        let crs = Crs::Utm {
+            zone: 32,
+            north: true,
+        };
+
+        // On the central meridian at the equator
+        let (x, y) = crs.to_xy(LLPoint::new(0.0, 9.0).unwrap());
+        assert!((x - 500_000.0).abs() < 1e-6 && y.abs() < 1e-6);
+
+        // One degree of latitude on the central meridian, about 110.6 km at the equator
+        let (_, y) = crs.to_xy(LLPoint::new(1.0, 9.0).unwrap());
+        assert!((y - 0.9996 * 110_574.0).abs() < 2.0, "{y}");
+
+        // Round trip
+        let llpoint = LLPoint::new(48.137, 11.575).unwrap();
+        let (x, y) = crs.to_xy(llpoint);
+        let back = crs.to_llpoint(x, y).unwrap();
+        assert!((back.lat() - llpoint.lat()).abs() < 1e-8);
+        assert!((back.lng() - llpoint.lng()).abs() < 1e-8);
+
+        // Southern hemisphere
+        let crs = Crs::Utm {
+            zone: 56,
+            north: false,
+        };
+        let llpoint = LLPoint::new(-33.86, 151.21).unwrap();
+        let (x, y) = crs.to_xy(llpoint);
+        assert!((6_200_000.0..6_300_000.0).contains(&y), "{y}");
+        let back = crs.to_llpoint(x, y).unwrap();
+        assert!((back.lat() - llpoint.lat()).abs() < 1e-8);
+    }
+}
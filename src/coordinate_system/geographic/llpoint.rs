@@ -21,6 +21,18 @@ impl LLPoint {
         Ok(Self { lat, lng })
     }
 
+    /// Parses "lat,lng", the order the bbox uses as well
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        let [lat, lng]: [f64; 2] = s
+            .split([',', ' '])
+            .map(|e| e.trim().parse::<f64>().map_err(|e| format!("{e}: {s}")))
+            .collect::<Result<Vec<_>, _>>()?
+            .try_into()
+            .map_err(|_| format!("Expected lat,lng: {s}"))?;
+
+        Self::new(lat, lng)
+    }
+
     pub fn lat(&self) -> f64 {
         self.lat
     }
@@ -57,4 +69,15 @@ mod tests {
         assert!(LLPoint::new(0., -181.).is_err());
         assert!(LLPoint::new(0., 181.).is_err());
     }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(
+            LLPoint::from_str("48.1,-11.5").unwrap(),
+            LLPoint::new(48.1, -11.5).unwrap()
+        );
+        assert!(LLPoint::from_str("48.1").is_err());
+        assert!(LLPoint::from_str("48.1,x").is_err());
+        assert!(LLPoint::from_str("95,0").is_err());
+    }
 }
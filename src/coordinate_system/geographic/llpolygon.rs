@@ -0,0 +1,187 @@
+use super::llpoint::LLPoint;
+use serde_json::Value;
+
+/// An area outlined by one or more rings, rings inside other rings are holes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LLPolygon {
+    rings: Vec<Vec<LLPoint>>,
+}
+
+impl LLPolygon {
+    pub fn new(rings: Vec<Vec<LLPoint>>) -> Result<Self, String> {
+        if rings.iter().all(|ring| ring.len() < 3) {
+            return Err("Invalid LLPolygon: no ring with at least 3 points".to_string());
+        }
+        Ok(Self { rings })
+    }
+
+    /// Reads the Polygon and MultiPolygon geometries of a GeoJSON geometry, feature or
+    /// feature collection
+    pub fn from_geojson(geojson: &Value) -> Result<Self, String> {
+        let mut rings = Vec::new();
+        collect_geojson_rings(geojson, &mut rings)?;
+        Self::new(rings)
+    }
+
+    /// Reads the outline of a relation from an Overpass response with `out geom`, joining
+    /// the member ways to rings
+    pub fn from_relation_geometry(data: &Value, relation_id: u64) -> Result<Self, String> {
+        let relation = data["elements"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|element| {
+                element["type"] == "relation" && element["id"].as_u64() == Some(relation_id)
+            })
+            .ok_or_else(|| format!("Relation {relation_id} not found"))?;
+
+        let mut segments = Vec::new();
+        for member in relation["members"].as_array().into_iter().flatten() {
+            if member["type"] != "way"
+                || !matches!(member["role"].as_str(), Some("outer" | "inner"))
+            {
+                continue;
+            }
+            let segment = member["geometry"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|point| {
+                    let (Some(lat), Some(lng)) = (point["lat"].as_f64(), point["lon"].as_f64())
+                    else {
+                        return Err(format!("Invalid geometry of relation {relation_id}"));
+                    };
+                    LLPoint::new(lat, lng)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            segments.push(segment);
+        }
+
+        Self::new(join_segments(segments))
+    }
+
+    pub fn rings(&self) -> &[Vec<LLPoint>] {
+        &self.rings
+    }
+}
+
+fn collect_geojson_rings(geojson: &Value, rings: &mut Vec<Vec<LLPoint>>) -> Result<(), String> {
+    let polygon_rings = |polygon: &Value| -> Result<Vec<Vec<LLPoint>>, String> {
+        polygon
+            .as_array()
+            .ok_or("Invalid GeoJSON polygon")?
+            .iter()
+            .map(|ring| {
+                ring.as_array()
+                    .ok_or("Invalid GeoJSON ring")?
+                    .iter()
+                    .map(
+                        |position| match (position[0].as_f64(), position[1].as_f64()) {
+                            (Some(lng), Some(lat)) => LLPoint::new(lat, lng),
+                            _ => Err("Invalid GeoJSON position".to_string()),
+                        },
+                    )
+                    .collect()
+            })
+            .collect()
+    };
+
+    match geojson["type"].as_str() {
+        Some("FeatureCollection") => {
+            for feature in geojson["features"].as_array().into_iter().flatten() {
+                collect_geojson_rings(feature, rings)?;
+            }
+        }
+        Some("Feature") => collect_geojson_rings(&geojson["geometry"], rings)?,
+        Some("Polygon") => rings.extend(polygon_rings(&geojson["coordinates"])?),
+        Some("MultiPolygon") => {
+            for polygon in geojson["coordinates"].as_array().into_iter().flatten() {
+                rings.extend(polygon_rings(polygon)?);
+            }
+        }
+        // Other geometries, such as points, outline no area
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Joins ways to rings where their end points meet. Ways that do not close a ring are kept
+/// as open rings.
+fn join_segments(mut segments: Vec<Vec<LLPoint>>) -> Vec<Vec<LLPoint>> {
+    segments.retain(|segment| !segment.is_empty());
+
+    let mut rings = Vec::new();
+    while let Some(mut ring) = segments.pop() {
+        while ring.first() != ring.last() {
+            let end = *ring.last().unwrap();
+            let Some(next) = segments
+                .iter()
+                .position(|segment| segment[0] == end || segment[segment.len() - 1] == end)
+            else {
+                break;
+            };
+            let mut segment = segments.swap_remove(next);
+            if segment[0] != end {
+                segment.reverse();
+            }
+            ring.extend_from_slice(&segment[1..]);
+        }
+        rings.push(ring);
+    }
+    rings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_geojson() {
+        let geojson = json!({
+            "type": "FeatureCollection",
+            "features": [{
+                "type": "Feature",
+                "properties": {},
+                "geometry": {
+                    "type": "MultiPolygon",
+                    "coordinates": [
+                        [[[11.5, 48.1], [11.6, 48.1], [11.6, 48.2], [11.5, 48.1]]],
+                        [[[11.7, 48.1], [11.8, 48.1], [11.8, 48.2], [11.7, 48.1]]]
+                    ]
+                }
+            }]
+        });
+        let polygon = LLPolygon::from_geojson(&geojson).unwrap();
+        assert_eq!(polygon.rings().len(), 2);
+        assert_eq!(polygon.rings()[0][1], LLPoint::new(48.1, 11.6).unwrap());
+
+        assert!(
+            LLPolygon::from_geojson(&json!({ "type": "Point", "coordinates": [0, 0] })).is_err()
+        );
+    }
+
+    #[test]
+    fn test_from_relation_geometry() {
+        let point = |lat: f64, lon: f64| json!({ "lat": lat, "lon": lon });
+        let data = json!({
+            "elements": [{
+                "type": "relation",
+                "id": 42,
+                "members": [
+                    { "type": "way", "role": "outer", "geometry": [point(0.0, 0.0), point(0.0, 1.0)] },
+                    { "type": "node", "role": "admin_centre", "lat": 0.5, "lon": 0.5 },
+                    { "type": "way", "role": "outer", "geometry": [point(1.0, 1.0), point(0.0, 1.0)] },
+                    { "type": "way", "role": "outer", "geometry": [point(1.0, 1.0), point(0.0, 0.0)] }
+                ]
+            }]
+        });
+        let polygon = LLPolygon::from_relation_geometry(&data, 42).unwrap();
+        assert_eq!(polygon.rings().len(), 1);
+        let ring = &polygon.rings()[0];
+        assert_eq!(ring.len(), 4);
+        assert_eq!(ring.first(), ring.last());
+
+        assert!(LLPolygon::from_relation_geometry(&data, 7).is_err());
+    }
+}
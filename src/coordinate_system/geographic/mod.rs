@@ -1,5 +1,7 @@
 mod llbbox;
 mod llpoint;
+mod llpolygon;
 
 pub use llbbox::LLBBox;
 pub use llpoint::LLPoint;
+pub use llpolygon::LLPolygon;
@@ -1,3 +1,5 @@
 pub mod cartesian;
+pub mod crs;
 pub mod geographic;
+pub mod projection;
 pub mod transformation;
@@ -0,0 +1,107 @@
+use super::geographic::LLPoint;
+use super::transformation::{lat_distance, lon_distance, EARTH_RADIUS};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::FRAC_PI_4;
+
+/// Map projection from geographic space to a plane, in meters east and north of a
+/// reference point. All projections use a spherical earth.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+pub enum Projection {
+    /// Degrees scaled by the length of a degree of latitude and longitude, the original
+    /// projection. Stretches shapes the further they are from the reference latitude.
+    #[default]
+    Equirectangular,
+    /// Transverse Mercator with the central meridian through the reference point, like UTM
+    /// without zones. Conformal, so shapes keep their aspect ratio.
+    TransverseMercator,
+    /// Web Mercator as used by web maps, scaled to true distances at the reference latitude
+    WebMercator,
+    /// Plane touching the earth at the reference point (east, north, up)
+    LocalTangentPlane,
+}
+
+impl Projection {
+    /// Returns the position of `llpoint` in meters east and north of `reference`
+    pub fn project(self, llpoint: LLPoint, reference: LLPoint) -> (f64, f64) {
+        let (lat, lng) = (llpoint.lat().to_radians(), llpoint.lng().to_radians());
+        let (lat0, lng0) = (reference.lat().to_radians(), reference.lng().to_radians());
+        let d_lng = lng - lng0;
+
+        match self {
+            Self::Equirectangular => (
+                (llpoint.lng() - reference.lng()) * lon_distance(reference.lat(), 0.0, 1.0),
+                (llpoint.lat() - reference.lat()) * lat_distance(0.0, 1.0),
+            ),
+            Self::TransverseMercator => {
+                let b = lat.cos() * d_lng.sin();
+                let east = EARTH_RADIUS * b.atanh();
+                let north = EARTH_RADIUS * (lat.tan().atan2(d_lng.cos()) - lat0);
+                (east, north)
+            }
+            Self::WebMercator => {
+                let mercator_y = |lat: f64| (FRAC_PI_4 + lat / 2.0).tan().ln();
+                let scale = EARTH_RADIUS * lat0.cos();
+                (scale * d_lng, scale * (mercator_y(lat) - mercator_y(lat0)))
+            }
+            Self::LocalTangentPlane => {
+                let ecef = |lat: f64, lng: f64| {
+                    [
+                        EARTH_RADIUS * lat.cos() * lng.cos(),
+                        EARTH_RADIUS * lat.cos() * lng.sin(),
+                        EARTH_RADIUS * lat.sin(),
+                    ]
+                };
+                let (p, p0) = (ecef(lat, lng), ecef(lat0, lng0));
+                let [dx, dy, dz] = [p[0] - p0[0], p[1] - p0[1], p[2] - p0[2]];
+                let east = -lng0.sin() * dx + lng0.cos() * dy;
+                let north =
+                    -lat0.sin() * lng0.cos() * dx - lat0.sin() * lng0.sin() * dy + lat0.cos() * dz;
+                (east, north)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_projections_agree_near_reference() {
+        let reference = LLPoint::new(60.0, 10.0).unwrap();
+        // About 1 km north and 1 km east
+        let llpoint = LLPoint::new(60.009, 10.018).unwrap();
+
+        for projection in Projection::value_variants() {
+            let (east, north) = projection.project(reference, reference);
+            assert!(east.abs() < 1e-6 && north.abs() < 1e-6, "{projection:?}");
+            let (east, north) = projection.project(llpoint, reference);
+            assert!((east - 1000.0).abs() < 10.0, "{projection:?}: {east}");
+            assert!((north - 1000.0).abs() < 10.0, "{projection:?}: {north}");
+        }
+    }
+
+    #[test]
+    fn test_conformal_aspect_ratio() {
+        // A square of 0.2° at 71°N, north of the reference point
+        let reference = LLPoint::new(70.0, 21.1).unwrap();
+        let corner = |lat: f64, lng: f64| LLPoint::new(lat, lng).unwrap();
+        let size = |projection: Projection| {
+            let (west, south) = projection.project(corner(70.9, 21.0), reference);
+            let (east, north) = projection.project(corner(71.1, 21.2), reference);
+            (east - west, north - south)
+        };
+
+        // Conformal projections keep the ratio of the real side lengths, cos(71°) : 1
+        for projection in [Projection::TransverseMercator, Projection::WebMercator] {
+            let (width, height) = size(projection);
+            let ratio = width / height;
+            assert!((ratio - 71f64.to_radians().cos()).abs() < 0.01, "{ratio}");
+        }
+
+        // Equirectangular uses the length of a degree of longitude at the reference latitude
+        let (width, height) = size(Projection::Equirectangular);
+        assert!((width / height - 70f64.to_radians().cos()).abs() < 0.01);
+    }
+}
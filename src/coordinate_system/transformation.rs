@@ -1,5 +1,13 @@
 use super::cartesian::{XZBBox, XZPoint};
 use super::geographic::{LLBBox, LLPoint};
+use super::projection::Projection;
+
+/// Mean earth radius in meters
+pub(super) const EARTH_RADIUS: f64 = 6_371_000.0;
+
+/// Points sampled along each edge of the llbbox to find the extent of its projection, whose
+/// edges may be curved
+const EDGE_SAMPLES: usize = 64;
 
 /// Transform geographic space (within llbbox) to a local tangential cartesian space (within xzbbox)
 pub struct CoordTransformer {
@@ -9,6 +17,18 @@ pub struct CoordTransformer {
     scale_factor_z: f64,
     min_lat: f64,
     min_lng: f64,
+    /// Map projection used instead of the bbox relative transformation above, see
+    /// `llbbox_to_xzbbox_projected`
+    projected: Option<ProjectedTransform>,
+}
+
+struct ProjectedTransform {
+    projection: Projection,
+    reference: LLPoint,
+    scale: f64,
+    /// Projected block coordinates at x = 0, z = 0
+    offset_x: f64,
+    offset_z: f64,
 }
 
 impl CoordTransformer {
@@ -45,21 +65,126 @@ impl CoordTransformer {
                 scale_factor_z,
                 min_lat: llbbox.min().lat(),
                 min_lng: llbbox.min().lng(),
+                projected: None,
             },
             xzbbox,
         ))
     }
 
-    pub fn transform_point(&self, llpoint: LLPoint) -> XZPoint {
+    /// Transforms with a map projection, `scale` blocks per projected meter.
+    ///
+    /// Without `origin` the projection is centered on the llbbox and its projected extent
+    /// starts at x = 0, z = 0, like in `llbbox_to_xzbbox`. The equirectangular projection
+    /// without origin is the transformation of `llbbox_to_xzbbox`.
+    ///
+    /// With `origin` the projection is centered on it and it lands at x = 0, z = 0. Every bbox
+    /// transformed with the same origin, projection and scale lands at its position relative
+    /// to the others, so separate generations stitch into one continuous world. The xzbbox
+    /// then excludes the blocks on the east and south edge, which belong to the neighbouring
+    /// bboxes.
+    pub fn llbbox_to_xzbbox_projected(
+        llbbox: &LLBBox,
+        scale: f64,
+        projection: Projection,
+        origin: Option<LLPoint>,
+    ) -> Result<(CoordTransformer, XZBBox), String> {
+        if projection == Projection::Equirectangular && origin.is_none() {
+            return Self::llbbox_to_xzbbox(llbbox, scale);
+        }
+
+        let err_header = format!("Construct {projection:?} LLBBox to XZBBox transformation failed");
+
+        if scale <= 0.0 {
+            return Err(format!("{}: scale <= 0.0", &err_header));
+        }
+
+        let reference = match origin {
+            Some(origin) => origin,
+            None => LLPoint::new(
+                (llbbox.min().lat() + llbbox.max().lat()) / 2.0,
+                (llbbox.min().lng() + llbbox.max().lng()) / 2.0,
+            )?,
+        };
+        let mut transformer = Self {
+            len_lat: llbbox.max().lat() - llbbox.min().lat(),
+            len_lng: llbbox.max().lng() - llbbox.min().lng(),
+            scale_factor_x: 0.0,
+            scale_factor_z: 0.0,
+            min_lat: llbbox.min().lat(),
+            min_lng: llbbox.min().lng(),
+            projected: Some(ProjectedTransform {
+                projection,
+                reference,
+                scale,
+                offset_x: 0.0,
+                offset_z: 0.0,
+            }),
+        };
+
+        // Extent of the projected llbbox, sampled along its edges
+        let (mut min_x, mut min_z) = (f64::MAX, f64::MAX);
+        let (mut max_x, mut max_z) = (f64::MIN, f64::MIN);
+        for i in 0..=EDGE_SAMPLES {
+            let t = i as f64 / EDGE_SAMPLES as f64;
+            let lat = llbbox.min().lat() + t * transformer.len_lat;
+            let lng = llbbox.min().lng() + t * transformer.len_lng;
+            for (lat, lng) in [
+                (lat, llbbox.min().lng()),
+                (lat, llbbox.max().lng()),
+                (llbbox.min().lat(), lng),
+                (llbbox.max().lat(), lng),
+            ] {
+                let (x, z) = transformer.transform_point_exact(LLPoint::new(lat, lng)?);
+                (min_x, min_z) = (min_x.min(x), min_z.min(z));
+                (max_x, max_z) = (max_x.max(x), max_z.max(z));
+            }
+        }
+        transformer.scale_factor_x = (max_x - min_x).floor();
+        transformer.scale_factor_z = (max_z - min_z).floor();
+
+        let xzbbox = if origin.is_some() {
+            let min = XZPoint::new(min_x.floor() as i32, min_z.floor() as i32);
+            let max = XZPoint::new(max_x.floor() as i32 - 1, max_z.floor() as i32 - 1);
+            XZBBox::rect_from_min_max(min, XZPoint::new(max.x.max(min.x), max.z.max(min.z)))
+        } else {
+            let projected = transformer.projected.as_mut().unwrap();
+            projected.offset_x = min_x;
+            projected.offset_z = min_z;
+            XZBBox::rect_from_xz_lengths(transformer.scale_factor_x, transformer.scale_factor_z)
+        }
+        .map_err(|e| format!("{}:\n{}", &err_header, e))?;
+
+        Ok((transformer, xzbbox))
+    }
+
+    /// Minecraft coordinates of the point before rounding to blocks
+    pub fn transform_point_exact(&self, llpoint: LLPoint) -> (f64, f64) {
+        if let Some(projected) = &self.projected {
+            let (east, north) = projected.projection.project(llpoint, projected.reference);
+            return (
+                east * projected.scale - projected.offset_x,
+                -north * projected.scale - projected.offset_z,
+            );
+        }
+
         // Calculate the relative position within the bounding box
         let rel_x: f64 = (llpoint.lng() - self.min_lng) / self.len_lng;
         let rel_z: f64 = 1.0 - (llpoint.lat() - self.min_lat) / self.len_lat;
 
-        // Apply scaling factors for each dimension and convert to Minecraft coordinates
-        let x: i32 = (rel_x * self.scale_factor_x) as i32;
-        let z: i32 = (rel_z * self.scale_factor_z) as i32;
+        // Apply scaling factors for each dimension
+        (rel_x * self.scale_factor_x, rel_z * self.scale_factor_z)
+    }
 
-        XZPoint::new(x, z)
+    pub fn transform_point(&self, llpoint: LLPoint) -> XZPoint {
+        let (x, z) = self.transform_point_exact(llpoint);
+
+        if self.projected.is_some() {
+            // Round down, so that negative coordinates don't share the blocks at x = 0 and z = 0
+            return XZPoint::new(x.floor() as i32, z.floor() as i32);
+        }
+
+        // Convert to Minecraft coordinates
+        XZPoint::new(x as i32, z as i32)
     }
 }
 
@@ -76,8 +201,8 @@ pub fn geo_distance(a: LLPoint, b: LLPoint) -> (f64, f64) {
 
 // Haversine but optimized for a latitude delta of 0
 // returns meters
-fn lon_distance(lat: f64, lon1: f64, lon2: f64) -> f64 {
-    const R: f64 = 6_371_000.0;
+pub(super) fn lon_distance(lat: f64, lon1: f64, lon2: f64) -> f64 {
+    const R: f64 = EARTH_RADIUS;
     let d_lon: f64 = (lon2 - lon1).to_radians();
     let a: f64 =
         lat.to_radians().cos() * lat.to_radians().cos() * (d_lon / 2.0).sin() * (d_lon / 2.0).sin();
@@ -88,8 +213,8 @@ fn lon_distance(lat: f64, lon1: f64, lon2: f64) -> f64 {
 
 // Haversine but optimized for a longitude delta of 0
 // returns meters
-fn lat_distance(lat1: f64, lat2: f64) -> f64 {
-    const R: f64 = 6_371_000.0;
+pub(super) fn lat_distance(lat1: f64, lat2: f64) -> f64 {
+    const R: f64 = EARTH_RADIUS;
     let d_lat: f64 = (lat2 - lat1).to_radians();
     let a: f64 = (d_lat / 2.0).sin() * (d_lat / 2.0).sin();
     let c: f64 = 2.0 * a.sqrt().atan2((1.0 - a).sqrt());
@@ -123,6 +248,7 @@ pub fn lat_lon_to_minecraft_coords(
 mod test {
     use super::*;
     use crate::test_utilities::get_llbbox_arnis;
+    use clap::ValueEnum;
 
     fn test_llxztransform_one_scale_one_factor(
         scale: f64,
@@ -172,6 +298,69 @@ mod test {
         test_llxztransform_one_scale_one_factor(0.1, 0.2, 0.7);
     }
 
+    // this ensures that neighbouring bboxes with the same origin share their edge
+    #[test]
+    pub fn test_anchored() {
+        let origin = LLPoint::new(48.1, 11.5).unwrap();
+        let west = LLBBox::new(48.1, 11.5, 48.11, 11.51).unwrap();
+        let east = LLBBox::new(48.1, 11.51, 48.11, 11.52).unwrap();
+        let south = LLBBox::new(48.09, 11.5, 48.1, 11.51).unwrap();
+
+        // Projections with straight meridians and parallels
+        for projection in [Projection::Equirectangular, Projection::WebMercator] {
+            let transform = |llbbox: &LLBBox| {
+                CoordTransformer::llbbox_to_xzbbox_projected(llbbox, 2.0, projection, Some(origin))
+                    .unwrap()
+            };
+            let (transformer, west_xz) = transform(&west);
+            let (other, east_xz) = transform(&east);
+            let (_, south_xz) = transform(&south);
+
+            assert_eq!(transformer.transform_point(origin), XZPoint::new(0, 0));
+            assert_eq!(west_xz.min_x(), 0);
+            assert_eq!(west_xz.max_z(), -1);
+            assert_eq!(east_xz.min_x(), west_xz.max_x() + 1);
+            assert_eq!(east_xz.min_z(), west_xz.min_z());
+            assert_eq!(south_xz.min_z(), 0);
+            assert_eq!(south_xz.min_x(), west_xz.min_x());
+
+            // Roughly 2 blocks per meter, 0.01° of latitude are about 1112 m
+            assert!((2220..2230).contains(&(west_xz.max_z() - west_xz.min_z())));
+
+            // A point is transformed the same way for every bbox
+            let llpoint = LLPoint::new(48.105, 11.509).unwrap();
+            assert_eq!(
+                transformer.transform_point(llpoint),
+                other.transform_point(llpoint)
+            );
+        }
+    }
+
+    // this ensures that projections without origin cover the bbox from x = 0, z = 0 on
+    #[test]
+    pub fn test_projected() {
+        let llbbox = get_llbbox_arnis();
+        let center = LLPoint::new(
+            (llbbox.min().lat() + llbbox.max().lat()) / 2.0,
+            (llbbox.min().lng() + llbbox.max().lng()) / 2.0,
+        )
+        .unwrap();
+        let (_, legacy) = CoordTransformer::llbbox_to_xzbbox(&llbbox, 1.0).unwrap();
+
+        for &projection in Projection::value_variants() {
+            let (transformer, xzbbox) =
+                CoordTransformer::llbbox_to_xzbbox_projected(&llbbox, 1.0, projection, None)
+                    .unwrap();
+            assert_eq!((xzbbox.min_x(), xzbbox.min_z()), (0, 0));
+            assert!((xzbbox.max_x() - legacy.max_x()).abs() <= 2);
+            assert!((xzbbox.max_z() - legacy.max_z()).abs() <= 2);
+
+            let xzpoint = transformer.transform_point(center);
+            assert!((xzpoint.x - xzbbox.max_x() / 2).abs() <= 1);
+            assert!((xzpoint.z - xzbbox.max_z() / 2).abs() <= 1);
+        }
+    }
+
     // this ensures that invalid inputs can be handled correctly
     #[test]
     pub fn test_invalid_construct() {
@@ -181,5 +370,13 @@ mod test {
 
         let obj = CoordTransformer::llbbox_to_xzbbox(&llbbox, -1.2);
         assert!(obj.is_err());
+
+        let obj = CoordTransformer::llbbox_to_xzbbox_projected(
+            &llbbox,
+            0.0,
+            Projection::TransverseMercator,
+            None,
+        );
+        assert!(obj.is_err());
     }
 }
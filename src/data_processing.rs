@@ -1,14 +1,23 @@
 use crate::args::Args;
-use crate::block_definitions::{BEDROCK, DIRT, GRASS_BLOCK, STONE};
+use crate::block_definitions::{Block, BEDROCK, DIRT, GRASS_BLOCK, STONE};
+use crate::checkpoint::Checkpoint;
 use crate::coordinate_system::cartesian::XZBBox;
 use crate::coordinate_system::geographic::LLBBox;
+use crate::element_processing::coastline::{self, Ocean};
+use crate::element_processing::highways::StreetSign;
 use crate::element_processing::*;
+use crate::floodfill;
 use crate::ground::Ground;
 use crate::osm_parser::ProcessedElement;
 use crate::progress::emit_gui_progress_update;
+use crate::render;
+use crate::tiling::TileGrid;
 use crate::world_editor::WorldEditor;
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
 
 pub const MIN_Y: i32 = -64;
 
@@ -16,16 +25,131 @@ pub fn generate_world(
     elements: Vec<ProcessedElement>,
     xzbbox: XZBBox,
     llbbox: LLBBox,
-    ground: Ground,
+    mut ground: Ground,
     args: &Args,
+    checkpoint: Option<&Checkpoint>,
 ) -> Result<(), String> {
     let mut editor: WorldEditor = WorldEditor::new(args.path.clone(), &xzbbox, llbbox);
 
     println!("{} Processing data...", "[4/7]".bold());
 
+    // Shape the terrain below water first, so rivers run downhill and lakes are level
+    if ground.elevation_enabled {
+        waterways::carve_riverbeds(&mut ground, &elements, &xzbbox);
+        water_areas::level_lakes(&mut ground, &elements, &xzbbox);
+    }
+
     // Set ground reference in the editor to enable elevation-aware block placement
     editor.set_ground(&ground);
+    editor.set_update_existing(args.update);
+    editor.set_version(args.mc_version);
+    if !args.export_only {
+        editor
+            .set_format(args.format)
+            .map_err(|e| format!("Failed to create Bedrock world: {e}"))?;
+    }
+
+    let street_signs = highways::street_signs(&elements, args.scale);
+    let ocean = Ocean::from_elements(&elements, &xzbbox, args);
+
+    if args.tile_size.is_some() || args.parallel {
+        let regions_per_tile = args.tile_size.unwrap_or(1);
+        generate_tiled(
+            &mut editor,
+            &elements,
+            &street_signs,
+            ocean.as_ref(),
+            &xzbbox,
+            regions_per_tile,
+            args,
+            checkpoint,
+        );
+    } else {
+        process_elements(&mut editor, &elements, &street_signs, args);
+        generate_ground(&mut editor, &xzbbox, ocean.as_ref(), args);
+    }
+
+    // Set sign for player orientation
+    /*editor.set_sign(
+        &[
+            "↑".to_string(),
+            "Generated World".to_string(),
+            "This direction".to_string(),
+            "".to_string(),
+        ],
+        9,
+        -61,
+        9,
+        6,
+    );*/
+
+    // Save world before exporting and rendering, so a failed export doesn't lose it
+    if !args.export_only {
+        editor.save();
+    }
+
+    if let Some(export_path) = &args.export {
+        println!("Exporting schematic to {}...", export_path.display());
+        match editor.to_schematic() {
+            Some(schematic) => schematic.save(export_path, args.mc_version)?,
+            None => eprintln!("Warning: No blocks were generated, skipping schematic export"),
+        }
+    }
 
+    if args.render.is_some() || args.render_tiles.is_some() {
+        println!("Rendering map...");
+        match editor.render_top_down() {
+            Some(image) => {
+                if let Some(render_path) = &args.render {
+                    image.save(render_path).map_err(|e| {
+                        format!("Failed to save render {}: {e}", render_path.display())
+                    })?;
+                }
+                if let Some(tiles_dir) = &args.render_tiles {
+                    render::save_tiles(&image, tiles_dir, editor.get_min_coords())?;
+                }
+            }
+            None => eprintln!("Warning: No blocks were generated, skipping render"),
+        }
+    }
+
+    // Update player spawn Y coordinate based on terrain height after generation
+    #[cfg(feature = "gui")]
+    if let Some(spawn_coords) = &args.spawn_point {
+        use crate::gui::update_player_spawn_y_after_generation;
+        let bbox_string = format!(
+            "{},{},{},{}",
+            args.bbox.min().lng(),
+            args.bbox.min().lat(),
+            args.bbox.max().lng(),
+            args.bbox.max().lat()
+        );
+
+        if let Err(e) = update_player_spawn_y_after_generation(
+            &args.path,
+            Some(*spawn_coords),
+            bbox_string,
+            args.scale,
+            args.projection,
+            args.origin,
+            &ground,
+        ) {
+            eprintln!("Warning: Failed to update spawn point Y coordinate: {e}");
+        }
+    }
+
+    emit_gui_progress_update(100.0, "Done! World generation completed.");
+    println!("{}", "Done! World generation completed.".green().bold());
+    Ok(())
+}
+
+/// Processes all elements in order and places the street signs (non-tiled generation)
+fn process_elements(
+    editor: &mut WorldEditor,
+    elements: &[ProcessedElement],
+    street_signs: &[StreetSign],
+    args: &Args,
+) {
     println!("{} Processing terrain...", "[5/7]".bold());
     emit_gui_progress_update(25.0, "Processing terrain...");
 
@@ -41,7 +165,7 @@ pub fn generate_world(
     let mut current_progress_prcs: f64 = 25.0;
     let mut last_emitted_progress: f64 = current_progress_prcs;
 
-    for element in &elements {
+    for element in elements {
         process_pb.inc(1);
         current_progress_prcs += progress_increment_prcs;
         if (current_progress_prcs - last_emitted_progress).abs() > 0.25 {
@@ -59,84 +183,16 @@ pub fn generate_world(
             process_pb.set_message("");
         }
 
-        match element {
-            ProcessedElement::Way(way) => {
-                if way.tags.contains_key("building") || way.tags.contains_key("building:part") {
-                    buildings::generate_buildings(&mut editor, way, args, None);
-                } else if way.tags.contains_key("highway") {
-                    highways::generate_highways(&mut editor, element, args, &elements);
-                } else if way.tags.contains_key("landuse") {
-                    landuse::generate_landuse(&mut editor, way, args);
-                } else if way.tags.contains_key("natural") {
-                    natural::generate_natural(&mut editor, element, args);
-                } else if way.tags.contains_key("amenity") {
-                    amenities::generate_amenities(&mut editor, element, args);
-                } else if way.tags.contains_key("leisure") {
-                    leisure::generate_leisure(&mut editor, way, args);
-                } else if way.tags.contains_key("barrier") {
-                    barriers::generate_barriers(&mut editor, element);
-                } else if way.tags.contains_key("waterway") {
-                    waterways::generate_waterways(&mut editor, way);
-                } else if way.tags.contains_key("bridge") {
-                    //bridges::generate_bridges(&mut editor, way, ground_level); // TODO FIX
-                } else if way.tags.contains_key("railway") {
-                    railways::generate_railways(&mut editor, way);
-                } else if way.tags.contains_key("roller_coaster") {
-                    railways::generate_roller_coaster(&mut editor, way);
-                } else if way.tags.contains_key("aeroway") || way.tags.contains_key("area:aeroway")
-                {
-                    highways::generate_aeroway(&mut editor, way, args);
-                } else if way.tags.get("service") == Some(&"siding".to_string()) {
-                    highways::generate_siding(&mut editor, way);
-                } else if way.tags.contains_key("man_made") {
-                    man_made::generate_man_made(&mut editor, element, args);
-                }
-            }
-            ProcessedElement::Node(node) => {
-                if node.tags.contains_key("door") || node.tags.contains_key("entrance") {
-                    doors::generate_doors(&mut editor, node);
-                } else if node.tags.contains_key("natural")
-                    && node.tags.get("natural") == Some(&"tree".to_string())
-                {
-                    natural::generate_natural(&mut editor, element, args);
-                } else if node.tags.contains_key("amenity") {
-                    amenities::generate_amenities(&mut editor, element, args);
-                } else if node.tags.contains_key("barrier") {
-                    barriers::generate_barrier_nodes(&mut editor, node);
-                } else if node.tags.contains_key("highway") {
-                    highways::generate_highways(&mut editor, element, args, &elements);
-                } else if node.tags.contains_key("tourism") {
-                    tourisms::generate_tourisms(&mut editor, node);
-                } else if node.tags.contains_key("man_made") {
-                    man_made::generate_man_made_nodes(&mut editor, node);
-                }
-            }
-            ProcessedElement::Relation(rel) => {
-                if rel.tags.contains_key("building") || rel.tags.contains_key("building:part") {
-                    buildings::generate_building_from_relation(&mut editor, rel, args);
-                } else if rel.tags.contains_key("water")
-                    || rel.tags.get("natural") == Some(&"water".to_string())
-                {
-                    water_areas::generate_water_areas(&mut editor, rel);
-                } else if rel.tags.contains_key("natural") {
-                    natural::generate_natural_from_relation(&mut editor, rel, args);
-                } else if rel.tags.contains_key("landuse") {
-                    landuse::generate_landuse_from_relation(&mut editor, rel, args);
-                } else if rel.tags.get("leisure") == Some(&"park".to_string()) {
-                    leisure::generate_leisure_from_relation(&mut editor, rel, args);
-                } else if rel.tags.contains_key("man_made") {
-                    man_made::generate_man_made(
-                        &mut editor,
-                        &ProcessedElement::Relation(rel.clone()),
-                        args,
-                    );
-                }
-            }
-        }
+        process_element(editor, element, args, elements);
     }
 
+    highways::generate_street_signs(editor, street_signs);
+
     process_pb.finish();
+}
 
+/// Generates the ground layer of the whole bbox (non-tiled generation)
+fn generate_ground(editor: &mut WorldEditor, xzbbox: &XZBBox, ocean: Option<&Ocean>, args: &Args) {
     // Generate ground layer
     let total_blocks: u64 = xzbbox.bounding_rect().total_blocks();
     let desired_updates: u64 = 1500;
@@ -164,30 +220,7 @@ pub fn generate_world(
 
     for x in xzbbox.min_x()..=xzbbox.max_x() {
         for z in xzbbox.min_z()..=xzbbox.max_z() {
-            // Add default dirt and grass layer if there isn't a stone layer already
-            if !editor.check_for_block(x, 0, z, Some(&[STONE])) {
-                editor.set_block(groundlayer_block, x, 0, z, None, None);
-                editor.set_block(DIRT, x, -1, z, None, None);
-                editor.set_block(DIRT, x, -2, z, None, None);
-            }
-
-            // Fill underground with stone
-            if args.fillground {
-                // Fill from bedrock+1 to 3 blocks below ground with stone
-                editor.fill_blocks_absolute(
-                    STONE,
-                    x,
-                    MIN_Y + 1,
-                    z,
-                    x,
-                    editor.get_absolute_y(x, -3, z),
-                    z,
-                    None,
-                    None,
-                );
-            }
-            // Generate a bedrock level at MIN_Y
-            editor.set_block_absolute(BEDROCK, x, MIN_Y, z, None, Some(&[BEDROCK]));
+            generate_ground_column(editor, x, z, groundlayer_block, ocean, args);
 
             block_counter += 1;
             if block_counter % batch_size == 0 {
@@ -202,48 +235,338 @@ pub fn generate_world(
         }
     }
 
-    // Set sign for player orientation
-    /*editor.set_sign(
-        "↑".to_string(),
-        "Generated World".to_string(),
-        "This direction".to_string(),
-        "".to_string(),
-        9,
-        -61,
-        9,
-        6,
-    );*/
-
     ground_pb.inc(block_counter % batch_size);
     ground_pb.finish();
+}
 
-    // Save world
-    editor.save();
+/// Generates the world tile by tile: only the elements intersecting a tile are processed.
+/// With `--tile-size` the regions of each tile are written to disk right after, keeping memory
+/// bounded for very large areas. With `--parallel` tiles are generated concurrently, each into
+/// its own editor, and merged afterwards. Elements keep their priority order within a tile
+/// and tiles never share regions. Processors that check for existing blocks (`block_at`,
+/// `check_for_block`) don't see blocks of neighboring tiles though, so features at tile
+/// borders may differ slightly from a run without tiles.
+#[allow(clippy::too_many_arguments)]
+fn generate_tiled(
+    editor: &mut WorldEditor,
+    elements: &[ProcessedElement],
+    street_signs: &[StreetSign],
+    ocean: Option<&Ocean>,
+    xzbbox: &XZBBox,
+    regions_per_tile: u32,
+    args: &Args,
+    checkpoint: Option<&Checkpoint>,
+) {
+    println!("{} Indexing elements into tiles...", "[5/7]".bold());
+    emit_gui_progress_update(25.0, "Indexing elements into tiles...");
 
-    // Update player spawn Y coordinate based on terrain height after generation
-    #[cfg(feature = "gui")]
-    if let Some(spawn_coords) = &args.spawn_point {
-        use crate::gui::update_player_spawn_y_after_generation;
-        let bbox_string = format!(
-            "{},{},{},{}",
-            args.bbox.min().lng(),
-            args.bbox.min().lat(),
-            args.bbox.max().lng(),
-            args.bbox.max().lat()
-        );
+    let grid = TileGrid::new(xzbbox, regions_per_tile);
+    let tiles = grid.tiles();
+    let tile_elements = grid.index_elements(elements);
+    let tiles_count = tiles.len();
 
-        if let Err(e) = update_player_spawn_y_after_generation(
-            &args.path,
-            Some(*spawn_coords),
-            bbox_string,
-            args.scale,
-            &ground,
-        ) {
-            eprintln!("Warning: Failed to update spawn point Y coordinate: {e}");
+    // Without an explicit tile size, tiles only split the work and are saved together
+    let flush_tiles = args.tile_size.is_some();
+
+    // Tiles already written to disk by an interrupted run are skipped
+    let checkpoint = checkpoint.filter(|_| flush_tiles);
+    if let Some(checkpoint) = checkpoint {
+        let completed = checkpoint.completed_tiles_count();
+        if completed > 0 {
+            println!("Skipping {completed} tiles completed before");
         }
     }
+    let is_completed = |tile_index: usize| {
+        checkpoint.is_some_and(|checkpoint| checkpoint.is_tile_completed(tile_index))
+    };
+    let complete_tile = |tile_index: usize| {
+        if let Some(checkpoint) = checkpoint {
+            if let Err(e) = checkpoint.complete_tile(tile_index) {
+                eprintln!("Warning: Failed to save checkpoint: {e}");
+            }
+        }
+    };
 
-    emit_gui_progress_update(100.0, "Done! World generation completed.");
-    println!("{}", "Done! World generation completed.".green().bold());
-    Ok(())
+    println!("{} Generating {} tiles...", "[6/7]".bold(), tiles_count);
+    emit_gui_progress_update(30.0, "Generating tiles...");
+
+    let tiles_pb: ProgressBar = ProgressBar::new(tiles_count as u64);
+    tiles_pb.set_style(ProgressStyle::default_bar()
+        .template("{spinner:.green} [{elapsed_precise}] [{bar:45.white/black}] {pos}/{len} tiles ({eta}) {msg}")
+        .unwrap()
+        .progress_chars("█▓░"));
+
+    let progress_increment_tile: f64 = 60.0 / tiles_count as f64;
+    let tiles_done = AtomicUsize::new(0);
+    let finish_tile = || {
+        tiles_pb.inc(1);
+        let done = tiles_done.fetch_add(1, Ordering::Relaxed) + 1;
+        emit_gui_progress_update(30.0 + done as f64 * progress_increment_tile, "");
+    };
+
+    if args.parallel {
+        let base_editor: &WorldEditor = editor;
+        let tile_editors: Vec<WorldEditor> = tiles
+            .into_par_iter()
+            .zip(tile_elements)
+            .enumerate()
+            .map(|(tile_index, (tile, indices))| {
+                let mut tile_editor = base_editor.for_tile(tile.clone());
+                if !is_completed(tile_index) {
+                    generate_tile(
+                        &mut tile_editor,
+                        &tile,
+                        &indices,
+                        elements,
+                        street_signs,
+                        ocean,
+                        args,
+                    );
+                    if flush_tiles {
+                        tile_editor.flush_regions();
+                        complete_tile(tile_index);
+                    }
+                }
+                finish_tile();
+                tile_editor
+            })
+            .collect();
+
+        for tile_editor in tile_editors {
+            editor.merge(tile_editor);
+        }
+    } else {
+        for (tile_index, (tile, indices)) in tiles.into_iter().zip(tile_elements).enumerate() {
+            if is_completed(tile_index) {
+                finish_tile();
+                continue;
+            }
+
+            tiles_pb.set_message(format!("({} elements)", indices.len()));
+
+            editor.set_tile(Some(tile.clone()));
+            generate_tile(editor, &tile, &indices, elements, street_signs, ocean, args);
+
+            // Write the finished regions and free their memory before the next tile
+            if flush_tiles {
+                editor.flush_regions();
+                complete_tile(tile_index);
+            }
+            finish_tile();
+        }
+
+        editor.set_tile(None);
+    }
+
+    tiles_pb.finish();
+}
+
+/// Processes the given elements, the street signs and the ground layer of a single tile
+fn generate_tile(
+    editor: &mut WorldEditor,
+    tile: &XZBBox,
+    indices: &[usize],
+    elements: &[ProcessedElement],
+    street_signs: &[StreetSign],
+    ocean: Option<&Ocean>,
+    args: &Args,
+) {
+    for &index in indices {
+        process_element(editor, &elements[index], args, elements);
+    }
+    highways::generate_street_signs(editor, street_signs);
+
+    for x in tile.min_x()..=tile.max_x() {
+        for z in tile.min_z()..=tile.max_z() {
+            generate_ground_column(editor, x, z, GRASS_BLOCK, ocean, args);
+        }
+    }
+}
+
+/// Generates a single OSM element and records it in the report of the editor
+fn process_element(
+    editor: &mut WorldEditor,
+    element: &ProcessedElement,
+    args: &Args,
+    elements: &[ProcessedElement],
+) {
+    let timeouts = floodfill::timeouts_on_thread();
+    let start = Instant::now();
+    let category = dispatch_element(editor, element, args, elements);
+    let floodfill_timeout = floodfill::timeouts_on_thread() > timeouts;
+    editor
+        .report()
+        .record_element(element, category, start.elapsed(), floodfill_timeout);
+}
+
+/// Dispatches an element to its element processor. Returns the category of the processor, or
+/// `None` if no processor matched the element.
+fn dispatch_element(
+    editor: &mut WorldEditor,
+    element: &ProcessedElement,
+    args: &Args,
+    elements: &[ProcessedElement],
+) -> Option<&'static str> {
+    match element {
+        ProcessedElement::Way(way) => {
+            if way.tags.contains_key("building") || way.tags.contains_key("building:part") {
+                buildings::generate_buildings(editor, way, args, None);
+                Some("building")
+            } else if way.tags.contains_key("highway") {
+                highways::generate_highways(editor, element, args, elements);
+                Some("highway")
+            } else if way.tags.contains_key("landuse") {
+                landuse::generate_landuse(editor, way, args);
+                Some("landuse")
+            } else if coastline::is_coastline(&way.tags) {
+                // Coastlines are assembled into the ocean before, which is placed with the ground
+                Some("coastline")
+            } else if way.tags.contains_key("natural") {
+                natural::generate_natural(editor, element, args);
+                Some("natural")
+            } else if way.tags.contains_key("amenity") {
+                amenities::generate_amenities(editor, element, args);
+                Some("amenity")
+            } else if way.tags.contains_key("leisure") {
+                leisure::generate_leisure(editor, way, args);
+                Some("leisure")
+            } else if way.tags.contains_key("barrier") {
+                barriers::generate_barriers(editor, element);
+                Some("barrier")
+            } else if way.tags.contains_key("waterway") {
+                waterways::generate_waterways(editor, way);
+                Some("waterway")
+            } else if way.tags.contains_key("bridge") {
+                //bridges::generate_bridges(editor, way, ground_level); // TODO FIX
+                None
+            } else if way.tags.contains_key("railway") {
+                railways::generate_railways(editor, way);
+                Some("railway")
+            } else if way.tags.contains_key("roller_coaster") {
+                railways::generate_roller_coaster(editor, way);
+                Some("roller_coaster")
+            } else if way.tags.contains_key("aeroway") || way.tags.contains_key("area:aeroway") {
+                highways::generate_aeroway(editor, way, args);
+                Some("aeroway")
+            } else if way.tags.get("service") == Some(&"siding".to_string()) {
+                highways::generate_siding(editor, way);
+                Some("siding")
+            } else if way.tags.contains_key("man_made") {
+                man_made::generate_man_made(editor, element, args);
+                Some("man_made")
+            } else {
+                None
+            }
+        }
+        ProcessedElement::Node(node) => {
+            if node.tags.contains_key("door") || node.tags.contains_key("entrance") {
+                doors::generate_doors(editor, node);
+                Some("door")
+            } else if node.tags.contains_key("natural")
+                && node.tags.get("natural") == Some(&"tree".to_string())
+            {
+                natural::generate_natural(editor, element, args);
+                Some("natural")
+            } else if node.tags.contains_key("amenity") {
+                amenities::generate_amenities(editor, element, args);
+                Some("amenity")
+            } else if node.tags.contains_key("barrier") {
+                barriers::generate_barrier_nodes(editor, node);
+                Some("barrier")
+            } else if node.tags.contains_key("highway") {
+                highways::generate_highways(editor, element, args, elements);
+                Some("highway")
+            } else if node.tags.contains_key("tourism") {
+                tourisms::generate_tourisms(editor, node);
+                Some("tourism")
+            } else if node.tags.contains_key("man_made") {
+                man_made::generate_man_made_nodes(editor, node);
+                Some("man_made")
+            } else {
+                None
+            }
+        }
+        ProcessedElement::Relation(rel) => {
+            if rel.tags.contains_key("building") || rel.tags.contains_key("building:part") {
+                buildings::generate_building_from_relation(editor, rel, args);
+                Some("building")
+            } else if rel.tags.contains_key("water")
+                || rel.tags.get("natural") == Some(&"water".to_string())
+            {
+                water_areas::generate_water_areas(editor, rel);
+                Some("water")
+            } else if rel.tags.contains_key("natural") {
+                natural::generate_natural_from_relation(editor, rel, args);
+                Some("natural")
+            } else if rel.tags.contains_key("landuse") {
+                landuse::generate_landuse_from_relation(editor, rel, args);
+                Some("landuse")
+            } else if rel.tags.get("leisure") == Some(&"park".to_string()) {
+                leisure::generate_leisure_from_relation(editor, rel, args);
+                Some("leisure")
+            } else if rel.tags.contains_key("man_made") {
+                man_made::generate_man_made(editor, &ProcessedElement::Relation(rel.clone()), args);
+                Some("man_made")
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Generates the ground layer, or the sea floor and water in the ocean, the optional stone fill
+/// and the bedrock floor of one block column
+fn generate_ground_column(
+    editor: &mut WorldEditor,
+    x: i32,
+    z: i32,
+    groundlayer_block: Block,
+    ocean: Option<&Ocean>,
+    args: &Args,
+) {
+    // Columns outside of polygon shaped areas stay untouched
+    if !editor.is_in_bounds(x, z) {
+        return;
+    }
+
+    if let Some(ocean) = ocean.filter(|ocean| ocean.contains(x, z)) {
+        ocean.generate_column(editor, x, z, args.fillground);
+    } else {
+        generate_land_column(editor, x, z, groundlayer_block, args);
+    }
+
+    // Generate a bedrock level at MIN_Y
+    editor.set_block_absolute(BEDROCK, x, MIN_Y, z, None, Some(&[BEDROCK]));
+}
+
+/// Generates the grass and dirt layer and the optional stone fill of a land column
+fn generate_land_column(
+    editor: &mut WorldEditor,
+    x: i32,
+    z: i32,
+    groundlayer_block: Block,
+    args: &Args,
+) {
+    // Add default dirt and grass layer if there isn't a stone layer already
+    if !editor.check_for_block(x, 0, z, Some(&[STONE])) {
+        editor.set_block(groundlayer_block, x, 0, z, None, None);
+        editor.set_block(DIRT, x, -1, z, None, None);
+        editor.set_block(DIRT, x, -2, z, None, None);
+    }
+
+    // Fill underground with stone
+    if args.fillground {
+        // Fill from bedrock+1 to 3 blocks below ground with stone
+        editor.fill_blocks_absolute(
+            STONE,
+            x,
+            MIN_Y + 1,
+            z,
+            x,
+            editor.get_absolute_y(x, -3, z),
+            z,
+            None,
+            None,
+        );
+    }
 }
@@ -0,0 +1,455 @@
+//! Oceans from `natural=coastline` ways.
+//!
+//! OSM draws coastlines as ways with the land on their left and the water on their right. The
+//! ways are joined where they share nodes and clipped to the bounding box, then the pieces
+//! crossing the box are closed along its edges, keeping the water side. Closed coastlines
+//! within the box are islands. Ocean columns get a sea floor and water up to sea level instead
+//! of the grass layer.
+
+use crate::args::Args;
+use crate::biomes::Biome;
+use crate::block_definitions::{SAND, STONE, WATER};
+use crate::coordinate_system::cartesian::{XZBBox, XZPoint};
+use crate::data_processing::MIN_Y;
+use crate::ground::Ground;
+use crate::osm_parser::{ProcessedElement, ProcessedNode, ProcessedWay};
+use crate::world_editor::WorldEditor;
+use std::collections::HashMap;
+
+/// Corners of the bbox rectangle as (min_x, min_z, max_x, max_z)
+type Rect = (f64, f64, f64, f64);
+
+pub fn is_coastline(tags: &HashMap<String, String>) -> bool {
+    tags.get("natural").map(String::as_str) == Some("coastline")
+}
+
+/// The blocks on the water side of the coastlines and how deep the sea is
+pub struct Ocean {
+    area: XZBBox,
+    depth: i32,
+    bathymetry: bool,
+}
+
+impl Ocean {
+    /// Assembles the ocean from the coastline ways among the elements. Returns `None` if there
+    /// are no coastlines or they leave no water within the bbox.
+    pub fn from_elements(
+        elements: &[ProcessedElement],
+        xzbbox: &XZBBox,
+        args: &Args,
+    ) -> Option<Self> {
+        let ways: Vec<&ProcessedWay> = elements
+            .iter()
+            .filter_map(|element| match element {
+                ProcessedElement::Way(way) if is_coastline(&way.tags) => Some(way),
+                _ => None,
+            })
+            .collect();
+        if ways.is_empty() {
+            return None;
+        }
+
+        let rings = ocean_rings(
+            &ways,
+            (
+                xzbbox.min_x() as f64,
+                xzbbox.min_z() as f64,
+                xzbbox.max_x() as f64,
+                xzbbox.max_z() as f64,
+            ),
+        );
+        let area = XZBBox::polygon_from_rings(rings, xzbbox).ok()?;
+
+        Some(Self {
+            area,
+            depth: args.sea_depth as i32,
+            bathymetry: args.bathymetry,
+        })
+    }
+
+    #[inline]
+    pub fn contains(&self, x: i32, z: i32) -> bool {
+        self.area.contains(&XZPoint::new(x, z))
+    }
+
+    /// Generates the sea floor and the water of an ocean column. Blocks placed by elements
+    /// before, like piers, are kept.
+    pub fn generate_column(&self, editor: &mut WorldEditor, x: i32, z: i32, fillground: bool) {
+        let Some(sea_level) = editor.get_ground().map(Ground::sea_level) else {
+            return;
+        };
+
+        let floor: i32 = if self.bathymetry {
+            editor.get_absolute_y(x, 0, z).min(sea_level - 1)
+        } else {
+            sea_level - self.depth
+        }
+        .max(MIN_Y + 1);
+
+        editor.set_block_absolute(SAND, x, floor, z, None, None);
+        if floor < sea_level {
+            editor.fill_blocks_absolute(WATER, x, floor + 1, z, x, sea_level, z, None, None);
+        }
+        if fillground && floor > MIN_Y + 1 {
+            editor.fill_blocks_absolute(STONE, x, MIN_Y + 1, z, x, floor - 1, z, None, None);
+        }
+        editor.set_biome(x, z, Biome::OCEAN);
+    }
+}
+
+/// Rings covering the water side of the coastlines within the rectangle, by the even-odd rule
+fn ocean_rings(ways: &[&ProcessedWay], rect: Rect) -> Vec<Vec<XZPoint>> {
+    let mut pieces: Vec<Vec<(f64, f64)>> = Vec::new();
+    let mut rings: Vec<Vec<(f64, f64)>> = Vec::new();
+    let mut islands: Vec<Vec<(f64, f64)>> = Vec::new();
+
+    for chain in join_ways(ways) {
+        let closed = chain.len() > 3 && chain[0].id == chain[chain.len() - 1].id;
+        let mut points: Vec<(f64, f64)> = chain
+            .iter()
+            .map(|node| (node.x as f64, node.z as f64))
+            .collect();
+
+        if closed {
+            if points.iter().all(|&point| inside(point, rect)) {
+                points.pop();
+                // Land is on the left, so islands run counterclockwise on the map
+                if signed_area(&points) < 0.0 {
+                    islands.push(points);
+                } else {
+                    rings.push(points);
+                }
+                continue;
+            }
+            // Start outside of the rectangle, so every piece enters and leaves it
+            points.pop();
+            let start = points
+                .iter()
+                .position(|&point| !inside(point, rect))
+                .unwrap();
+            points.rotate_left(start);
+            points.push(points[0]);
+        } else {
+            // Ways are cut off at the bbox when they are downloaded, so their open ends are
+            // extended to the nearest edge
+            if inside(points[0], rect) {
+                points.insert(0, nearest_edge_point(points[0], rect));
+            }
+            let last = points[points.len() - 1];
+            if inside(last, rect) {
+                points.push(nearest_edge_point(last, rect));
+            }
+        }
+
+        pieces.extend(clip_polyline(&points, rect));
+    }
+
+    rings.extend(stitch_pieces(&pieces, rect));
+
+    // Without coastlines crossing the bbox it is all water around the islands
+    if rings.is_empty() && !islands.is_empty() {
+        let (min_x, min_z, max_x, max_z) = rect;
+        rings.push(vec![
+            (min_x, min_z),
+            (max_x, min_z),
+            (max_x, max_z),
+            (min_x, max_z),
+        ]);
+    }
+
+    // Islands are holes in the water, others belong to land that is missing around them
+    for island in islands {
+        if covers(&rings, island[0]) {
+            rings.push(island);
+        } else {
+            eprintln!("Warning: Skipping a coastline island that is not surrounded by water");
+        }
+    }
+
+    rings
+        .into_iter()
+        .map(|ring| {
+            ring.into_iter()
+                .map(|(x, z)| XZPoint::new(x.round() as i32, z.round() as i32))
+                .collect()
+        })
+        .collect()
+}
+
+/// Joins ways whose ends share a node into chains of nodes
+fn join_ways<'a>(ways: &[&'a ProcessedWay]) -> Vec<Vec<&'a ProcessedNode>> {
+    let mut pending: Vec<Vec<&ProcessedNode>> = ways
+        .iter()
+        .filter(|way| way.nodes.len() > 1)
+        .map(|way| way.nodes.iter().collect())
+        .collect();
+    let mut chains: Vec<Vec<&ProcessedNode>> = Vec::new();
+
+    while let Some(mut chain) = pending.pop() {
+        loop {
+            let (first, last) = (chain[0].id, chain[chain.len() - 1].id);
+            if chain.len() > 3 && first == last {
+                break;
+            }
+            if let Some(index) = pending.iter().position(|next| next[0].id == last) {
+                let next = pending.swap_remove(index);
+                chain.extend(&next[1..]);
+            } else if let Some(index) = pending
+                .iter()
+                .position(|previous| previous[previous.len() - 1].id == first)
+            {
+                let mut previous = pending.swap_remove(index);
+                previous.extend(&chain[1..]);
+                chain = previous;
+            } else {
+                break;
+            }
+        }
+        chains.push(chain);
+    }
+
+    chains
+}
+
+/// Splits a polyline into the pieces within the rectangle, each starting and ending on its edge
+fn clip_polyline(points: &[(f64, f64)], rect: Rect) -> Vec<Vec<(f64, f64)>> {
+    let mut pieces: Vec<Vec<(f64, f64)>> = Vec::new();
+    let mut current: Vec<(f64, f64)> = Vec::new();
+
+    for segment in points.windows(2) {
+        let (a, b) = (segment[0], segment[1]);
+        let Some((t0, t1)) = clip_segment(a, b, rect) else {
+            continue;
+        };
+        let lerp = |t: f64| (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+
+        if current.is_empty() {
+            current.push(lerp(t0));
+        }
+        current.push(lerp(t1));
+        if t1 < 1.0 {
+            pieces.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+
+    // Pieces only touching the edge enclose no water
+    pieces.retain(|piece| piece.len() > 1 && piece.iter().any(|&p| p != piece[0]));
+    pieces
+}
+
+/// Parameters of the part of segment a-b within the rectangle (Liang-Barsky)
+fn clip_segment(a: (f64, f64), b: (f64, f64), rect: Rect) -> Option<(f64, f64)> {
+    let (min_x, min_z, max_x, max_z) = rect;
+    let (dx, dz) = (b.0 - a.0, b.1 - a.1);
+    let (mut t0, mut t1) = (0.0_f64, 1.0_f64);
+
+    for (p, q) in [
+        (-dx, a.0 - min_x),
+        (dx, max_x - a.0),
+        (-dz, a.1 - min_z),
+        (dz, max_z - a.1),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+
+    (t0 <= t1).then_some((t0, t1))
+}
+
+/// Closes the pieces into rings along the edge of the rectangle. Water is on the right of the
+/// pieces, so from the end of a piece the edge is followed clockwise on the map (north up) to
+/// the next piece starting there.
+fn stitch_pieces(pieces: &[Vec<(f64, f64)>], rect: Rect) -> Vec<Vec<(f64, f64)>> {
+    let (min_x, min_z, max_x, max_z) = rect;
+    let perimeter = 2.0 * ((max_x - min_x) + (max_z - min_z));
+    let corners = [
+        (max_x, min_z),
+        (max_x, max_z),
+        (min_x, max_z),
+        (min_x, min_z),
+    ];
+
+    let mut used = vec![false; pieces.len()];
+    let mut rings: Vec<Vec<(f64, f64)>> = Vec::new();
+
+    for start in 0..pieces.len() {
+        if used[start] {
+            continue;
+        }
+
+        let mut ring: Vec<(f64, f64)> = Vec::new();
+        let mut index = start;
+        loop {
+            used[index] = true;
+            ring.extend(&pieces[index]);
+
+            let exit = edge_position(ring[ring.len() - 1], rect);
+            let distance =
+                |point: (f64, f64)| (edge_position(point, rect) - exit).rem_euclid(perimeter);
+            let next = (0..pieces.len())
+                .filter(|&i| !used[i] || i == start)
+                .min_by(|&a, &b| distance(pieces[a][0]).total_cmp(&distance(pieces[b][0])))
+                .unwrap();
+
+            // Corners passed on the way to the next piece
+            let mut passed: Vec<(f64, f64)> = corners
+                .into_iter()
+                .filter(|&corner| {
+                    let d = distance(corner);
+                    d > 0.0 && d < distance(pieces[next][0])
+                })
+                .collect();
+            passed.sort_by(|&a, &b| distance(a).total_cmp(&distance(b)));
+            ring.extend(passed);
+
+            if next == start {
+                break;
+            }
+            index = next;
+        }
+        rings.push(ring);
+    }
+
+    rings
+}
+
+/// Distance along the edge of the rectangle, clockwise on the map from its north west corner
+fn edge_position((x, z): (f64, f64), rect: Rect) -> f64 {
+    let (min_x, min_z, max_x, max_z) = rect;
+    let (width, height) = (max_x - min_x, max_z - min_z);
+    let distances = [z - min_z, max_x - x, max_z - z, x - min_x].map(f64::abs);
+    let edge = (0..4)
+        .min_by(|&a, &b| distances[a].total_cmp(&distances[b]))
+        .unwrap();
+    match edge {
+        0 => x - min_x,
+        1 => width + (z - min_z),
+        2 => width + height + (max_x - x),
+        _ => 2.0 * width + height + (max_z - z),
+    }
+}
+
+fn nearest_edge_point((x, z): (f64, f64), rect: Rect) -> (f64, f64) {
+    let (min_x, min_z, max_x, max_z) = rect;
+    let candidates = [(x, min_z), (max_x, z), (x, max_z), (min_x, z)];
+    candidates
+        .into_iter()
+        .min_by(|a, b| {
+            let da = (a.0 - x).abs() + (a.1 - z).abs();
+            let db = (b.0 - x).abs() + (b.1 - z).abs();
+            da.total_cmp(&db)
+        })
+        .unwrap()
+}
+
+fn inside((x, z): (f64, f64), (min_x, min_z, max_x, max_z): Rect) -> bool {
+    (min_x..=max_x).contains(&x) && (min_z..=max_z).contains(&z)
+}
+
+/// Shoelace area, positive for rings running clockwise on the map (z points south)
+fn signed_area(ring: &[(f64, f64)]) -> f64 {
+    let n = ring.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (ring[i], ring[(i + 1) % n]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+/// Whether the point is covered by the rings, by the even-odd rule
+fn covers(rings: &[Vec<(f64, f64)>], (x, z): (f64, f64)) -> bool {
+    let mut inside = false;
+    for ring in rings {
+        for i in 0..ring.len() {
+            let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+            if (a.1 > z) != (b.1 > z) && x < a.0 + (z - a.1) / (b.1 - a.1) * (b.0 - a.0) {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn way(id: u64, nodes: &[(u64, i32, i32)]) -> ProcessedWay {
+        ProcessedWay {
+            id,
+            nodes: nodes
+                .iter()
+                .map(|&(id, x, z)| ProcessedNode {
+                    id,
+                    tags: HashMap::new(),
+                    x,
+                    z,
+                })
+                .collect(),
+            tags: HashMap::from([("natural".to_string(), "coastline".to_string())]),
+        }
+    }
+
+    fn ocean(ways: &[ProcessedWay]) -> XZBBox {
+        let ways: Vec<&ProcessedWay> = ways.iter().collect();
+        let clip = XZBBox::rect_from_xz_lengths(100.0, 100.0).unwrap();
+        XZBBox::polygon_from_rings(ocean_rings(&ways, (0.0, 0.0, 100.0, 100.0)), &clip).unwrap()
+    }
+
+    #[test]
+    fn test_coastline_stitching() {
+        // A coast running east in two ways, cut off inside the bbox: land north, water south
+        let coast = [
+            way(1, &[(1, 5, 40), (2, 50, 50)]),
+            way(2, &[(2, 50, 50), (3, 95, 40)]),
+        ];
+        let area = ocean(&coast);
+        assert!(area.contains(&XZPoint::new(50, 90)));
+        assert!(area.contains(&XZPoint::new(0, 100)));
+        assert!(!area.contains(&XZPoint::new(50, 10)));
+        assert!(!area.contains(&XZPoint::new(0, 0)));
+
+        // Reversed, the water is north
+        let reversed = [way(1, &[(3, 95, 40), (2, 50, 50), (1, 5, 40)])];
+        let area = ocean(&reversed);
+        assert!(area.contains(&XZPoint::new(50, 10)));
+        assert!(!area.contains(&XZPoint::new(50, 90)));
+
+        // An island, counterclockwise on the map, is a hole in the water around it
+        let island = [way(
+            1,
+            &[
+                (1, 40, 40),
+                (2, 40, 60),
+                (3, 60, 60),
+                (4, 60, 40),
+                (1, 40, 40),
+            ],
+        )];
+        let area = ocean(&island);
+        assert!(area.contains(&XZPoint::new(10, 10)));
+        assert!(!area.contains(&XZPoint::new(50, 50)));
+
+        // A bay: the coast enters and leaves through the south edge, with water in between
+        let bay = [way(
+            1,
+            &[(1, 20, 120), (2, 20, 30), (3, 80, 30), (4, 80, 120)],
+        )];
+        let area = ocean(&bay);
+        assert!(area.contains(&XZPoint::new(50, 50)));
+        assert!(!area.contains(&XZPoint::new(10, 50)));
+        assert!(!area.contains(&XZPoint::new(50, 10)));
+    }
+}
@@ -4,8 +4,9 @@ use crate::bresenham::bresenham_line;
 use crate::coordinate_system::cartesian::XZPoint;
 use crate::floodfill::flood_fill_area;
 use crate::osm_parser::{ProcessedElement, ProcessedWay};
+use crate::style::style;
 use crate::world_editor::WorldEditor;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Generates highways with elevation support based on layer tags and connectivity analysis
 pub fn generate_highways(
@@ -117,23 +118,8 @@ fn generate_highways_internal(
             };
 
             // Handle areas like pedestrian plazas
-            let mut surface_block: Block = STONE; // Default block
-
             // Determine the block type based on the 'surface' tag
-            if let Some(surface) = element.tags().get("surface") {
-                surface_block = match surface.as_str() {
-                    "paving_stones" | "sett" => STONE_BRICKS,
-                    "bricks" => BRICK,
-                    "wood" => OAK_PLANKS,
-                    "asphalt" => BLACK_CONCRETE,
-                    "gravel" | "fine_gravel" => GRAVEL,
-                    "grass" => GRASS_BLOCK,
-                    "dirt" | "ground" | "earth" => DIRT,
-                    "sand" => SAND,
-                    "concrete" => LIGHT_GRAY_CONCRETE,
-                    _ => STONE, // Default to stone for unknown surfaces
-                };
-            }
+            let surface_block: Block = style().block("highway_surface", element.tags(), STONE);
 
             // Fill the area using flood fill or by iterating through the nodes
             let polygon_coords: Vec<(i32, i32)> = way
@@ -150,7 +136,7 @@ fn generate_highways_internal(
         } else {
             let mut previous_node: Option<(i32, i32)> = None;
             let mut block_type = BLACK_CONCRETE;
-            let mut block_range: i32 = 2;
+            let mut block_range: i32 = road_half_width(highway_type, element.tags());
             let mut add_stripe = false;
             let mut add_outline = false;
             let scale_factor = args.scale;
@@ -172,58 +158,45 @@ fn generate_highways_internal(
                 }
             }
 
-            // Determine block type and range based on highway type
+            // Determine block type based on highway type
             match highway_type.as_str() {
                 "footway" | "pedestrian" => {
                     block_type = GRAY_CONCRETE;
-                    block_range = 1;
                 }
                 "path" => {
                     block_type = DIRT_PATH;
-                    block_range = 1;
                 }
                 "motorway" | "primary" | "trunk" => {
-                    block_range = 5;
                     add_stripe = true;
                 }
                 "secondary" => {
-                    block_range = 4;
                     add_stripe = true;
                 }
                 "tertiary" => {
                     add_stripe = true;
                 }
                 "track" => {
-                    block_range = 1;
+                    // Narrow unpaved road, never striped
                 }
                 "service" => {
                     block_type = GRAY_CONCRETE;
-                    block_range = 2;
                 }
                 "secondary_link" | "tertiary_link" => {
                     //Exit ramps, sliproads
                     block_type = BLACK_CONCRETE;
-                    block_range = 1;
                 }
                 "escape" => {
                     // Sand trap for vehicles on mountainous roads
                     block_type = SAND;
-                    block_range = 1;
                 }
                 "steps" => {
                     //TODO: Add correct stairs respecting height, step_count, etc.
                     block_type = GRAY_CONCRETE;
-                    block_range = 1;
                 }
 
                 _ => {
                     if let Some(lanes) = element.tags().get("lanes") {
-                        if lanes == "2" {
-                            block_range = 3;
-                            add_stripe = true;
-                            add_outline = true;
-                        } else if lanes != "1" {
-                            block_range = 4;
+                        if lanes != "1" {
                             add_stripe = true;
                             add_outline = true;
                         }
@@ -636,3 +609,256 @@ pub fn generate_aeroway(editor: &mut WorldEditor, way: &ProcessedWay, args: &Arg
         previous_node = Some((node.x, node.z));
     }
 }
+
+/// Half width of the road surface of a highway beside its center line, before scaling
+fn road_half_width(highway_type: &str, tags: &HashMap<String, String>) -> i32 {
+    match highway_type {
+        "motorway" | "primary" | "trunk" => 5,
+        "secondary" => 4,
+        "tertiary" | "service" => 2,
+        "footway" | "pedestrian" | "path" | "track" | "secondary_link" | "tertiary_link"
+        | "escape" | "steps" => 1,
+        _ => match tags.get("lanes").map(String::as_str) {
+            Some("2") => 3,
+            Some("1") | None => 2,
+            Some(_) => 4,
+        },
+    }
+}
+
+/// Highways that get no street name signs where they cross other roads
+const UNSIGNED_HIGHWAYS: [&str; 10] = [
+    "footway",
+    "path",
+    "cycleway",
+    "steps",
+    "bridleway",
+    "track",
+    "corridor",
+    "platform",
+    "proposed",
+    "construction",
+];
+
+/// A street name sign standing at a corner of an intersection
+pub struct StreetSign {
+    x: i32,
+    z: i32,
+    /// Rotation of the sign, facing the road it names
+    rotation: u8,
+    lines: [String; 4],
+}
+
+/// Finds the intersections of named roads, i.e. nodes shared by ways with different names
+/// (or refs), and returns one sign per crossing road. Each sign stands at a corner beside its
+/// road, just outside the widest road surface of the intersection.
+pub fn street_signs(elements: &[ProcessedElement], scale: f64) -> Vec<StreetSign> {
+    // Roads passing each node with their name, direction at the node and half width
+    type Road<'a> = (&'a str, (f64, f64), i32);
+    let mut intersections: BTreeMap<u64, ((i32, i32), Vec<Road>)> = BTreeMap::new();
+
+    for element in elements {
+        let ProcessedElement::Way(way) = element else {
+            continue;
+        };
+        let Some(highway_type) = way.tags.get("highway") else {
+            continue;
+        };
+        let is_set = |key: &str| way.tags.get(key).is_some_and(|value| value != "no");
+        let layer = way
+            .tags
+            .get("layer")
+            .and_then(|layer| layer.parse::<i32>().ok())
+            .unwrap_or(0);
+        if UNSIGNED_HIGHWAYS.contains(&highway_type.as_str())
+            || is_set("area")
+            || is_set("tunnel")
+            || is_set("bridge")
+            || layer != 0
+        {
+            continue;
+        }
+        let Some(name) = way.tags.get("name").or_else(|| way.tags.get("ref")) else {
+            continue;
+        };
+
+        let mut half_width = road_half_width(highway_type, &way.tags);
+        if scale < 1.0 {
+            half_width = ((half_width as f64) * scale).floor() as i32;
+        }
+
+        for (i, node) in way.nodes.iter().enumerate() {
+            let prev = &way.nodes[i.saturating_sub(1)];
+            let next = &way.nodes[(i + 1).min(way.nodes.len() - 1)];
+            let (dx, dz) = ((next.x - prev.x) as f64, (next.z - prev.z) as f64);
+            let length = dx.hypot(dz);
+            if length == 0.0 {
+                continue;
+            }
+
+            let (_, roads) = intersections
+                .entry(node.id)
+                .or_insert_with(|| ((node.x, node.z), Vec::new()));
+            // Consecutive ways of the same street do not make an intersection
+            if !roads.iter().any(|(road_name, _, _)| road_name == name) {
+                roads.push((name, (dx / length, dz / length), half_width));
+            }
+        }
+    }
+
+    let mut signs = Vec::new();
+    for ((x, z), roads) in intersections.into_values() {
+        if roads.len() < 2 {
+            continue;
+        }
+
+        let offset = roads.iter().map(|(_, _, w)| *w).max().unwrap_or(0) as f64 + 1.0;
+        for (name, (dx, dz), _) in roads {
+            // Beside the road on its right, past the crossing road, facing the road
+            let (nx, nz) = (-dz, dx);
+            signs.push(StreetSign {
+                x: x + ((nx + dx) * offset).round() as i32,
+                z: z + ((nz + dz) * offset).round() as i32,
+                rotation: sign_rotation(-nx, -nz),
+                lines: sign_lines(name),
+            });
+        }
+    }
+    signs
+}
+
+/// Places the street name signs of the intersections
+pub fn generate_street_signs(editor: &mut WorldEditor, signs: &[StreetSign]) {
+    for sign in signs {
+        editor.set_sign(&sign.lines, sign.x, 1, sign.z, sign.rotation);
+    }
+}
+
+/// Rotation of a standing sign whose front faces the given direction
+fn sign_rotation(dx: f64, dz: f64) -> u8 {
+    // Rotation 0 faces south (+Z), each step turns 22.5 degrees clockwise
+    let degrees = (-dx).atan2(dz).to_degrees();
+    ((degrees / 22.5).round() as i32).rem_euclid(16) as u8
+}
+
+/// Wraps a street name onto the four lines of a sign, centered vertically
+fn sign_lines(text: &str) -> [String; 4] {
+    const LINE_LENGTH: usize = 15;
+
+    let mut wrapped: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        let lines_full = wrapped.len() == 4;
+        match wrapped.last_mut() {
+            Some(line)
+                if lines_full || line.chars().count() + 1 + word.chars().count() <= LINE_LENGTH =>
+            {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => wrapped.push(word.to_string()),
+        }
+    }
+
+    let mut lines: [String; 4] = Default::default();
+    let first = if wrapped.len() <= 2 { 1 } else { 0 };
+    for (line, text) in lines[first..].iter_mut().zip(wrapped) {
+        *line = text;
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osm_parser::ProcessedNode;
+
+    fn road(id: u64, highway: &str, name: &str, nodes: &[(u64, i32, i32)]) -> ProcessedElement {
+        ProcessedElement::Way(ProcessedWay {
+            id,
+            nodes: nodes
+                .iter()
+                .map(|&(id, x, z)| ProcessedNode {
+                    id,
+                    tags: HashMap::new(),
+                    x,
+                    z,
+                })
+                .collect(),
+            tags: HashMap::from([
+                ("highway".to_string(), highway.to_string()),
+                ("name".to_string(), name.to_string()),
+            ]),
+        })
+    }
+
+    #[test]
+    fn test_sign_rotation() {
+        assert_eq!(sign_rotation(0.0, 1.0), 0); // South
+        assert_eq!(sign_rotation(-1.0, 0.0), 4); // West
+        assert_eq!(sign_rotation(0.0, -1.0), 8); // North
+        assert_eq!(sign_rotation(1.0, 0.0), 12); // East
+        assert_eq!(sign_rotation(-1.0, 1.0), 2); // South-west
+    }
+
+    #[test]
+    fn test_sign_lines() {
+        // One or two lines are centered, three and four start at the top
+        assert_eq!(sign_lines("Main Street"), ["", "Main Street", "", ""]);
+        assert_eq!(
+            sign_lines("Avenue of the Americas"),
+            ["", "Avenue of the", "Americas", ""]
+        );
+        assert_eq!(
+            sign_lines("Rue du Faubourg Saint-Honoré et Boulevard"),
+            ["Rue du Faubourg", "Saint-Honoré et", "Boulevard", ""]
+        );
+
+        // Words beyond the fourth line are appended to it
+        assert_eq!(
+            sign_lines("Aaaaaaaaaa Bbbbbbbbbb Cccccccccc Dddddddddd Eeeeeeeeee"),
+            [
+                "Aaaaaaaaaa",
+                "Bbbbbbbbbb",
+                "Cccccccccc",
+                "Dddddddddd Eeeeeeeeee"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_same_street_gets_no_sign() {
+        let elements = [
+            road(1, "residential", "Elm Street", &[(1, 0, 0), (2, 20, 0)]),
+            road(2, "residential", "Elm Street", &[(2, 20, 0), (3, 40, 0)]),
+        ];
+        assert!(street_signs(&elements, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_crossing_signs() {
+        // A primary road running east crossed by a residential street running south
+        let elements = [
+            road(
+                1,
+                "primary",
+                "Main Street",
+                &[(1, 0, 20), (2, 20, 20), (3, 40, 20)],
+            ),
+            road(
+                2,
+                "residential",
+                "Elm Street",
+                &[(4, 20, 0), (2, 20, 20), (5, 20, 40)],
+            ),
+        ];
+        let signs = street_signs(&elements, 1.0);
+        assert_eq!(signs.len(), 2);
+
+        // Both stand just outside the primary road (half width 5), each on the right of its
+        // road past the crossing, facing the road it names
+        let main = signs.iter().find(|s| s.lines[1] == "Main Street").unwrap();
+        assert_eq!((main.x, main.z, main.rotation), (26, 26, 8));
+        let elm = signs.iter().find(|s| s.lines[1] == "Elm Street").unwrap();
+        assert_eq!((elm.x, elm.z, elm.rotation), (14, 26, 12));
+    }
+}
@@ -1,48 +1,32 @@
 use crate::args::Args;
 use crate::block_definitions::*;
 use crate::element_processing::tree::Tree;
+use crate::entities::Entity;
 use crate::floodfill::flood_fill_area;
 use crate::osm_parser::{ProcessedMemberRole, ProcessedRelation, ProcessedWay};
+use crate::style::{style, StyleRule};
 use crate::world_editor::WorldEditor;
 use rand::Rng;
 
+const LIVESTOCK: [&str; 4] = [
+    "minecraft:cow",
+    "minecraft:sheep",
+    "minecraft:pig",
+    "minecraft:chicken",
+];
+
 pub fn generate_landuse(editor: &mut WorldEditor, element: &ProcessedWay, args: &Args) {
     // Determine block type based on landuse tag
     let binding: String = "".to_string();
     let landuse_tag: &String = element.tags.get("landuse").unwrap_or(&binding);
 
-    let block_type = match landuse_tag.as_str() {
-        "greenfield" | "meadow" | "grass" | "orchard" | "forest" => GRASS_BLOCK,
-        "farmland" => FARMLAND,
-        "cemetery" => PODZOL,
-        "construction" => COARSE_DIRT,
-        "traffic_island" => STONE_BLOCK_SLAB,
-        "residential" => {
-            let residential_tag = element.tags.get("residential").unwrap_or(&binding);
-            if residential_tag == "rural" {
-                GRASS_BLOCK
-            } else {
-                STONE_BRICKS
-            }
-        }
-        "commercial" => SMOOTH_STONE,
-        "education" => POLISHED_ANDESITE,
-        "religious" => POLISHED_ANDESITE,
-        "industrial" => COBBLESTONE,
-        "military" => GRAY_CONCRETE,
-        "railway" => GRAVEL,
-        "landfill" => {
-            // Gravel if man_made = spoil_heap or heap, coarse dirt else
-            let manmade_tag = element.tags.get("man_made").unwrap_or(&binding);
-            if manmade_tag == "spoil_heap" || manmade_tag == "heap" {
-                GRAVEL
-            } else {
-                COARSE_DIRT
-            }
-        }
-        "quarry" => STONE,
-        _ => GRASS_BLOCK,
-    };
+    let landuse_style = style().rule("landuse", &element.tags);
+    let block_type: Block = landuse_style
+        .and_then(StyleRule::block)
+        .unwrap_or(GRASS_BLOCK);
+    let block_height: i32 = landuse_style.map_or(0, StyleRule::height);
+    let biome = style().biome(&element.tags);
+    let animal_density: f64 = landuse_style.map_or(0.0, |rule| rule.density("animal"));
 
     // Get the area of the landuse element
     let polygon_coords: Vec<(i32, i32)> = element.nodes.iter().map(|n| (n.x, n.z)).collect();
@@ -51,12 +35,23 @@ pub fn generate_landuse(editor: &mut WorldEditor, element: &ProcessedWay, args:
     let mut rng: rand::prelude::ThreadRng = rand::thread_rng();
 
     for (x, z) in floor_area {
-        if landuse_tag == "traffic_island" {
-            editor.set_block(block_type, x, 1, z, None, None);
-        } else if landuse_tag == "construction" || landuse_tag == "railway" {
-            editor.set_block(block_type, x, 0, z, None, Some(&[SPONGE]));
+        if let Some(biome) = biome {
+            editor.set_biome(x, z, biome);
+        }
+
+        if landuse_tag == "construction" || landuse_tag == "railway" {
+            editor.set_block(block_type, x, block_height, z, None, Some(&[SPONGE]));
         } else {
-            editor.set_block(block_type, x, 0, z, None, None);
+            editor.set_block(block_type, x, block_height, z, None, None);
+        }
+
+        // Livestock grazing on pastures and farmland
+        if rng.gen_bool(animal_density)
+            && editor.check_for_block(x, 0, z, Some(&[GRASS_BLOCK, FARMLAND]))
+        {
+            let animal = LIVESTOCK[rng.gen_range(0..LIVESTOCK.len())];
+            let yaw: f32 = rng.gen_range(0.0..360.0);
+            editor.add_entity(Entity::new(animal).with_yaw(yaw), x, 1, z);
         }
 
         // Add specific features for different landuse types
@@ -92,10 +87,15 @@ pub fn generate_landuse(editor: &mut WorldEditor, element: &ProcessedWay, args:
             }
             "forest" => {
                 if editor.check_for_block(x, 0, z, Some(&[GRASS_BLOCK])) {
-                    let random_choice: i32 = rng.gen_range(0..30);
-                    if random_choice == 20 {
+                    let density =
+                        |decoration| landuse_style.map_or(0.0, |rule| rule.density(decoration));
+                    let tree_density = density("tree");
+                    let flower_density = density("flower");
+
+                    let random_choice: f64 = rng.gen();
+                    if random_choice < tree_density {
                         Tree::create(editor, (x, 1, z));
-                    } else if random_choice == 2 {
+                    } else if random_choice < tree_density + flower_density {
                         let flower_block: Block = match rng.gen_range(1..=5) {
                             1 => OAK_LEAVES,
                             2 => RED_FLOWER,
@@ -104,7 +104,7 @@ pub fn generate_landuse(editor: &mut WorldEditor, element: &ProcessedWay, args:
                             _ => WHITE_FLOWER,
                         };
                         editor.set_block(flower_block, x, 1, z, None, None);
-                    } else if random_choice <= 12 {
+                    } else if random_choice < tree_density + flower_density + density("grass") {
                         editor.set_block(GRASS, x, 1, z, None, None);
                     }
                 }
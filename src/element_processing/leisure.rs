@@ -2,8 +2,10 @@ use crate::args::Args;
 use crate::block_definitions::*;
 use crate::bresenham::bresenham_line;
 use crate::element_processing::tree::Tree;
+use crate::entities::Entity;
 use crate::floodfill::flood_fill_area;
 use crate::osm_parser::{ProcessedMemberRole, ProcessedRelation, ProcessedWay};
+use crate::style::{style, StyleRule};
 use crate::world_editor::WorldEditor;
 use rand::Rng;
 
@@ -14,33 +16,12 @@ pub fn generate_leisure(editor: &mut WorldEditor, element: &ProcessedWay, args:
         let mut current_leisure: Vec<(i32, i32)> = vec![];
 
         // Determine block type based on leisure type
-        let block_type: Block = match leisure_type.as_str() {
-            "park" | "nature_reserve" | "garden" | "disc_golf_course" | "golf_course" => {
-                GRASS_BLOCK
-            }
-            "schoolyard" => BLACK_CONCRETE,
-            "playground" | "recreation_ground" | "pitch" | "beach_resort" | "dog_park" => {
-                if let Some(surface) = element.tags.get("surface") {
-                    match surface.as_str() {
-                        "clay" => TERRACOTTA,
-                        "sand" => SAND,
-                        "tartan" => RED_TERRACOTTA,
-                        "grass" => GRASS_BLOCK,
-                        "dirt" => DIRT,
-                        "pebblestone" | "cobblestone" | "unhewn_cobblestone" => COBBLESTONE,
-                        _ => GREEN_STAINED_HARDENED_CLAY,
-                    }
-                } else {
-                    GREEN_STAINED_HARDENED_CLAY
-                }
-            }
-            "swimming_pool" | "swimming_area" => WATER, //Swimming area: Area in a larger body of water for swimming
-            "bathing_place" => SMOOTH_SANDSTONE,        // Could be sand or concrete
-            "outdoor_seating" => SMOOTH_STONE,          //Usually stone or stone bricks
-            "water_park" | "slipway" => LIGHT_GRAY_CONCRETE, // Water park area, not the pool. Usually is concrete
-            "ice_rink" => PACKED_ICE, // TODO: Ice for Ice Rink, needs building defined
-            _ => GRASS_BLOCK,
-        };
+        let leisure_style = style().rule("leisure", &element.tags);
+        let block_type: Block = leisure_style
+            .and_then(StyleRule::block)
+            .unwrap_or(GRASS_BLOCK);
+        let density = |decoration| leisure_style.map_or(0.0, |rule| rule.density(decoration));
+        let biome = style().biome(&element.tags);
 
         // Process leisure area nodes
         for node in &element.nodes {
@@ -86,38 +67,42 @@ pub fn generate_leisure(editor: &mut WorldEditor, element: &ProcessedWay, args:
 
             for (x, z) in filled_area {
                 editor.set_block(block_type, x, 0, z, Some(&[GRASS_BLOCK]), None);
+                if let Some(biome) = biome {
+                    editor.set_biome(x, z, biome);
+                }
 
-                // Add decorative elements for parks and gardens
-                if matches!(leisure_type.as_str(), "park" | "garden" | "nature_reserve")
-                    && editor.check_for_block(x, 0, z, Some(&[GRASS_BLOCK]))
-                {
+                // Boats moored in the water of marinas
+                if editor.check_for_block(x, 0, z, Some(&[WATER])) {
                     let mut rng: rand::prelude::ThreadRng = rand::thread_rng();
-                    let random_choice: i32 = rng.gen_range(0..1000);
+                    if rng.gen_bool(density("boat")) {
+                        let yaw: f32 = rng.gen_range(0.0..360.0);
+                        editor.add_entity(Entity::new("minecraft:oak_boat").with_yaw(yaw), x, 1, z);
+                    }
+                }
 
-                    match random_choice {
-                        0..30 => {
-                            // Flowers
-                            let flower_choice = match random_choice {
-                                0..10 => RED_FLOWER,
-                                10..20 => YELLOW_FLOWER,
-                                20..30 => BLUE_FLOWER,
-                                _ => WHITE_FLOWER,
-                            };
-                            editor.set_block(flower_choice, x, 1, z, None, None);
-                        }
-                        30..90 => {
-                            // Grass
-                            editor.set_block(GRASS, x, 1, z, None, None);
-                        }
-                        90..105 => {
-                            // Oak leaves
-                            editor.set_block(OAK_LEAVES, x, 1, z, None, None);
-                        }
-                        105..120 => {
-                            // Tree
-                            Tree::create(editor, (x, 1, z));
-                        }
-                        _ => {}
+                // Add decorative elements for parks and gardens
+                if editor.check_for_block(x, 0, z, Some(&[GRASS_BLOCK])) {
+                    let mut rng: rand::prelude::ThreadRng = rand::thread_rng();
+                    let flower_density = density("flower");
+                    let grass_density = flower_density + density("grass");
+                    let bush_density = grass_density + density("bush");
+                    let tree_density = bush_density + density("tree");
+
+                    let random_choice: f64 = rng.gen();
+                    if random_choice < flower_density {
+                        // Flowers
+                        let flower_choice =
+                            [RED_FLOWER, YELLOW_FLOWER, BLUE_FLOWER][rng.gen_range(0..3)];
+                        editor.set_block(flower_choice, x, 1, z, None, None);
+                    } else if random_choice < grass_density {
+                        // Grass
+                        editor.set_block(GRASS, x, 1, z, None, None);
+                    } else if random_choice < bush_density {
+                        // Oak leaves
+                        editor.set_block(OAK_LEAVES, x, 1, z, None, None);
+                    } else if random_choice < tree_density {
+                        // Tree
+                        Tree::create(editor, (x, 1, z));
                     }
                 }
 
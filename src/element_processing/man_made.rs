@@ -1,6 +1,7 @@
 use crate::args::Args;
 use crate::block_definitions::*;
 use crate::bresenham::bresenham_line;
+use crate::entities::Entity;
 use crate::osm_parser::{ProcessedElement, ProcessedNode};
 use crate::world_editor::WorldEditor;
 
@@ -48,6 +49,7 @@ fn generate_pier(editor: &mut WorldEditor, element: &ProcessedElement) {
 
         let pier_height = 1; // Pier deck height above ground
         let support_spacing = 4; // Support pillars every 4 blocks
+        let boat_spacing = 8; // Boat moored every 8 blocks where there is water
 
         // Generate the pier walkway using bresenham line algorithm
         for i in 0..nodes.len() - 1 {
@@ -83,6 +85,38 @@ fn generate_pier(editor: &mut WorldEditor, element: &ProcessedElement) {
                 }
             }
         }
+
+        // Moor boats alongside the finished deck
+        let half_width = pier_width / 2;
+        for i in 0..nodes.len() - 1 {
+            let start_node = &nodes[i];
+            let end_node = &nodes[i + 1];
+
+            let line_points =
+                bresenham_line(start_node.x, 0, start_node.z, end_node.x, 0, end_node.z);
+            // Boats lie along the pier
+            let yaw: f32 = if (end_node.x - start_node.x).abs() > (end_node.z - start_node.z).abs()
+            {
+                90.0
+            } else {
+                0.0
+            };
+
+            for (center_x, _y, center_z) in line_points.iter().step_by(boat_spacing) {
+                let sides = [
+                    (center_x + half_width + 2, *center_z),
+                    (center_x - half_width - 2, *center_z),
+                    (*center_x, center_z + half_width + 2),
+                    (*center_x, center_z - half_width - 2),
+                ];
+                if let Some(&(x, z)) = sides.iter().find(|(x, z)| {
+                    editor.check_for_block(*x, 0, *z, Some(&[WATER]))
+                        && !editor.block_at(*x, pier_height, *z)
+                }) {
+                    editor.add_entity(Entity::new("minecraft:oak_boat").with_yaw(yaw), x, 1, z);
+                }
+            }
+        }
     }
 }
 
@@ -4,6 +4,7 @@ use crate::bresenham::bresenham_line;
 use crate::element_processing::tree::Tree;
 use crate::floodfill::flood_fill_area;
 use crate::osm_parser::{ProcessedElement, ProcessedMemberRole, ProcessedRelation, ProcessedWay};
+use crate::style::{style, StyleRule};
 use crate::world_editor::WorldEditor;
 use rand::Rng;
 
@@ -20,30 +21,14 @@ pub fn generate_natural(editor: &mut WorldEditor, element: &ProcessedElement, ar
             let mut previous_node: Option<(i32, i32)> = None;
             let mut corner_addup: (i32, i32, i32) = (0, 0, 0);
             let mut current_natural: Vec<(i32, i32)> = vec![];
-            let binding: String = "".to_string();
 
             // Determine block type based on natural tag
-            let block_type: Block = match natural_type.as_str() {
-                "scrub" | "grassland" | "wood" | "heath" | "tree_row" => GRASS_BLOCK,
-                "sand" | "dune" => SAND,
-                "beach" | "shoal" => {
-                    let surface = element.tags().get("natural").unwrap_or(&binding);
-                    match surface.as_str() {
-                        "gravel" => GRAVEL,
-                        _ => SAND,
-                    }
-                }
-                "water" | "reef" => WATER,
-                "bare_rock" => STONE,
-                "blockfield" => COBBLESTONE,
-                "glacier" => PACKED_ICE,
-                "mud" | "wetland" => MUD,
-                "mountain_range" => COBBLESTONE,
-                "saddle" | "ridge" => STONE,
-                "shrubbery" | "tundra" | "hill" => GRASS_BLOCK,
-                "cliff" => STONE,
-                _ => GRASS_BLOCK,
-            };
+            let natural_style = style().rule("natural", element.tags());
+            let block_type: Block = natural_style
+                .and_then(StyleRule::block)
+                .unwrap_or(GRASS_BLOCK);
+            let density = |decoration| natural_style.map_or(0.0, |rule| rule.density(decoration));
+            let biome = style().biome(element.tags());
 
             let ProcessedElement::Way(way) = element else {
                 return;
@@ -83,6 +68,9 @@ pub fn generate_natural(editor: &mut WorldEditor, element: &ProcessedElement, ar
 
                 for (x, z) in filled_area {
                     editor.set_block(block_type, x, 0, z, None, None);
+                    if let Some(biome) = biome {
+                        editor.set_biome(x, z, biome);
+                    }
                     // Generate custom layer instead of dirt, must be stone on the lowest level
                     match natural_type.as_str() {
                         "beach" | "sand" | "dune" | "shoal" => {
@@ -159,10 +147,13 @@ pub fn generate_natural(editor: &mut WorldEditor, element: &ProcessedElement, ar
                             if !editor.check_for_block(x, 0, z, Some(&[GRASS_BLOCK])) {
                                 continue;
                             }
-                            let random_choice: i32 = rng.gen_range(0..30);
-                            if random_choice == 0 {
+                            let tree_density = density("tree");
+                            let flower_density = density("flower");
+
+                            let random_choice: f64 = rng.gen();
+                            if random_choice < tree_density {
                                 Tree::create(editor, (x, 1, z));
-                            } else if random_choice == 1 {
+                            } else if random_choice < tree_density + flower_density {
                                 let flower_block = match rng.gen_range(1..=4) {
                                     1 => RED_FLOWER,
                                     2 => BLUE_FLOWER,
@@ -170,7 +161,9 @@ pub fn generate_natural(editor: &mut WorldEditor, element: &ProcessedElement, ar
                                     _ => WHITE_FLOWER,
                                 };
                                 editor.set_block(flower_block, x, 1, z, None, None);
-                            } else if random_choice <= 12 {
+                            } else if random_choice
+                                < tree_density + flower_density + density("grass")
+                            {
                                 editor.set_block(GRASS, x, 1, z, None, None);
                             }
                         }
@@ -1,8 +1,12 @@
 use crate::block_definitions::*;
 use crate::bresenham::bresenham_line;
+use crate::entities::Entity;
 use crate::osm_parser::ProcessedWay;
 use crate::world_editor::WorldEditor;
 
+/// Distance in rail blocks between minecarts standing on a track
+const MINECART_SPACING: usize = 64;
+
 pub fn generate_railways(editor: &mut WorldEditor, element: &ProcessedWay) {
     if let Some(railway_type) = element.tags.get("railway") {
         if [
@@ -30,6 +34,8 @@ pub fn generate_railways(editor: &mut WorldEditor, element: &ProcessedWay) {
             }
         }
 
+        let mut rail_count: usize = 0;
+
         for i in 1..element.nodes.len() {
             let prev_node = element.nodes[i - 1].xz();
             let cur_node = element.nodes[i].xz();
@@ -61,6 +67,11 @@ pub fn generate_railways(editor: &mut WorldEditor, element: &ProcessedWay) {
 
                 editor.set_block(rail_block, bx, 1, bz, None, None);
 
+                rail_count += 1;
+                if rail_count % MINECART_SPACING == MINECART_SPACING / 2 {
+                    editor.add_entity(Entity::new("minecraft:minecart"), bx, 1, bz);
+                }
+
                 if bx % 4 == 0 {
                     editor.set_block(OAK_LOG, bx, 0, bz, None, None);
                 }
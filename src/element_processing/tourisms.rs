@@ -1,6 +1,8 @@
 use crate::block_definitions::*;
+use crate::entities::{Entity, Facing};
 use crate::osm_parser::ProcessedNode;
 use crate::world_editor::WorldEditor;
+use fastnbt::Value;
 
 pub fn generate_tourisms(editor: &mut WorldEditor, element: &ProcessedNode) {
     // Skip if 'layer' or 'level' is negative in the tags
@@ -27,8 +29,31 @@ pub fn generate_tourisms(editor: &mut WorldEditor, element: &ProcessedNode) {
                     // TODO draw a sign with text if provided
                     editor.set_block(COBBLESTONE_WALL, x, 1, z, None, None);
                     editor.set_block(OAK_PLANKS, x, 2, z, None, None);
+
+                    // Maps hang on the board in a frame
+                    if info_type == "map" {
+                        editor.add_entity(
+                            Entity::item_frame(Facing::South, "minecraft:map"),
+                            x,
+                            2,
+                            z + 1,
+                        );
+                    }
                 }
             }
+        } else if tourism_type == "artwork" {
+            if let Some("statue" | "sculpture") =
+                element.tags.get("artwork_type").map(|x| x.as_str())
+            {
+                // Armor stand posing on a pedestal
+                editor.set_block(SMOOTH_STONE, x, 1, z, None, None);
+                editor.add_entity(
+                    Entity::new("minecraft:armor_stand").with_data("ShowArms", Value::Byte(1)),
+                    x,
+                    2,
+                    z,
+                );
+            }
         }
     }
 }
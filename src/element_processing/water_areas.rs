@@ -1,10 +1,15 @@
 use geo::{Contains, Intersects, LineString, Point, Polygon, Rect};
+use std::collections::HashMap;
 use std::time::Instant;
 
 use crate::{
+    biomes::Biome,
     block_definitions::WATER,
-    coordinate_system::cartesian::XZPoint,
-    osm_parser::{ProcessedMemberRole, ProcessedNode, ProcessedRelation},
+    bresenham::bresenham_line,
+    coordinate_system::cartesian::{XZBBox, XZPoint},
+    ground::Ground,
+    osm_parser::{ProcessedElement, ProcessedMemberRole, ProcessedNode, ProcessedRelation},
+    style::style,
     world_editor::WorldEditor,
 };
 
@@ -26,6 +31,8 @@ pub fn generate_water_areas(editor: &mut WorldEditor, element: &ProcessedRelatio
         }
     }
 
+    let biome = style().biome(&element.tags);
+
     let mut outers: Vec<Vec<ProcessedNode>> = vec![];
     let mut inners: Vec<Vec<ProcessedNode>> = vec![];
 
@@ -73,6 +80,7 @@ pub fn generate_water_areas(editor: &mut WorldEditor, element: &ProcessedRelatio
                 individual_outers_xz,
                 empty_inners_xz,
                 editor,
+                biome,
                 start_time,
             );
             continue;
@@ -97,6 +105,7 @@ pub fn generate_water_areas(editor: &mut WorldEditor, element: &ProcessedRelatio
             individual_outers_xz,
             inners_xz,
             editor,
+            biome,
             start_time,
         );
     }
@@ -206,6 +215,7 @@ fn inverse_floodfill(
     outers: Vec<Vec<XZPoint>>,
     inners: Vec<Vec<XZPoint>>,
     editor: &mut WorldEditor,
+    biome: Option<Biome>,
     start_time: Instant,
 ) {
     let inners: Vec<_> = inners
@@ -242,6 +252,7 @@ fn inverse_floodfill(
         &outers,
         &inners,
         editor,
+        biome,
         start_time,
     );
 }
@@ -252,6 +263,7 @@ fn inverse_floodfill_recursive(
     outers: &[Polygon],
     inners: &[Polygon],
     editor: &mut WorldEditor,
+    biome: Option<Biome>,
     start_time: Instant,
 ) {
     // Check if we've exceeded 25 seconds
@@ -268,7 +280,7 @@ fn inverse_floodfill_recursive(
     // Multiply as i64 to avoid overflow; in release builds where unchecked math is
     // enabled, this could cause the rest of this code to end up in an infinite loop.
     if ((max.0 - min.0) as i64) * ((max.1 - min.1) as i64) < ITERATIVE_THRES {
-        inverse_floodfill_iterative(min, max, 0, outers, inners, editor);
+        inverse_floodfill_iterative(min, max, 0, outers, inners, editor, biome);
         return;
     }
 
@@ -290,7 +302,7 @@ fn inverse_floodfill_recursive(
         if outers.iter().any(|outer: &Polygon| outer.contains(&rect))
             && !inners.iter().any(|inner: &Polygon| inner.intersects(&rect))
         {
-            rect_fill(min_x, max_x, min_z, max_z, 0, editor);
+            rect_fill(min_x, max_x, min_z, max_z, 0, editor, biome);
             continue;
         }
 
@@ -312,6 +324,7 @@ fn inverse_floodfill_recursive(
                 &outers_intersects,
                 &inners_intersects,
                 editor,
+                biome,
                 start_time,
             );
         }
@@ -326,6 +339,7 @@ fn inverse_floodfill_iterative(
     outers: &[Polygon],
     inners: &[Polygon],
     editor: &mut WorldEditor,
+    biome: Option<Biome>,
 ) {
     for x in min.0..max.0 {
         for z in min.1..max.1 {
@@ -334,7 +348,7 @@ fn inverse_floodfill_iterative(
             if outers.iter().any(|poly: &Polygon| poly.contains(&p))
                 && inners.iter().all(|poly: &Polygon| !poly.contains(&p))
             {
-                editor.set_block(WATER, x, ground_level, z, None, None);
+                set_water(editor, x, ground_level, z, biome);
             }
         }
     }
@@ -347,10 +361,191 @@ fn rect_fill(
     max_z: i32,
     ground_level: i32,
     editor: &mut WorldEditor,
+    biome: Option<Biome>,
 ) {
     for x in min_x..max_x {
         for z in min_z..max_z {
-            editor.set_block(WATER, x, ground_level, z, None, None);
+            set_water(editor, x, ground_level, z, biome);
+        }
+    }
+}
+
+fn set_water(editor: &mut WorldEditor, x: i32, ground_level: i32, z: i32, biome: Option<Biome>) {
+    editor.set_block(WATER, x, ground_level, z, None, None);
+    if let Some(biome) = biome {
+        editor.set_biome(x, z, biome);
+    }
+}
+
+type Rings = Vec<Vec<ProcessedNode>>;
+
+/// Flattens the ground below lakes, ponds and other standing water to the lowest point of their
+/// shoreline, so the water placed on it is level. Flowing water areas like rivers keep their
+/// slope. Run after `waterways::carve_riverbeds`, so lakes are level with their outflow.
+pub fn level_lakes(ground: &mut Ground, elements: &[ProcessedElement], xzbbox: &XZBBox) {
+    let mut lakes: Vec<(i32, Rings, Rings)> = vec![];
+    for element in elements {
+        let (outers, inners): (Rings, Rings) = match element {
+            ProcessedElement::Way(way)
+                if way.tags.get("natural") == Some(&"water".to_string())
+                    && is_standing_water(&way.tags) =>
+            {
+                (vec![way.nodes.clone()], vec![])
+            }
+            ProcessedElement::Relation(rel)
+                if (rel.tags.contains_key("water")
+                    || rel.tags.get("natural") == Some(&"water".to_string()))
+                    && is_standing_water(&rel.tags) =>
+            {
+                let mut outers: Vec<Vec<ProcessedNode>> = vec![];
+                let mut inners: Vec<Vec<ProcessedNode>> = vec![];
+                for mem in &rel.members {
+                    match mem.role {
+                        ProcessedMemberRole::Outer => outers.push(mem.way.nodes.clone()),
+                        ProcessedMemberRole::Inner => inners.push(mem.way.nodes.clone()),
+                    }
+                }
+                merge_loopy_loops(&mut outers);
+                merge_loopy_loops(&mut inners);
+                (outers, inners)
+            }
+            _ => continue,
+        };
+        // Disconnected loops are reported when the water is generated
+        let is_closed = |ring: &Vec<ProcessedNode>| ring[0].id == ring[ring.len() - 1].id;
+        if outers.is_empty() || !outers.iter().chain(&inners).all(is_closed) {
+            continue;
+        }
+
+        if let Some(level) = shoreline_level(ground, &outers, xzbbox) {
+            lakes.push((level, outers, inners));
+        }
+    }
+
+    // Lower lakes come last, so where lakes share ground a higher one never raises a lower one
+    lakes.sort_by_key(|(level, _, _)| -level);
+    for (level, outers, inners) in lakes {
+        level_lake(ground, &outers, &inners, xzbbox, level);
+    }
+}
+
+/// Whether a water area is standing water, see `level_lakes`
+fn is_standing_water(tags: &HashMap<String, String>) -> bool {
+    !matches!(
+        tags.get("water").map(String::as_str),
+        Some("river" | "stream" | "canal" | "ditch" | "drain" | "rapids" | "stream_pool")
+    )
+}
+
+/// The lowest ground level on the outline of the outer rings within the bbox
+fn shoreline_level(ground: &Ground, outers: &[Vec<ProcessedNode>], xzbbox: &XZBBox) -> Option<i32> {
+    outers
+        .iter()
+        .flat_map(|ring| {
+            ring.windows(2)
+                .flat_map(|pair| bresenham_line(pair[0].x, 0, pair[0].z, pair[1].x, 0, pair[1].z))
+        })
+        .filter(|&(x, _, z)| xzbbox.contains(&XZPoint::new(x, z)))
+        .map(|(x, _, z)| ground.level(XZPoint::new(x - xzbbox.min_x(), z - xzbbox.min_z())))
+        .min()
+}
+
+/// Sets the ground within the outer rings to the given level
+fn level_lake(
+    ground: &mut Ground,
+    outers: &[Vec<ProcessedNode>],
+    inners: &[Vec<ProcessedNode>],
+    xzbbox: &XZBBox,
+    level: i32,
+) {
+    let origin = XZPoint::new(xzbbox.min_x(), xzbbox.min_z());
+    let relative = |x: i32, z: i32| XZPoint::new(x - origin.x, z - origin.z);
+
+    let rings: Vec<Vec<XZPoint>> = outers
+        .iter()
+        .chain(inners)
+        .map(|ring| ring.iter().map(ProcessedNode::xz).collect())
+        .collect();
+    let Ok(area) = XZBBox::polygon_from_rings(rings, xzbbox) else {
+        return;
+    };
+
+    let nodes = outers.iter().flatten();
+    let (min_x, max_x) = nodes
+        .clone()
+        .map(|node| node.x)
+        .fold((i32::MAX, i32::MIN), |(min, max), x| {
+            (min.min(x), max.max(x))
+        });
+    let (min_z, max_z) = nodes
+        .map(|node| node.z)
+        .fold((i32::MAX, i32::MIN), |(min, max), z| {
+            (min.min(z), max.max(z))
+        });
+
+    for x in min_x.max(xzbbox.min_x())..=max_x.min(xzbbox.max_x()) {
+        for z in min_z.max(xzbbox.min_z())..=max_z.min(xzbbox.max_z()) {
+            if area.contains(&XZPoint::new(x, z)) {
+                ground.set_level(relative(x, z), level);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osm_parser::ProcessedWay;
+
+    fn lake(id: u64, corners: [(i32, i32); 4]) -> ProcessedElement {
+        let ids = [1, 2, 3, 4, 1].map(|n| id * 10 + n);
+        let points = [0, 1, 2, 3, 0].map(|i| corners[i]);
+        ProcessedElement::Way(ProcessedWay {
+            id,
+            nodes: ids
+                .iter()
+                .zip(points)
+                .map(|(&id, (x, z))| ProcessedNode {
+                    id,
+                    tags: HashMap::new(),
+                    x,
+                    z,
+                })
+                .collect(),
+            tags: HashMap::from([("natural".to_string(), "water".to_string())]),
+        })
+    }
+
+    #[test]
+    fn test_level_lake() {
+        // Ground sloping up towards +x
+        let heights: Vec<Vec<f64>> = (0..41)
+            .map(|_| (0..41).map(|x| x as f64 * 0.5).collect())
+            .collect();
+        let mut ground = Ground::from_heights(heights.clone());
+        let xzbbox = XZBBox::rect_from_xz_lengths(40.0, 40.0).unwrap();
+        let shoreline_level = (10..=30)
+            .map(|z| ground.level(XZPoint::new(10, z)))
+            .min()
+            .unwrap();
+
+        let elements = [lake(1, [(10, 10), (30, 10), (30, 30), (10, 30)])];
+        level_lakes(&mut ground, &elements, &xzbbox);
+
+        for x in 11..30 {
+            for z in 11..30 {
+                assert_eq!(ground.level(XZPoint::new(x, z)), shoreline_level);
+            }
+        }
+        assert_eq!(ground.level(XZPoint::new(38, 20)), 19);
+
+        // Flowing water keeps its slope
+        let mut river = lake(2, [(10, 10), (30, 10), (30, 30), (10, 30)]);
+        if let ProcessedElement::Way(way) = &mut river {
+            way.tags.insert("water".to_string(), "river".to_string());
         }
+        let mut sloped = Ground::from_heights(heights);
+        level_lakes(&mut sloped, &[river], &xzbbox);
+        assert!(sloped.level(XZPoint::new(30, 20)) > sloped.level(XZPoint::new(10, 20)));
     }
 }
@@ -1,30 +1,13 @@
 use crate::block_definitions::*;
 use crate::bresenham::bresenham_line;
-use crate::osm_parser::ProcessedWay;
+use crate::coordinate_system::cartesian::{XZBBox, XZPoint};
+use crate::ground::Ground;
+use crate::osm_parser::{ProcessedElement, ProcessedWay};
 use crate::world_editor::WorldEditor;
+use std::collections::HashMap;
 
 pub fn generate_waterways(editor: &mut WorldEditor, element: &ProcessedWay) {
-    if let Some(waterway_type) = element.tags.get("waterway") {
-        let (mut waterway_width, waterway_depth) = get_waterway_dimensions(waterway_type);
-
-        // Check for custom width in tags
-        if let Some(width_str) = element.tags.get("width") {
-            waterway_width = width_str.parse::<i32>().unwrap_or_else(|_| {
-                width_str
-                    .parse::<f32>()
-                    .map(|f: f32| f as i32)
-                    .unwrap_or(waterway_width)
-            });
-        }
-
-        // Skip layers below the ground level
-        if matches!(
-            element.tags.get("layer").map(|s| s.as_str()),
-            Some("-1") | Some("-2") | Some("-3")
-        ) {
-            return;
-        }
-
+    if let Some((waterway_width, waterway_depth)) = channel_dimensions(element) {
         // Process consecutive node pairs to create waterways
         // Use windows(2) to avoid connecting last node back to first
         for nodes_pair in element.nodes.windows(2) {
@@ -49,6 +32,33 @@ pub fn generate_waterways(editor: &mut WorldEditor, element: &ProcessedWay) {
     }
 }
 
+/// Width and depth of the channel of a waterway, `None` if it is no waterway or runs below the
+/// ground level
+fn channel_dimensions(element: &ProcessedWay) -> Option<(i32, i32)> {
+    let waterway_type = element.tags.get("waterway")?;
+    let (mut waterway_width, waterway_depth) = get_waterway_dimensions(waterway_type);
+
+    // Check for custom width in tags
+    if let Some(width_str) = element.tags.get("width") {
+        waterway_width = width_str.parse::<i32>().unwrap_or_else(|_| {
+            width_str
+                .parse::<f32>()
+                .map(|f: f32| f as i32)
+                .unwrap_or(waterway_width)
+        });
+    }
+
+    // Skip layers below the ground level
+    if matches!(
+        element.tags.get("layer").map(|s| s.as_str()),
+        Some("-1") | Some("-2") | Some("-3")
+    ) {
+        return None;
+    }
+
+    Some((waterway_width, waterway_depth))
+}
+
 /// Determines width and depth based on waterway type
 fn get_waterway_dimensions(waterway_type: &str) -> (i32, i32) {
     match waterway_type {
@@ -113,3 +123,214 @@ fn create_water_channel(
         }
     }
 }
+
+/// How far banks are carved beyond the channel, at one block down per block
+const MAX_BANK_WIDTH: i32 = 6;
+
+/// Lowers the ground along flowing waterways so their water level never rises in the direction
+/// of the way, with banks sloping down to the channel. Ways are carved upstream first, so the
+/// level keeps falling where one way flows into the next.
+pub fn carve_riverbeds(ground: &mut Ground, elements: &[ProcessedElement], xzbbox: &XZBBox) {
+    let waterways: Vec<(&ProcessedWay, i32)> = elements
+        .iter()
+        .filter_map(|element| match element {
+            ProcessedElement::Way(way) if is_flowing(way) => {
+                channel_dimensions(way).map(|(width, _)| (way, width))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let origin = XZPoint::new(xzbbox.min_x(), xzbbox.min_z());
+    for index in upstream_order(&waterways) {
+        let (way, width) = waterways[index];
+        carve_riverbed(ground, way, width, origin);
+    }
+}
+
+/// Whether the way is a river, stream or other waterway with flowing water
+fn is_flowing(way: &ProcessedWay) -> bool {
+    let is_closed = way.nodes.len() > 2 && way.nodes[0].id == way.nodes[way.nodes.len() - 1].id;
+    !is_closed
+        && matches!(
+            way.tags.get("waterway").map(String::as_str),
+            Some(
+                "river"
+                    | "stream"
+                    | "canal"
+                    | "brook"
+                    | "ditch"
+                    | "drain"
+                    | "flowline"
+                    | "tidal_channel"
+            )
+        )
+}
+
+/// Orders the waterways so each comes after the ways ending at its first node
+fn upstream_order(waterways: &[(&ProcessedWay, i32)]) -> Vec<usize> {
+    let mut starting_at: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut inflows: Vec<usize> = vec![0; waterways.len()];
+    for (index, (way, _)) in waterways.iter().enumerate() {
+        starting_at.entry(way.nodes[0].id).or_default().push(index);
+    }
+    for (way, _) in waterways {
+        for &next in starting_at
+            .get(&way.nodes[way.nodes.len() - 1].id)
+            .into_iter()
+            .flatten()
+        {
+            inflows[next] += 1;
+        }
+    }
+
+    let mut order: Vec<usize> = Vec::with_capacity(waterways.len());
+    let mut sources: Vec<usize> = (0..waterways.len()).filter(|&i| inflows[i] == 0).collect();
+    while let Some(index) = sources.pop() {
+        order.push(index);
+        let (way, _) = waterways[index];
+        for &next in starting_at
+            .get(&way.nodes[way.nodes.len() - 1].id)
+            .into_iter()
+            .flatten()
+        {
+            inflows[next] -= 1;
+            if inflows[next] == 0 {
+                sources.push(next);
+            }
+        }
+    }
+
+    // Ways in loops have no upstream end, they follow in their original order
+    let mut placed: Vec<bool> = vec![false; waterways.len()];
+    for &index in &order {
+        placed[index] = true;
+    }
+    order.extend((0..waterways.len()).filter(|&i| !placed[i]));
+    order
+}
+
+/// Carves the channel of one waterway and its banks into the ground
+fn carve_riverbed(ground: &mut Ground, way: &ProcessedWay, width: i32, origin: XZPoint) {
+    let half_width = width / 2;
+    // The first ring around the channel is at water level as well, see create_water_channel
+    let reach = half_width + 1 + MAX_BANK_WIDTH;
+    let mut water_level: Option<i32> = None;
+
+    for nodes_pair in way.nodes.windows(2) {
+        let (prev_node, current_node) = (nodes_pair[0].xz(), nodes_pair[1].xz());
+        let bresenham_points: Vec<(i32, i32, i32)> = bresenham_line(
+            prev_node.x,
+            0,
+            prev_node.z,
+            current_node.x,
+            0,
+            current_node.z,
+        );
+
+        for (bx, _, bz) in bresenham_points {
+            let center = XZPoint::new(bx - origin.x, bz - origin.z);
+            let level = water_level.map_or(ground.level(center), |water_level| {
+                water_level.min(ground.level(center))
+            });
+            water_level = Some(level);
+
+            for dx in -reach..=reach {
+                for dz in -reach..=reach {
+                    let point = XZPoint::new(center.x + dx, center.z + dz);
+                    let distance = dx.abs().max(dz.abs());
+                    let target = level + (distance - half_width - 1).max(0);
+                    if ground.level(point) > target {
+                        ground.lower_level(point, target);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osm_parser::ProcessedNode;
+
+    fn way(id: u64, nodes: &[(u64, i32, i32)]) -> ProcessedWay {
+        ProcessedWay {
+            id,
+            nodes: nodes
+                .iter()
+                .map(|&(id, x, z)| ProcessedNode {
+                    id,
+                    tags: HashMap::new(),
+                    x,
+                    z,
+                })
+                .collect(),
+            tags: HashMap::from([("waterway".to_string(), "river".to_string())]),
+        }
+    }
+
+    fn order(ways: &[ProcessedWay]) -> Vec<usize> {
+        let waterways: Vec<(&ProcessedWay, i32)> = ways.iter().map(|way| (way, 4)).collect();
+        upstream_order(&waterways)
+    }
+
+    #[test]
+    fn test_upstream_order() {
+        // A chain listed downstream first
+        let chain = [
+            way(1, &[(3, 20, 0), (4, 30, 0)]),
+            way(2, &[(2, 10, 0), (3, 20, 0)]),
+            way(3, &[(1, 0, 0), (2, 10, 0)]),
+        ];
+        assert_eq!(order(&chain), vec![2, 1, 0]);
+
+        // Two tributaries join before the outflow
+        let confluence = [
+            way(1, &[(3, 20, 0), (4, 30, 0)]),
+            way(2, &[(1, 0, 0), (3, 20, 0)]),
+            way(3, &[(2, 0, 10), (3, 20, 0)]),
+        ];
+        let confluence_order = order(&confluence);
+        assert_eq!(confluence_order.len(), 3);
+        assert_eq!(confluence_order[2], 0);
+
+        // A loop has no upstream end, its ways follow the way feeding it in their original order
+        let looped = [
+            way(1, &[(1, 0, 0), (2, 10, 0)]),
+            way(2, &[(2, 10, 0), (1, 0, 0)]),
+            way(3, &[(5, 0, 10), (1, 0, 0)]),
+        ];
+        assert_eq!(order(&looped), vec![2, 0, 1]);
+    }
+
+    #[test]
+    fn test_carved_level_never_rises() {
+        // Ground rising along the way, with a ridge halfway
+        let heights: Vec<Vec<f64>> = (0..31)
+            .map(|_| {
+                (0..61)
+                    .map(|x| x as f64 * 0.5 + if (28..33).contains(&x) { 6.0 } else { 0.0 })
+                    .collect()
+            })
+            .collect();
+        let mut ground = Ground::from_heights(heights);
+        let river = way(1, &[(1, 0, 10), (2, 30, 10), (3, 60, 10)]);
+        let start_level = ground.level(XZPoint::new(0, 10));
+
+        carve_riverbed(&mut ground, &river, 4, XZPoint::new(0, 0));
+
+        let levels: Vec<i32> = (0..=60)
+            .map(|x| ground.level(XZPoint::new(x, 10)))
+            .collect();
+        assert_eq!(levels[0], start_level);
+        assert!(
+            levels.windows(2).all(|pair| pair[1] <= pair[0]),
+            "{levels:?}"
+        );
+
+        // Ground beside the channel slopes down to it, outside the banks it is untouched
+        assert!(ground.level(XZPoint::new(40, 14)) <= start_level + 1);
+        assert_eq!(ground.level(XZPoint::new(40, 25)), 20);
+    }
+}
@@ -1,5 +1,9 @@
-use crate::coordinate_system::{geographic::LLBBox, transformation::geo_distance};
+use crate::args::Args;
+use crate::coordinate_system::geographic::{LLBBox, LLPoint};
+use crate::coordinate_system::transformation::CoordTransformer;
+use crate::local_dem;
 use image::Rgb;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 
 /// Maximum Y coordinate in Minecraft (build height limit)
@@ -15,16 +19,50 @@ const TERRARIUM_OFFSET: f64 = 32768.0;
 const MIN_ZOOM: u8 = 10;
 /// Maximum zoom level for terrain tiles
 const MAX_ZOOM: u8 = 15;
+/// Height difference in meters at which the edge-preserving filter weighs neighbours less than
+/// half, so steeper steps like cliffs and embankments are kept
+const EDGE_RANGE_SIGMA: f64 = 2.0;
+
+/// Smoothing of the elevation grid before it is converted to Minecraft heights
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Smoothing {
+    /// Blur radius chosen from the grid size
+    #[default]
+    Auto,
+    /// Keep the sampled heights
+    None,
+    /// Blur with the given sigma in blocks
+    Sigma(f64),
+}
+
+impl Smoothing {
+    /// Parses "auto", "none" or a sigma in blocks
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "none" => Ok(Self::None),
+            sigma => match sigma.parse::<f64>() {
+                Ok(0.0) => Ok(Self::None),
+                Ok(sigma) if sigma > 0.0 && sigma.is_finite() => Ok(Self::Sigma(sigma)),
+                _ => Err(format!(
+                    "Invalid smoothing: {s}, use auto, none or a positive number of blocks"
+                )),
+            },
+        }
+    }
+}
 
 /// Holds processed elevation data and metadata
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ElevationData {
-    /// Height values in Minecraft Y coordinates
-    pub(crate) heights: Vec<Vec<i32>>,
+    /// Height values in Minecraft Y coordinates, not rounded so they can be interpolated
+    pub(crate) heights: Vec<Vec<f64>>,
     /// Width of the elevation grid
     pub(crate) width: usize,
     /// Height of the elevation grid
     pub(crate) height: usize,
+    /// Y coordinate of sea level (0 m), clamped like the heights
+    pub(crate) sea_level: f64,
 }
 
 /// Calculates appropriate zoom level for the given bounding box
@@ -66,28 +104,121 @@ fn download_tile(
     Ok(img.to_rgb8())
 }
 
-pub fn fetch_elevation_data(
-    bbox: &LLBBox,
-    scale: f64,
-    ground_level: i32,
-) -> Result<ElevationData, Box<dyn std::error::Error>> {
-    let (base_scale_z, base_scale_x) = geo_distance(bbox.min(), bbox.max());
+/// Raw heights in meters on the grid that `Ground` consumes, NaN where no sample was found.
+/// Grid cells are the blocks of the generated area, using the same transformation as the
+/// OSM data.
+pub(crate) struct HeightGrid {
+    bbox: LLBBox,
+    transformer: CoordTransformer,
+    min_x: f64,
+    min_z: f64,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) heights: Vec<Vec<f64>>,
+    /// Number of samples with implausible heights
+    extreme_values: usize,
+}
 
-    // Apply same floor() and scale operations as CoordTransformer.llbbox_to_xzbbox()
-    let scale_factor_z: f64 = base_scale_z.floor() * scale;
-    let scale_factor_x: f64 = base_scale_x.floor() * scale;
+impl HeightGrid {
+    pub(crate) fn new(args: &Args) -> Result<Self, String> {
+        let (transformer, xzbbox) = CoordTransformer::llbbox_to_xzbbox_projected(
+            &args.bbox,
+            args.scale,
+            args.projection,
+            args.origin,
+        )?;
+
+        // Match grid dimensions with Minecraft world size
+        let width: usize = (xzbbox.max_x() - xzbbox.min_x()) as usize;
+        let height: usize = (xzbbox.max_z() - xzbbox.min_z()) as usize;
+
+        Ok(Self {
+            bbox: args.bbox,
+            transformer,
+            min_x: xzbbox.min_x() as f64,
+            min_z: xzbbox.min_z() as f64,
+            width,
+            height,
+            heights: vec![vec![f64::NAN; width]; height],
+            extreme_values: 0,
+        })
+    }
 
-    // Calculate zoom and tiles
-    let zoom: u8 = calculate_zoom_level(bbox);
-    let tiles: Vec<(u32, u32)> = get_tile_coordinates(bbox, zoom);
+    pub(crate) fn bbox(&self) -> &LLBBox {
+        &self.bbox
+    }
+
+    /// Stores the height in meters sampled at the given coordinates, if they are within the
+    /// bounding box
+    pub(crate) fn add_sample(&mut self, lat: f64, lng: f64, height: f64) {
+        // Skip samples outside the requested bounding box
+        let Ok(llpoint) = LLPoint::new(lat, lng) else {
+            return;
+        };
+        if !self.bbox.contains(&llpoint) {
+            return;
+        }
 
-    // Match grid dimensions with Minecraft world size
-    let grid_width: usize = scale_factor_x as usize;
-    let grid_height: usize = scale_factor_z as usize;
+        // Map geographic coordinates to grid coordinates
+        let (x, z) = self.transformer.transform_point_exact(llpoint);
+        let scaled_x = (x - self.min_x).round();
+        let scaled_y = (z - self.min_z).round();
+        if scaled_x < 0.0
+            || scaled_y < 0.0
+            || scaled_y >= self.height as f64
+            || scaled_x >= self.width as f64
+        {
+            return;
+        }
+
+        // Track extreme values for debugging
+        if !(-1000.0..=10000.0).contains(&height) {
+            self.extreme_values += 1;
+            if self.extreme_values <= 5 {
+                // Only log first 5 extreme values
+                eprintln!("Extreme value found at {lat:.6},{lng:.6}: {height}m");
+            }
+        }
+
+        self.heights[scaled_y as usize][scaled_x as usize] = height;
+    }
+}
+
+/// Decodes a pixel of a Terrarium tile: (R * 256 + G + B/256) - 32768
+pub(crate) fn decode_terrarium(pixel: &Rgb<u8>) -> f64 {
+    (pixel[0] as f64 * 256.0 + pixel[1] as f64 + pixel[2] as f64 / 256.0) - TERRARIUM_OFFSET
+}
+
+/// Samples all pixels of a Web Mercator tile, e.g. in Terrarium format
+pub(crate) fn sample_rgb_tile(
+    grid: &mut HeightGrid,
+    rgb_img: &image::RgbImage,
+    (tile_x, tile_y): (u32, u32),
+    zoom: u8,
+    decode: fn(&Rgb<u8>) -> f64,
+) {
+    let tile_size: f64 = rgb_img.width() as f64;
+    for (y, row) in rgb_img.rows().enumerate() {
+        for (x, pixel) in row.enumerate() {
+            // Convert tile pixel coordinates back to geographic coordinates
+            let pixel_lng = ((tile_x as f64 + x as f64 / tile_size) / (2.0_f64.powi(zoom as i32)))
+                * 360.0
+                - 180.0;
+            let pixel_lat_rad = std::f64::consts::PI
+                * (1.0
+                    - 2.0 * (tile_y as f64 + y as f64 / tile_size) / (2.0_f64.powi(zoom as i32)));
+            let pixel_lat = pixel_lat_rad.sinh().atan().to_degrees();
+
+            grid.add_sample(pixel_lat, pixel_lng, decode(pixel));
+        }
+    }
+}
 
-    // Initialize height grid with proper dimensions
-    let mut height_grid: Vec<Vec<f64>> = vec![vec![f64::NAN; grid_width]; grid_height];
-    let mut extreme_values_found = Vec::new(); // Track extreme values for debugging
+/// Downloads the Terrarium tiles covering the grid from AWS, or loads them from the cache
+fn download_terrarium_tiles(grid: &mut HeightGrid) -> Result<(), Box<dyn std::error::Error>> {
+    // Calculate zoom and tiles
+    let zoom: u8 = calculate_zoom_level(&grid.bbox);
+    let tiles: Vec<(u32, u32)> = get_tile_coordinates(&grid.bbox, zoom);
 
     let client: reqwest::blocking::Client = reqwest::blocking::Client::new();
 
@@ -152,69 +283,38 @@ pub fn fetch_elevation_data(
             download_tile(&client, *tile_x, *tile_y, zoom, &tile_path)?
         };
 
-        // Only process pixels that fall within the requested bbox
-        for (y, row) in rgb_img.rows().enumerate() {
-            for (x, pixel) in row.enumerate() {
-                // Convert tile pixel coordinates back to geographic coordinates
-                let pixel_lng = ((*tile_x as f64 + x as f64 / 256.0) / (2.0_f64.powi(zoom as i32)))
-                    * 360.0
-                    - 180.0;
-                let pixel_lat_rad = std::f64::consts::PI
-                    * (1.0
-                        - 2.0 * (*tile_y as f64 + y as f64 / 256.0) / (2.0_f64.powi(zoom as i32)));
-                let pixel_lat = pixel_lat_rad.sinh().atan().to_degrees();
-
-                // Skip pixels outside the requested bounding box
-                if pixel_lat < bbox.min().lat()
-                    || pixel_lat > bbox.max().lat()
-                    || pixel_lng < bbox.min().lng()
-                    || pixel_lng > bbox.max().lng()
-                {
-                    continue;
-                }
-
-                // Map geographic coordinates to grid coordinates
-                let rel_x = (pixel_lng - bbox.min().lng()) / (bbox.max().lng() - bbox.min().lng());
-                let rel_y =
-                    1.0 - (pixel_lat - bbox.min().lat()) / (bbox.max().lat() - bbox.min().lat());
-
-                let scaled_x = (rel_x * grid_width as f64).round() as usize;
-                let scaled_y = (rel_y * grid_height as f64).round() as usize;
+        sample_rgb_tile(grid, &rgb_img, (*tile_x, *tile_y), zoom, decode_terrarium);
+    }
 
-                if scaled_y >= grid_height || scaled_x >= grid_width {
-                    continue;
-                }
+    Ok(())
+}
 
-                // Decode Terrarium format: (R * 256 + G + B/256) - 32768
-                let height: f64 =
-                    (pixel[0] as f64 * 256.0 + pixel[1] as f64 + pixel[2] as f64 / 256.0)
-                        - TERRARIUM_OFFSET;
-
-                // Track extreme values for debugging
-                if !(-1000.0..=10000.0).contains(&height) {
-                    extreme_values_found
-                        .push((tile_x, tile_y, x, y, pixel[0], pixel[1], pixel[2], height));
-                    if extreme_values_found.len() <= 5 {
-                        // Only log first 5 extreme values
-                        eprintln!("Extreme value found: tile({tile_x},{tile_y}) pixel({x},{y}) RGB({},{},{}) = {height}m", 
-                                 pixel[0], pixel[1], pixel[2]);
-                    }
-                }
+pub fn fetch_elevation_data(args: &Args) -> Result<ElevationData, Box<dyn std::error::Error>> {
+    let scale: f64 = args.scale;
+    let ground_level: i32 = args.ground_level;
 
-                height_grid[scaled_y][scaled_x] = height;
-            }
-        }
+    let mut grid = HeightGrid::new(args)?;
+    match &args.dem {
+        Some(dem) => local_dem::sample_local_dem(dem, args.dem_crs, &mut grid)?,
+        None => download_terrarium_tiles(&mut grid)?,
     }
 
     // Report on extreme values found
-    if !extreme_values_found.is_empty() {
+    if grid.extreme_values > 0 {
         eprintln!(
-            "Found {} total extreme elevation values during tile processing",
-            extreme_values_found.len()
+            "Found {} total extreme elevation values while sampling elevation data",
+            grid.extreme_values
         );
         eprintln!("This may indicate corrupted tile data or areas with invalid elevation data");
     }
 
+    if grid.heights.iter().flatten().all(|height| height.is_nan()) {
+        return Err("No elevation data found within the bounding box".into());
+    }
+
+    let (grid_width, grid_height) = (grid.width, grid.height);
+    let mut height_grid: Vec<Vec<f64>> = grid.heights;
+
     // Fill in any NaN values by interpolating from nearest valid values
     fill_nan_values(&mut height_grid);
 
@@ -230,7 +330,7 @@ pub fn fetch_elevation_data(
 
     let grid_size: f64 = (grid_width.min(grid_height) as f64).max(1.0);
 
-    let sigma: f64 = if grid_size <= SMALL_GRID_REF {
+    let auto_sigma: f64 = if grid_size <= SMALL_GRID_REF {
         // Linear scaling for small grids
         SMALL_SIGMA_REF * (grid_size / SMALL_GRID_REF)
     } else {
@@ -247,10 +347,20 @@ pub fn fetch_elevation_data(
         grid_width, grid_height, sigma
     ); */
 
-    // Continue with the existing blur and conversion to Minecraft heights...
-    let blurred_heights: Vec<Vec<f64>> = apply_gaussian_blur(&height_grid, sigma);
+    let sigma: f64 = match args.smoothing {
+        Smoothing::Auto => auto_sigma,
+        Smoothing::None => 0.0,
+        Smoothing::Sigma(sigma) => sigma,
+    };
 
-    let mut mc_heights: Vec<Vec<i32>> = Vec::with_capacity(blurred_heights.len());
+    // Continue with the existing blur and conversion to Minecraft heights...
+    let blurred_heights: Vec<Vec<f64>> = if sigma <= 0.0 {
+        height_grid
+    } else if args.preserve_edges {
+        apply_bilateral_filter(&height_grid, sigma, EDGE_RANGE_SIGMA)
+    } else {
+        apply_gaussian_blur(&height_grid, sigma)
+    };
 
     // Find min/max in raw data
     let mut min_height: f64 = f64::MAX;
@@ -285,9 +395,64 @@ pub fn fetch_elevation_data(
         );
     }
 
+    let exaggeration: f64 = args.vertical_exaggeration;
+    let (mc_heights, sea_level_y): (Vec<Vec<f64>>, f64) = match args.sea_level {
+        Some(sea_level) => {
+            let vertical_scale: f64 = args
+                .vertical_scale
+                .unwrap_or(BASE_HEIGHT_SCALE * scale.sqrt());
+            let heights = absolute_heights(
+                blurred_heights,
+                sea_level,
+                vertical_scale * exaggeration,
+                ground_level,
+            );
+            let sea_level_y = (sea_level as f64).clamp(ground_level as f64, MAX_Y as f64);
+            (heights, sea_level_y)
+        }
+        None => relative_heights(
+            blurred_heights,
+            min_height,
+            max_height,
+            scale,
+            exaggeration,
+            ground_level,
+        ),
+    };
+
+    let mut min_block_height: i32 = i32::MAX;
+    let mut max_block_height: i32 = i32::MIN;
+    for row in &mc_heights {
+        for &height in row {
+            min_block_height = min_block_height.min(height.round() as i32);
+            max_block_height = max_block_height.max(height.round() as i32);
+        }
+    }
+    eprintln!("Minecraft height data range: {min_block_height} to {max_block_height} blocks");
+
+    Ok(ElevationData {
+        heights: mc_heights,
+        width: grid_width,
+        height: grid_height,
+        sea_level: sea_level_y,
+    })
+}
+
+/// Fits the heights between `ground_level` and the build limit, so the lowest point of the
+/// area is at `ground_level`. Also returns the Y coordinate that 0 m maps to.
+fn relative_heights(
+    blurred_heights: Vec<Vec<f64>>,
+    min_height: f64,
+    max_height: f64,
+    scale: f64,
+    exaggeration: f64,
+    ground_level: i32,
+) -> (Vec<Vec<f64>>, f64) {
+    let mut mc_heights: Vec<Vec<f64>> = Vec::with_capacity(blurred_heights.len());
+
     let height_range: f64 = max_height - min_height;
     // Apply scale factor to height scaling
-    let mut height_scale: f64 = BASE_HEIGHT_SCALE * scale.sqrt(); // sqrt to make height scaling less extreme
+    let mut height_scale: f64 = BASE_HEIGHT_SCALE * scale.sqrt() * exaggeration; // sqrt to make height scaling less extreme
     let mut scaled_range: f64 = height_range * height_scale;
 
     // Adaptive scaling: ensure we don't exceed reasonable Y range
@@ -305,39 +470,81 @@ pub fn fetch_elevation_data(
         eprintln!("Adjusted scaled range: {scaled_range:.1} blocks");
     }
 
+    let to_y = |h: f64| {
+        // Scale the height differences
+        let relative_height: f64 = (h - min_height) / height_range;
+        let scaled_height: f64 = relative_height * scaled_range;
+        // With terrain enabled, ground_level is used as the MIN_Y for terrain
+        (ground_level as f64 + scaled_height).clamp(ground_level as f64, MAX_Y as f64)
+    };
+
     // Convert to scaled Minecraft Y coordinates
     for row in blurred_heights {
-        let mc_row: Vec<i32> = row
-            .iter()
-            .map(|&h| {
-                // Scale the height differences
-                let relative_height: f64 = (h - min_height) / height_range;
-                let scaled_height: f64 = relative_height * scaled_range;
-                // With terrain enabled, ground_level is used as the MIN_Y for terrain
-                ((ground_level as f64 + scaled_height).round() as i32).clamp(ground_level, MAX_Y)
-            })
-            .collect();
+        let mc_row: Vec<f64> = row.iter().map(|&h| to_y(h)).collect();
         mc_heights.push(mc_row);
     }
 
-    let mut min_block_height: i32 = i32::MAX;
-    let mut max_block_height: i32 = i32::MIN;
-    for row in &mc_heights {
-        for &height in row {
-            min_block_height = min_block_height.min(height);
-            max_block_height = max_block_height.max(height);
-        }
+    // A flat area has no height range to place sea level on, it is kept at the ground
+    let sea_level: f64 = if height_range > 0.0 {
+        to_y(0.0)
+    } else {
+        ground_level as f64
+    };
+
+    (mc_heights, sea_level)
+}
+
+/// Maps heights in meters to Y coordinates at a fixed offset from `sea_level`, the same in every
+/// run. Heights outside of `ground_level` and the build limit are clamped and reported.
+fn absolute_heights(
+    blurred_heights: Vec<Vec<f64>>,
+    sea_level: i32,
+    vertical_scale: f64,
+    ground_level: i32,
+) -> Vec<Vec<f64>> {
+    let (min_y, max_y) = (ground_level as f64, MAX_Y as f64);
+    let mut clamped_low: usize = 0;
+    let mut clamped_high: usize = 0;
+
+    let mc_heights: Vec<Vec<f64>> = blurred_heights
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|h| {
+                    let y: f64 = sea_level as f64 + h * vertical_scale;
+                    if y < min_y {
+                        clamped_low += 1;
+                    } else if y > max_y {
+                        clamped_high += 1;
+                    }
+                    y.clamp(min_y, max_y)
+                })
+                .collect()
+        })
+        .collect();
+
+    eprintln!(
+        "Absolute elevation: sea level at Y={sea_level}, {vertical_scale:.3} blocks per meter"
+    );
+    if clamped_low > 0 {
+        let min_meters: f64 = (ground_level - sea_level) as f64 / vertical_scale;
+        eprintln!(
+            "Warning: {clamped_low} blocks below {min_meters:.1} m were raised to Y={ground_level}, \
+             lower --ground-level or raise --sea-level to keep them"
+        );
+    }
+    if clamped_high > 0 {
+        let max_meters: f64 = (MAX_Y - sea_level) as f64 / vertical_scale;
+        eprintln!(
+            "Warning: {clamped_high} blocks above {max_meters:.1} m were cut off at Y={MAX_Y}, \
+             lower --sea-level or --vertical-scale to keep them"
+        );
     }
-    eprintln!("Minecraft height data range: {min_block_height} to {max_block_height} blocks");
 
-    Ok(ElevationData {
-        heights: mc_heights,
-        width: grid_width,
-        height: grid_height,
-    })
+    mc_heights
 }
 
-fn get_tile_coordinates(bbox: &LLBBox, zoom: u8) -> Vec<(u32, u32)> {
+pub(crate) fn get_tile_coordinates(bbox: &LLBBox, zoom: u8) -> Vec<(u32, u32)> {
     // Convert lat/lng to tile coordinates
     let (x1, y1) = lat_lng_to_tile(bbox.min().lat(), bbox.min().lng(), zoom);
     let (x2, y2) = lat_lng_to_tile(bbox.max().lat(), bbox.max().lng(), zoom);
@@ -403,6 +610,50 @@ fn apply_gaussian_blur(heights: &[Vec<f64>], sigma: f64) -> Vec<Vec<f64>> {
     blurred
 }
 
+/// Blurs like `apply_gaussian_blur`, but weighs neighbours less the more their height differs,
+/// so steep steps keep their shape while small bumps are smoothed. Filters rows and columns
+/// separately like the Gaussian blur, which approximates the full 2D filter at a fraction of
+/// its cost.
+fn apply_bilateral_filter(heights: &[Vec<f64>], sigma: f64, range_sigma: f64) -> Vec<Vec<f64>> {
+    let kernel_size: usize = (sigma * 3.0).ceil() as usize * 2 + 1;
+    let kernel: Vec<f64> = create_gaussian_kernel(kernel_size, sigma);
+    let range_weight =
+        |a: f64, b: f64| (-(a - b).powi(2) / (2.0 * range_sigma * range_sigma)).exp();
+
+    let filter_line = |line: &[f64]| -> Vec<f64> {
+        (0..line.len())
+            .map(|i| {
+                let mut sum: f64 = 0.0;
+                let mut weight_sum: f64 = 0.0;
+                for (j, k) in kernel.iter().enumerate() {
+                    let idx: i32 = i as i32 + j as i32 - kernel_size as i32 / 2;
+                    if idx >= 0 && idx < line.len() as i32 {
+                        let value: f64 = line[idx as usize];
+                        let weight: f64 = k * range_weight(value, line[i]);
+                        sum += value * weight;
+                        weight_sum += weight;
+                    }
+                }
+                sum / weight_sum
+            })
+            .collect()
+    };
+
+    // Horizontal pass
+    let mut filtered: Vec<Vec<f64>> = heights.iter().map(|row| filter_line(row)).collect();
+
+    // Vertical pass
+    let width: usize = filtered.first().map_or(0, Vec::len);
+    for x in 0..width {
+        let column: Vec<f64> = filtered.iter().map(|row| row[x]).collect();
+        for (row, value) in filtered.iter_mut().zip(filter_line(&column)) {
+            row[x] = value;
+        }
+    }
+
+    filtered
+}
+
 fn create_gaussian_kernel(size: usize, sigma: f64) -> Vec<f64> {
     let mut kernel: Vec<f64> = vec![0.0; size];
     let center: f64 = size as f64 / 2.0;
@@ -513,6 +764,42 @@ fn filter_elevation_outliers(height_grid: &mut [Vec<f64>]) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_absolute_heights() {
+        let heights = vec![vec![0.0, 10.0, -400.0], vec![1000.0, 2.4, -2.6]];
+        let mc_heights = absolute_heights(heights, 62, 0.5, -62);
+        assert_eq!(
+            mc_heights,
+            vec![vec![62.0, 67.0, -62.0], vec![MAX_Y as f64, 63.2, 60.7]]
+        );
+    }
+
+    #[test]
+    fn test_bilateral_filter_keeps_steps() {
+        // A 10 m step with 0.2 m noise on both sides
+        let heights: Vec<Vec<f64>> = (0..20)
+            .map(|z| {
+                (0..40)
+                    .map(|x| if x < 20 { 0.0 } else { 10.0 } + if (x + z) % 2 == 0 { 0.2 } else { 0.0 })
+                    .collect()
+            })
+            .collect();
+
+        let filtered = apply_bilateral_filter(&heights, 5.0, EDGE_RANGE_SIGMA);
+        let blurred = apply_gaussian_blur(&heights, 5.0);
+        let row = 10;
+        // The step stays sharp, while the Gaussian blur turns it into a slope
+        assert!(filtered[row][19] < 0.5 && filtered[row][20] > 9.5);
+        assert!(blurred[row][19] > 3.0 && blurred[row][20] < 7.0);
+        // The noise is smoothed
+        assert!((filtered[row][5] - filtered[row][6]).abs() < 0.1);
+
+        assert_eq!(Smoothing::from_str("auto"), Ok(Smoothing::Auto));
+        assert_eq!(Smoothing::from_str("0"), Ok(Smoothing::None));
+        assert_eq!(Smoothing::from_str("2.5"), Ok(Smoothing::Sigma(2.5)));
+        assert!(Smoothing::from_str("-1").is_err());
+    }
+
     #[test]
     fn test_terrarium_height_decoding() {
         // Test known Terrarium RGB values
@@ -0,0 +1,207 @@
+//! Entities placed in the generated world, such as boats, minecarts, animals, armor stands
+//! and item frames.
+//!
+//! Since 1.17 entities are stored apart from the blocks, in the region files of the
+//! `entities` folder. Older versions keep them in the `Entities` list of their chunk.
+//! Bedrock worlds and schematics are written without entities.
+
+use crate::mc_version::McVersion;
+use fastnbt::{IntArray, Value};
+use std::collections::HashMap;
+
+/// Directions of hanging entities, as stored in `Facing`
+#[allow(unused)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Facing {
+    Down = 0,
+    Up = 1,
+    North = 2,
+    South = 3,
+    West = 4,
+    East = 5,
+}
+
+#[derive(Clone, Debug)]
+pub struct Entity {
+    /// Namespaced entity id of the latest version
+    id: String,
+    pos: (f64, f64, f64),
+    yaw: f32,
+    /// Entity specific NBT
+    data: HashMap<String, Value>,
+}
+
+impl Entity {
+    pub fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            pos: (0.0, 0.0, 0.0),
+            yaw: 0.0,
+            data: HashMap::new(),
+        }
+    }
+
+    /// An item frame holding one item, hanging in the block it is placed at
+    pub fn item_frame(facing: Facing, item: &str) -> Self {
+        Self::new("minecraft:item_frame")
+            .with_data("Facing", Value::Byte(facing as i8))
+            .with_data("Item", item_nbt(item))
+    }
+
+    /// Rotation around the vertical axis in degrees, 0 faces south
+    pub fn with_yaw(mut self, yaw: f32) -> Self {
+        self.yaw = yaw;
+        self
+    }
+
+    pub fn with_data(mut self, key: &str, value: Value) -> Self {
+        self.data.insert(key.to_string(), value);
+        self
+    }
+
+    /// Moves the entity to the given absolute position
+    pub fn at(mut self, x: f64, y: f64, z: f64) -> Self {
+        self.pos = (x, y, z);
+        self
+    }
+
+    /// Builds the NBT of the entity in the format of the target version, with a new UUID.
+    /// Legacy versions get the entity shifted up like its chunk.
+    pub fn to_nbt(&self, version: McVersion) -> Value {
+        let (x, y, z) = self.pos;
+        let y = y + f64::from(version.y_offset());
+
+        let mut nbt = self.data.clone();
+
+        // Boats got an id per wood type in 1.21.2
+        match self.id.strip_suffix("_boat") {
+            Some(wood) if version < McVersion::Latest => {
+                let wood = wood.strip_prefix("minecraft:").unwrap_or(wood);
+                nbt.insert(
+                    "id".to_string(),
+                    Value::String("minecraft:boat".to_string()),
+                );
+                nbt.insert("Type".to_string(), Value::String(wood.to_string()));
+            }
+            _ => {
+                nbt.insert("id".to_string(), Value::String(self.id.clone()));
+            }
+        }
+
+        // Hanging entities also store the block they occupy
+        if nbt.contains_key("Facing") {
+            nbt.insert("TileX".to_string(), Value::Int(x.floor() as i32));
+            nbt.insert("TileY".to_string(), Value::Int(y.floor() as i32));
+            nbt.insert("TileZ".to_string(), Value::Int(z.floor() as i32));
+        }
+
+        // Item stacks count with a byte before 1.20.5
+        if version < McVersion::V1_20_5 {
+            if let Some(Value::Compound(item)) = nbt.get_mut("Item") {
+                if let Some(Value::Int(count)) = item.remove("count") {
+                    item.insert("Count".to_string(), Value::Byte(count as i8));
+                }
+            }
+        }
+
+        nbt.insert(
+            "Pos".to_string(),
+            Value::List(vec![Value::Double(x), Value::Double(y), Value::Double(z)]),
+        );
+        nbt.insert(
+            "Motion".to_string(),
+            Value::List(vec![Value::Double(0.0); 3]),
+        );
+        nbt.insert(
+            "Rotation".to_string(),
+            Value::List(vec![Value::Float(self.yaw), Value::Float(0.0)]),
+        );
+        nbt.insert(
+            "UUID".to_string(),
+            Value::IntArray(IntArray::new(rand::random::<[i32; 4]>().to_vec())),
+        );
+
+        Value::Compound(nbt)
+    }
+}
+
+/// A stack of a single item
+fn item_nbt(id: &str) -> Value {
+    Value::Compound(HashMap::from([
+        ("id".to_string(), Value::String(id.to_string())),
+        ("count".to_string(), Value::Int(1)),
+    ]))
+}
+
+/// The NBT of a chunk in the region files of the `entities` folder
+pub fn entity_chunk_nbt(
+    chunk_x: i32,
+    chunk_z: i32,
+    entities: Vec<Value>,
+    version: McVersion,
+) -> Value {
+    Value::Compound(HashMap::from([
+        (
+            "DataVersion".to_string(),
+            Value::Int(version.data_version()),
+        ),
+        (
+            "Position".to_string(),
+            Value::IntArray(IntArray::new(vec![chunk_x, chunk_z])),
+        ),
+        ("Entities".to_string(), Value::List(entities)),
+    ]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compound(value: Value) -> HashMap<String, Value> {
+        match value {
+            Value::Compound(map) => map,
+            _ => panic!("Entity NBT is no compound"),
+        }
+    }
+
+    #[test]
+    fn test_entity_nbt() {
+        let boat = Entity::new("minecraft:oak_boat").at(1.5, -61.0, 2.5);
+
+        let latest = compound(boat.to_nbt(McVersion::Latest));
+        assert_eq!(
+            latest["id"],
+            Value::String("minecraft:oak_boat".to_string())
+        );
+        assert_eq!(
+            latest["Pos"],
+            Value::List(vec![
+                Value::Double(1.5),
+                Value::Double(-61.0),
+                Value::Double(2.5)
+            ])
+        );
+        assert!(matches!(&latest["UUID"], Value::IntArray(uuid) if uuid.len() == 4));
+
+        let legacy = compound(boat.to_nbt(McVersion::V1_16));
+        assert_eq!(legacy["id"], Value::String("minecraft:boat".to_string()));
+        assert_eq!(legacy["Type"], Value::String("oak".to_string()));
+        let Value::List(pos) = &legacy["Pos"] else {
+            panic!("Pos is no list");
+        };
+        assert_eq!(pos[1], Value::Double(3.0));
+    }
+
+    #[test]
+    fn test_item_frame() {
+        let frame = Entity::item_frame(Facing::South, "minecraft:map").at(4.5, -60.0, 7.5);
+
+        let latest = compound(frame.to_nbt(McVersion::Latest));
+        assert_eq!(latest["Facing"], Value::Byte(3));
+        assert_eq!(latest["TileY"], Value::Int(-60));
+        assert_eq!(compound(latest["Item"].clone())["count"], Value::Int(1));
+
+        let legacy = compound(frame.to_nbt(McVersion::V1_20));
+        assert_eq!(compound(legacy["Item"].clone())["Count"], Value::Byte(1));
+    }
+}
@@ -1,8 +1,20 @@
 use geo::{Contains, LineString, Point, Polygon};
 use itertools::Itertools;
+use std::cell::Cell;
 use std::collections::{HashSet, VecDeque};
 use std::time::{Duration, Instant};
 
+thread_local! {
+    /// Number of flood fills on this thread that stopped at the timeout
+    static TIMEOUTS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Number of flood fills that stopped at the timeout on the current thread so far. Comparing
+/// it before and after processing an element tells if its area was cut short.
+pub fn timeouts_on_thread() -> usize {
+    TIMEOUTS.get()
+}
+
 /// Main flood fill function with automatic algorithm selection
 /// Chooses the best algorithm based on polygon size and complexity
 pub fn flood_fill_area(
@@ -75,6 +87,7 @@ fn optimized_flood_fill_area(
             if filled_area.len() % 100 == 0 {
                 if let Some(timeout) = timeout {
                     if start_time.elapsed() > *timeout {
+                        TIMEOUTS.set(TIMEOUTS.get() + 1);
                         return filled_area;
                     }
                 }
@@ -163,6 +176,7 @@ fn original_flood_fill_area(
             if global_visited.len() % 200 == 0 {
                 if let Some(timeout) = timeout {
                     if &start_time.elapsed() > timeout {
+                        TIMEOUTS.set(TIMEOUTS.get() + 1);
                         return filled_area;
                     }
                 }
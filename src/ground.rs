@@ -1,12 +1,13 @@
 use crate::args::Args;
-use crate::coordinate_system::{cartesian::XZPoint, geographic::LLBBox};
+use crate::coordinate_system::cartesian::XZPoint;
 use crate::elevation_data::{fetch_elevation_data, ElevationData};
 use crate::progress::emit_gui_progress_update;
 use colored::Colorize;
 use image::{Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
 
 /// Represents terrain data and elevation settings
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Ground {
     pub elevation_enabled: bool,
     ground_level: i32,
@@ -22,16 +23,31 @@ impl Ground {
         }
     }
 
-    pub fn new_enabled(bbox: &LLBBox, scale: f64, ground_level: i32) -> Self {
-        let elevation_data = fetch_elevation_data(bbox, scale, ground_level)
-            .expect("Failed to fetch elevation data");
+    pub fn new_enabled(args: &Args) -> Self {
+        let elevation_data = fetch_elevation_data(args).expect("Failed to fetch elevation data");
         Self {
             elevation_enabled: true,
-            ground_level,
+            ground_level: args.ground_level,
             elevation_data: Some(elevation_data),
         }
     }
 
+    /// Terrain with the given heights in Y coordinates, one grid point per block
+    #[cfg(test)]
+    pub fn from_heights(heights: Vec<Vec<f64>>) -> Self {
+        let (width, height) = (heights[0].len(), heights.len());
+        Self {
+            elevation_enabled: true,
+            ground_level: -62,
+            elevation_data: Some(ElevationData {
+                heights,
+                width,
+                height,
+                sea_level: -62.0,
+            }),
+        }
+    }
+
     /// Returns the ground level at the given coordinates
     #[inline(always)]
     pub fn level(&self, coord: XZPoint) -> i32 {
@@ -44,6 +60,50 @@ impl Ground {
         self.interpolate_height(x_ratio, z_ratio, data)
     }
 
+    /// Sets the ground level at the given coordinates, e.g. to level a lake. Changes the grid
+    /// points `level` interpolates between, so blocks between changed coordinates get the same
+    /// level. Flat ground stays unchanged.
+    pub fn set_level(&mut self, coord: XZPoint, level: i32) {
+        self.update_grid_points(coord, |_| level as f64);
+    }
+
+    /// Lowers the ground level at the given coordinates to at most `level`, e.g. to carve a
+    /// riverbed. Ground that is lower already is kept, so carving never raises it.
+    pub fn lower_level(&mut self, coord: XZPoint, level: i32) {
+        self.update_grid_points(coord, |height| height.min(level as f64));
+    }
+
+    /// Applies `update` to the grid points the level at the given coordinates is interpolated from
+    fn update_grid_points(&mut self, coord: XZPoint, update: impl Fn(f64) -> f64) {
+        if !self.elevation_enabled || coord.x < 0 || coord.z < 0 {
+            return;
+        }
+        let Some(data) = self.elevation_data.as_mut() else {
+            return;
+        };
+        if coord.x as usize > data.width || coord.z as usize > data.height {
+            return;
+        }
+
+        // Same mapping as in get_data_coordinates and interpolate_height
+        let x: f64 = coord.x as f64 / data.width as f64 * (data.width - 1) as f64;
+        let z: f64 = coord.z as f64 / data.height as f64 * (data.height - 1) as f64;
+        let (x0, z0) = (x.floor() as usize, z.floor() as usize);
+        let (x1, z1) = ((x0 + 1).min(data.width - 1), (z0 + 1).min(data.height - 1));
+        for (gx, gz) in [(x0, z0), (x1, z0), (x0, z1), (x1, z1)] {
+            data.heights[gz][gx] = update(data.heights[gz][gx]);
+        }
+    }
+
+    /// Returns the Y coordinate of sea level: the ground level on flat terrain, otherwise where
+    /// 0 m of the elevation data is placed
+    pub fn sea_level(&self) -> i32 {
+        match &self.elevation_data {
+            Some(data) if self.elevation_enabled => data.sea_level.round() as i32,
+            _ => self.ground_level,
+        }
+    }
+
     #[allow(unused)]
     #[inline(always)]
     pub fn min_level<I: Iterator<Item = XZPoint>>(&self, coords: I) -> Option<i32> {
@@ -70,12 +130,19 @@ impl Ground {
         (x_ratio.clamp(0.0, 1.0), z_ratio.clamp(0.0, 1.0))
     }
 
-    /// Interpolates height value from the elevation grid
+    /// Interpolates height value from the elevation grid, blending the four surrounding grid
+    /// points so slopes don't turn into terraces
     #[inline(always)]
     fn interpolate_height(&self, x_ratio: f64, z_ratio: f64, data: &ElevationData) -> i32 {
-        let x: usize = ((x_ratio * (data.width - 1) as f64).round() as usize).min(data.width - 1);
-        let z: usize = ((z_ratio * (data.height - 1) as f64).round() as usize).min(data.height - 1);
-        data.heights[z][x]
+        let x: f64 = x_ratio * (data.width - 1) as f64;
+        let z: f64 = z_ratio * (data.height - 1) as f64;
+        let (x0, z0) = (x.floor() as usize, z.floor() as usize);
+        let (x1, z1) = ((x0 + 1).min(data.width - 1), (z0 + 1).min(data.height - 1));
+        let (tx, tz) = (x - x0 as f64, z - z0 as f64);
+
+        let top: f64 = data.heights[z0][x0] * (1.0 - tx) + data.heights[z0][x1] * tx;
+        let bottom: f64 = data.heights[z1][x0] * (1.0 - tx) + data.heights[z1][x1] * tx;
+        (top * (1.0 - tz) + bottom * tz).round() as i32
     }
 
     fn save_debug_image(&self, filename: &str) {
@@ -93,8 +160,8 @@ impl Ground {
         let mut img: image::ImageBuffer<Rgb<u8>, Vec<u8>> =
             RgbImage::new(width as u32, height as u32);
 
-        let mut min_height: i32 = i32::MAX;
-        let mut max_height: i32 = i32::MIN;
+        let mut min_height: f64 = f64::MAX;
+        let mut max_height: f64 = f64::MIN;
 
         for row in heights {
             for &h in row {
@@ -105,8 +172,7 @@ impl Ground {
 
         for (y, row) in heights.iter().enumerate() {
             for (x, &h) in row.iter().enumerate() {
-                let normalized: u8 =
-                    (((h - min_height) as f64 / (max_height - min_height) as f64) * 255.0) as u8;
+                let normalized: u8 = (((h - min_height) / (max_height - min_height)) * 255.0) as u8;
                 img.put_pixel(
                     x as u32,
                     y as u32,
@@ -132,7 +198,7 @@ pub fn generate_ground_data(args: &Args) -> Ground {
     if args.terrain {
         println!("{} Fetching elevation...", "[3/7]".bold());
         emit_gui_progress_update(15.0, "Fetching elevation...");
-        let ground = Ground::new_enabled(&args.bbox, args.scale, args.ground_level);
+        let ground = Ground::new_enabled(args);
         if args.debug {
             ground.save_debug_image("elevation_debug");
         }
@@ -1,13 +1,17 @@
 use crate::args::Args;
 use crate::coordinate_system::cartesian::XZPoint;
 use crate::coordinate_system::geographic::{LLBBox, LLPoint};
+use crate::coordinate_system::projection::Projection;
 use crate::data_processing;
+use crate::elevation_data::Smoothing;
 use crate::ground::{self, Ground};
 use crate::map_transformation;
+use crate::mc_version::McVersion;
 use crate::osm_parser;
 use crate::progress;
 use crate::retrieve_data;
 use crate::version_check;
+use crate::world_editor::WorldFormat;
 use fastnbt::Value;
 use flate2::read::GzDecoder;
 use fs2::FileExt;
@@ -258,6 +262,9 @@ fn create_new_world(base_path: &Path) -> Result<String, String> {
             let current_time_millis = current_time.as_millis() as i64;
             data.insert("LastPlayed".to_string(), Value::Long(current_time_millis));
 
+            // Mark the world as created by the version the chunks are written for
+            set_level_version(data, McVersion::default());
+
             // Update player position and rotation
             if let Some(Value::Compound(ref mut player)) = data.get_mut("Player") {
                 if let Some(Value::List(ref mut pos)) = player.get_mut("Pos") {
@@ -306,6 +313,21 @@ fn create_new_world(base_path: &Path) -> Result<String, String> {
     Ok(new_world_path.display().to_string())
 }
 
+/// Sets the game version in the `Data` compound of level.dat
+fn set_level_version(data: &mut std::collections::HashMap<String, Value>, version: McVersion) {
+    data.insert(
+        "DataVersion".to_string(),
+        Value::Int(version.data_version()),
+    );
+    if let Some(Value::Compound(ref mut level_version)) = data.get_mut("Version") {
+        level_version.insert("Id".to_string(), Value::Int(version.data_version()));
+        level_version.insert(
+            "Name".to_string(),
+            Value::String(version.release_name().to_string()),
+        );
+    }
+}
+
 /// Adds localized area name to the world name in level.dat
 fn add_localized_world_name(world_path: PathBuf, bbox: &LLBBox) -> PathBuf {
     // Only proceed if the path exists
@@ -419,6 +441,8 @@ fn update_player_position(
     spawn_point: Option<(f64, f64)>,
     bbox_text: String,
     scale: f64,
+    projection: Projection,
+    origin: Option<LLPoint>,
 ) -> Result<(), String> {
     use crate::coordinate_system::transformation::CoordTransformer;
 
@@ -438,8 +462,9 @@ fn update_player_position(
     }
 
     // Convert lat/lng to Minecraft coordinates
-    let (transformer, _) = CoordTransformer::llbbox_to_xzbbox(&llbbox, scale)
-        .map_err(|e| format!("Failed to build transformation on coordinate systems:\n{e}"))?;
+    let (transformer, _) =
+        CoordTransformer::llbbox_to_xzbbox_projected(&llbbox, scale, projection, origin)
+            .map_err(|e| format!("Failed to build transformation on coordinate systems:\n{e}"))?;
 
     let xzpoint = transformer.transform_point(llpoint);
 
@@ -525,6 +550,8 @@ pub fn update_player_spawn_y_after_generation(
     spawn_point: Option<(f64, f64)>,
     bbox_text: String,
     scale: f64,
+    projection: Projection,
+    origin: Option<LLPoint>,
     ground: &Ground,
 ) -> Result<(), String> {
     use crate::coordinate_system::transformation::CoordTransformer;
@@ -593,8 +620,9 @@ pub fn update_player_spawn_y_after_generation(
         // Parse coordinates for terrain lookup
         let llbbox = LLBBox::from_str(&bbox_text)
             .map_err(|e| format!("Failed to parse bounding box for spawn point:\n{e}"))?;
-        let (_, xzbbox) = CoordTransformer::llbbox_to_xzbbox(&llbbox, scale)
-            .map_err(|e| format!("Failed to build transformation:\n{e}"))?;
+        let (_, xzbbox) =
+            CoordTransformer::llbbox_to_xzbbox_projected(&llbbox, scale, projection, origin)
+                .map_err(|e| format!("Failed to build transformation:\n{e}"))?;
 
         // Calculate relative coordinates for ground system
         let relative_x = existing_spawn_x - xzbbox.min_x();
@@ -668,18 +696,39 @@ fn gui_start_generation(
     bbox_text: String,
     selected_world: String,
     world_scale: f64,
+    projection: String,
+    origin: String,
     ground_level: i32,
     floodfill_timeout: u64,
     terrain_enabled: bool,
+    vertical_exaggeration: f64,
+    terrain_smoothing: String,
+    preserve_edges: bool,
     interior_enabled: bool,
     roof_enabled: bool,
     fillground_enabled: bool,
     is_new_world: bool,
     spawn_point: Option<(f64, f64)>,
 ) -> Result<(), String> {
+    use clap::ValueEnum;
     use progress::emit_gui_error;
     use LLBBox;
 
+    let projection = Projection::from_str(&projection, true).unwrap_or_default();
+    // An empty origin places the bbox corner at 0,0
+    let origin = match origin.trim() {
+        "" => None,
+        origin => match LLPoint::from_str(origin) {
+            Ok(origin) => Some(origin),
+            Err(e) => {
+                let error_msg = format!("Failed to parse origin: {e}");
+                eprintln!("{error_msg}");
+                emit_gui_error(&error_msg);
+                return Err(error_msg);
+            }
+        },
+    };
+
     // If spawn point was chosen and the world is new, check and set the spawn point
     if is_new_world && spawn_point.is_some() {
         // Verify the spawn point is within bounds
@@ -704,6 +753,8 @@ fn gui_start_generation(
                     spawn_point,
                     bbox_text.clone(),
                     world_scale,
+                    projection,
+                    origin,
                 )
                 .map_err(|e| format!("Failed to set spawn point: {e}"))?;
             }
@@ -749,22 +800,65 @@ fn gui_start_generation(
                 save_json_file: None,
                 path: updated_world_path,
                 downloader: "requests".to_string(),
+                overpass_url: Vec::new(),
+                overpass_retries: 1,
+                overpass_backoff: 0,
+                overpass_query: None,
                 scale: world_scale,
+                origin,
+                projection,
                 ground_level,
                 terrain: terrain_enabled,
+                dem: None,
+                dem_crs: None,
+                sea_level: None,
+                vertical_scale: None,
+                vertical_exaggeration,
+                smoothing: Smoothing::from_str(&terrain_smoothing).unwrap_or_default(),
+                preserve_edges,
+                sea_depth: 8,
+                bathymetry: false,
                 interior: interior_enabled,
                 roof: roof_enabled,
                 fillground: fillground_enabled,
+                update: false,
+                tile_size: None,
+                parallel: false,
+                checkpoint: false,
+                resume: false,
+                format: WorldFormat::default(),
+                export: None,
+                render: None,
+                render_tiles: None,
+                polygon: None,
+                boundary_relation: None,
+                export_only: false,
+                mc_version: McVersion::default(),
+                blocks: None,
+                style: None,
                 debug: false,
                 timeout: Some(std::time::Duration::from_secs(floodfill_timeout)),
                 spawn_point,
             };
 
             // Run data fetch and world generation
-            match retrieve_data::fetch_data_from_overpass(args.bbox, args.debug, "requests", None) {
+            match retrieve_data::fetch_data_from_overpass(
+                args.bbox,
+                args.debug,
+                "requests",
+                None,
+                &retrieve_data::OverpassSettings::default(),
+            ) {
                 Ok(raw_data) => {
-                    let (mut parsed_elements, mut xzbbox) =
-                        osm_parser::parse_osm_data(raw_data, args.bbox, args.scale, args.debug);
+                    let (mut parsed_elements, mut xzbbox) = osm_parser::parse_osm_data(
+                        raw_data,
+                        args.bbox,
+                        None,
+                        args.scale,
+                        args.projection,
+                        args.origin,
+                        args.debug,
+                    );
                     parsed_elements.sort_by(|el1, el2| {
                         let (el1_priority, el2_priority) =
                             (osm_parser::get_priority(el1), osm_parser::get_priority(el2));
@@ -793,6 +887,7 @@ fn gui_start_generation(
                         args.bbox,
                         ground,
                         &args,
+                        None,
                     );
                     // Session lock will be automatically released when _session_lock goes out of scope
                     Ok(())
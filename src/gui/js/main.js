@@ -89,11 +89,20 @@ async function applyLocalization(localization) {
     "h2[data-localize='customization_settings']": "customization_settings",
     "label[data-localize='world_scale']": "world_scale",
     "label[data-localize='custom_bounding_box']": "custom_bounding_box",
+    "label[data-localize='projection']": "projection",
+    "label[data-localize='origin']": "origin",
     "label[data-localize='floodfill_timeout']": "floodfill_timeout",
     // DEPRECATED: Ground level localization removed
     // "label[data-localize='ground_level']": "ground_level",
     "label[data-localize='language']": "language",
     "label[data-localize='terrain']": "terrain",
+    "label[data-localize='vertical_exaggeration']": "vertical_exaggeration",
+    "label[data-localize='terrain_smoothing']": "terrain_smoothing",
+    "option[data-localize='smoothing_auto']": "smoothing_auto",
+    "option[data-localize='smoothing_none']": "smoothing_none",
+    "option[data-localize='smoothing_low']": "smoothing_low",
+    "option[data-localize='smoothing_high']": "smoothing_high",
+    "label[data-localize='preserve_edges']": "preserve_edges",
     "label[data-localize='interior']": "interior",
     "label[data-localize='roof']": "roof",
     "label[data-localize='fillground']": "fillground",
@@ -216,6 +225,8 @@ function initSettings() {
   const settingsModal = document.getElementById("settings-modal");
   const slider = document.getElementById("scale-value-slider");
   const sliderValue = document.getElementById("slider-value");
+  const exaggerationSlider = document.getElementById("exaggeration-slider");
+  const exaggerationValue = document.getElementById("exaggeration-value");
 
   // Open settings modal
   function openSettings() {
@@ -236,6 +247,9 @@ function initSettings() {
   slider.addEventListener("input", () => {
     sliderValue.textContent = parseFloat(slider.value).toFixed(2);
   });
+  exaggerationSlider.addEventListener("input", () => {
+    exaggerationValue.textContent = parseFloat(exaggerationSlider.value).toFixed(2);
+  });
 
   // Language selector
   const languageSelect = document.getElementById("language-select");
@@ -596,10 +610,15 @@ async function startGeneration() {
     }
 
     var terrain = document.getElementById("terrain-toggle").checked;
+    var vertical_exaggeration = parseFloat(document.getElementById("exaggeration-slider").value);
+    var terrain_smoothing = document.getElementById("smoothing-select").value;
+    var preserve_edges = document.getElementById("preserve-edges-toggle").checked;
     var interior = document.getElementById("interior-toggle").checked;
     var roof = document.getElementById("roof-toggle").checked;
     var fill_ground = document.getElementById("fillground-toggle").checked;
     var scale = parseFloat(document.getElementById("scale-value-slider").value);
+    var projection = document.getElementById("projection-select").value;
+    var origin = document.getElementById("origin-coords").value;
     var floodfill_timeout = parseInt(document.getElementById("floodfill-timeout").value, 10);
     // var ground_level = parseInt(document.getElementById("ground-level").value, 10);
     // DEPRECATED: Ground level input removed from UI
@@ -614,9 +633,14 @@ async function startGeneration() {
         bboxText: selectedBBox,
         selectedWorld: worldPath,
         worldScale: scale,
+        projection: projection,
+        origin: origin,
         groundLevel: ground_level,
         floodfillTimeout: floodfill_timeout,
         terrainEnabled: terrain,
+        verticalExaggeration: vertical_exaggeration,
+        terrainSmoothing: terrain_smoothing,
+        preserveEdges: preserve_edges,
         interiorEnabled: interior,
         roofEnabled: roof,
         fillgroundEnabled: fill_ground,
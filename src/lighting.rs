@@ -0,0 +1,532 @@
+//! Precomputed light and heightmaps for written chunks.
+//!
+//! Chunks that are marked as lit are loaded by the game as they are instead of being relit
+//! on first load. Light is computed for each chunk on a grid that includes the 15 blocks of
+//! its neighbors that light can travel from. Blocks are classified by name, so partial blocks
+//! such as slabs and stairs are approximated as slightly dimming light in all directions.
+
+use crate::block_definitions::Block;
+use fastnbt::{ByteArray, LongArray, Value};
+use std::collections::{HashMap, VecDeque};
+
+/// Lowest block of the world, heightmaps are relative to it
+const WORLD_MIN_Y: i32 = -64;
+
+/// Distance light travels into the neighboring chunks
+const PADDING: i32 = 15;
+const GRID_SIZE: usize = 16 + 2 * PADDING as usize;
+
+/// How a block affects light and heightmaps
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LightProps {
+    /// Light levels lost when light passes through the block (0-15)
+    pub opacity: u8,
+    /// Light level emitted by the block
+    pub emission: u8,
+    /// Whether entities collide with the block or it contains fluid
+    pub motion_blocking: bool,
+    pub is_air: bool,
+}
+
+impl LightProps {
+    pub const AIR: LightProps = LightProps {
+        opacity: 0,
+        emission: 0,
+        motion_blocking: false,
+        is_air: true,
+    };
+
+    /// Classifies a block by its name, with or without namespace
+    pub fn of(name: &str) -> Self {
+        let name = name.rsplit(':').next().unwrap_or(name);
+        if matches!(name, "air" | "cave_air" | "void_air") {
+            return Self::AIR;
+        }
+
+        let emission = match name {
+            "glowstone" | "sea_lantern" | "lantern" | "jack_o_lantern" | "shroomlight"
+            | "beacon" | "lava" | "campfire" | "end_gateway" => 15,
+            _ if name.ends_with("froglight") => 15,
+            "torch" | "wall_torch" | "end_rod" => 14,
+            "soul_lantern" | "soul_torch" | "soul_wall_torch" | "soul_campfire" => 10,
+            "magma_block" => 3,
+            _ => 0,
+        };
+
+        // Blocks without collision, light passes through them
+        let passable = matches!(
+            name,
+            "short_grass"
+                | "grass"
+                | "tall_grass"
+                | "fern"
+                | "large_fern"
+                | "dead_bush"
+                | "dandelion"
+                | "poppy"
+                | "blue_orchid"
+                | "allium"
+                | "azure_bluet"
+                | "oxeye_daisy"
+                | "cornflower"
+                | "lily_of_the_valley"
+                | "sunflower"
+                | "lilac"
+                | "rose_bush"
+                | "peony"
+                | "wheat"
+                | "carrots"
+                | "potatoes"
+                | "beetroots"
+                | "sugar_cane"
+                | "vine"
+                | "snow"
+                | "lever"
+                | "redstone_wire"
+                | "torch"
+                | "wall_torch"
+                | "soul_torch"
+                | "soul_wall_torch"
+        ) || [
+            "_tulip",
+            "_sapling",
+            "rail",
+            "_sign",
+            "_pressure_plate",
+            "_button",
+        ]
+        .iter()
+        .any(|suffix| name.ends_with(suffix));
+
+        // Blocks with collision that let light through
+        let see_through = (name.contains("glass") && name != "tinted_glass")
+            || matches!(
+                name,
+                "iron_bars"
+                    | "ladder"
+                    | "scaffolding"
+                    | "chest"
+                    | "anvil"
+                    | "brewing_stand"
+                    | "cauldron"
+                    | "lantern"
+                    | "soul_lantern"
+                    | "end_rod"
+                    | "campfire"
+                    | "soul_campfire"
+            )
+            || [
+                "_fence",
+                "_fence_gate",
+                "_wall",
+                "_door",
+                "_trapdoor",
+                "_carpet",
+                "_bed",
+            ]
+            .iter()
+            .any(|suffix| name.ends_with(suffix));
+
+        // Blocks that dim light by one level
+        let filtering = name.ends_with("_leaves")
+            || name.ends_with("_slab")
+            || name.ends_with("_stairs")
+            || matches!(
+                name,
+                "water"
+                    | "lava"
+                    | "ice"
+                    | "cobweb"
+                    | "farmland"
+                    | "dirt_path"
+                    | "slime_block"
+                    | "honey_block"
+            );
+
+        let opacity = if passable || see_through {
+            0
+        } else if filtering {
+            1
+        } else {
+            15
+        };
+        let motion_blocking = !passable && name != "cobweb";
+
+        LightProps {
+            opacity,
+            emission,
+            motion_blocking,
+            is_air: false,
+        }
+    }
+}
+
+/// Cache of the light properties of blocks, indexed by block id
+#[derive(Default)]
+pub struct LightCache {
+    props: Vec<Option<LightProps>>,
+}
+
+impl LightCache {
+    pub fn get(&mut self, block: Block) -> LightProps {
+        let id = usize::from(block.id());
+        if id >= self.props.len() {
+            self.props.resize(id + 1, None);
+        }
+        *self.props[id].get_or_insert_with(|| LightProps::of(block.name()))
+    }
+}
+
+/// Blocks of a chunk and the surrounding blocks that light can reach it from
+pub struct LightGrid {
+    min_y: i32,
+    height: usize,
+    opacity: Vec<u8>,
+    emission: Vec<u8>,
+}
+
+impl LightGrid {
+    /// Creates a grid of air spanning the given sections
+    pub fn new(min_section: i8, max_section: i8) -> Self {
+        let min_y = i32::from(min_section) * 16;
+        let height = (i32::from(max_section) - i32::from(min_section) + 1).max(0) as usize * 16;
+        let len = GRID_SIZE * GRID_SIZE * height;
+        Self {
+            min_y,
+            height,
+            opacity: vec![0; len],
+            emission: vec![0; len],
+        }
+    }
+
+    #[inline]
+    fn index(x: usize, y: usize, z: usize) -> usize {
+        (y * GRID_SIZE + z) * GRID_SIZE + x
+    }
+
+    /// Copies a section into the grid. `chunk_dx` and `chunk_dz` give the position of its
+    /// chunk relative to the lit chunk, `props` returns the properties of the block at a
+    /// section index (y * 256 + z * 16 + x).
+    pub fn set_section(
+        &mut self,
+        chunk_dx: i32,
+        chunk_dz: i32,
+        section_y: i8,
+        mut props: impl FnMut(usize) -> LightProps,
+    ) {
+        let base_y = i32::from(section_y) * 16 - self.min_y;
+        if base_y < 0 || base_y as usize >= self.height {
+            return;
+        }
+        let base_x = chunk_dx * 16 + PADDING;
+        let base_z = chunk_dz * 16 + PADDING;
+        // Only the part of the section that lies within the grid
+        let range = |base: i32| (-base).max(0)..(GRID_SIZE as i32 - base).min(16);
+
+        for y in 0..16 {
+            for z in range(base_z) {
+                for x in range(base_x) {
+                    let block = props((y * 256 + z * 16 + x) as usize);
+                    let index = Self::index(
+                        (base_x + x) as usize,
+                        base_y as usize + y as usize,
+                        (base_z + z) as usize,
+                    );
+                    self.opacity[index] = block.opacity;
+                    self.emission[index] = block.emission;
+                }
+            }
+        }
+    }
+
+    /// Computes sky and block light of all blocks in the grid
+    pub fn compute(&self) -> ChunkLight {
+        ChunkLight {
+            min_y: self.min_y,
+            height: self.height,
+            sky: self.sky_light(),
+            block: self.block_light(),
+        }
+    }
+
+    fn sky_light(&self) -> Vec<u8> {
+        let mut light = vec![0; self.opacity.len()];
+
+        // Full sky light reaches down until the first block that is not transparent
+        let mut lowest_lit = vec![self.height; GRID_SIZE * GRID_SIZE];
+        for z in 0..GRID_SIZE {
+            for x in 0..GRID_SIZE {
+                for y in (0..self.height).rev() {
+                    let index = Self::index(x, y, z);
+                    if self.opacity[index] != 0 {
+                        break;
+                    }
+                    light[index] = 15;
+                    lowest_lit[z * GRID_SIZE + x] = y;
+                }
+            }
+        }
+
+        // It spreads from where it borders blocks that are not fully lit
+        let mut queue = VecDeque::new();
+        for z in 0..GRID_SIZE {
+            for x in 0..GRID_SIZE {
+                let lowest = lowest_lit[z * GRID_SIZE + x];
+                let mut highest_neighbor = lowest;
+                for (nx, nz) in horizontal_neighbors(x, z) {
+                    highest_neighbor = highest_neighbor.max(lowest_lit[nz * GRID_SIZE + nx]);
+                }
+                for y in lowest..highest_neighbor.max(lowest + 1).min(self.height) {
+                    queue.push_back(Self::index(x, y, z));
+                }
+            }
+        }
+
+        self.propagate(&mut light, queue);
+        light
+    }
+
+    fn block_light(&self) -> Vec<u8> {
+        let mut light = self.emission.clone();
+        let queue = (0..light.len()).filter(|&i| light[i] > 0).collect();
+        self.propagate(&mut light, queue);
+        light
+    }
+
+    fn propagate(&self, light: &mut [u8], mut queue: VecDeque<usize>) {
+        let layer = GRID_SIZE * GRID_SIZE;
+        while let Some(index) = queue.pop_front() {
+            let level = light[index];
+            if level <= 1 {
+                continue;
+            }
+
+            let x = index % GRID_SIZE;
+            let z = index / GRID_SIZE % GRID_SIZE;
+            let y = index / layer;
+            let neighbors = [
+                (x > 0).then(|| index - 1),
+                (x + 1 < GRID_SIZE).then(|| index + 1),
+                (z > 0).then(|| index - GRID_SIZE),
+                (z + 1 < GRID_SIZE).then(|| index + GRID_SIZE),
+                (y > 0).then(|| index - layer),
+                (y + 1 < self.height).then(|| index + layer),
+            ];
+
+            for neighbor in neighbors.into_iter().flatten() {
+                let new_level = level.saturating_sub(self.opacity[neighbor].max(1));
+                if new_level > light[neighbor] {
+                    light[neighbor] = new_level;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+    }
+}
+
+fn horizontal_neighbors(x: usize, z: usize) -> impl Iterator<Item = (usize, usize)> {
+    [
+        (x.wrapping_sub(1), z),
+        (x + 1, z),
+        (x, z.wrapping_sub(1)),
+        (x, z + 1),
+    ]
+    .into_iter()
+    .filter(|&(x, z)| x < GRID_SIZE && z < GRID_SIZE)
+}
+
+/// Computed light of a `LightGrid`
+pub struct ChunkLight {
+    min_y: i32,
+    height: usize,
+    sky: Vec<u8>,
+    block: Vec<u8>,
+}
+
+impl ChunkLight {
+    /// Sky and block light of a section of the lit chunk as stored in chunk NBT: 2048 bytes
+    /// with two 4-bit values each, the first in the lower bits
+    pub fn section_nbt(&self, section_y: i8) -> Option<(Value, Value)> {
+        let base_y = i32::from(section_y) * 16 - self.min_y;
+        if base_y < 0 || base_y as usize + 16 > self.height {
+            return None;
+        }
+
+        let nibbles = |light: &[u8]| {
+            let mut data = vec![0i8; 2048];
+            for i in 0..4096 {
+                let index = LightGrid::index(
+                    (i & 15) + PADDING as usize,
+                    base_y as usize + (i >> 8),
+                    (i >> 4 & 15) + PADDING as usize,
+                );
+                data[i / 2] |= ((light[index] & 15) << (4 * (i % 2))) as i8;
+            }
+            Value::ByteArray(ByteArray::new(data))
+        };
+
+        Some((nibbles(&self.sky), nibbles(&self.block)))
+    }
+}
+
+/// Heights of the highest blocks of each column, above the bottom of the world
+pub struct Heightmaps {
+    world_surface: [u16; 256],
+    motion_blocking: [u16; 256],
+}
+
+impl Default for Heightmaps {
+    fn default() -> Self {
+        Self {
+            world_surface: [0; 256],
+            motion_blocking: [0; 256],
+        }
+    }
+}
+
+impl Heightmaps {
+    /// Includes the blocks of a section, `props` returns the properties of the block at a
+    /// section index (y * 256 + z * 16 + x)
+    pub fn add_section(&mut self, section_y: i8, mut props: impl FnMut(usize) -> LightProps) {
+        for i in 0..4096 {
+            let block = props(i);
+            if block.is_air {
+                continue;
+            }
+            let column = i & 255;
+            let height = (i32::from(section_y) * 16 + (i >> 8) as i32 + 1 - WORLD_MIN_Y) as u16;
+            self.world_surface[column] = self.world_surface[column].max(height);
+            if block.motion_blocking {
+                self.motion_blocking[column] = self.motion_blocking[column].max(height);
+            }
+        }
+    }
+
+    /// The `Heightmaps` compound of a chunk
+    pub fn to_nbt(&self) -> Value {
+        Value::Compound(HashMap::from([
+            (
+                "WORLD_SURFACE".to_string(),
+                pack_heightmap(&self.world_surface),
+            ),
+            (
+                "MOTION_BLOCKING".to_string(),
+                pack_heightmap(&self.motion_blocking),
+            ),
+        ]))
+    }
+}
+
+/// Packs 9-bit heights, 7 per long like block states
+fn pack_heightmap(heights: &[u16; 256]) -> Value {
+    let longs = heights
+        .chunks(7)
+        .map(|heights| {
+            heights.iter().enumerate().fold(0i64, |long, (i, &height)| {
+                long | i64::from(height) << (9 * i)
+            })
+        })
+        .collect();
+    Value::LongArray(LongArray::new(longs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STONE: LightProps = LightProps {
+        opacity: 15,
+        emission: 0,
+        motion_blocking: true,
+        is_air: false,
+    };
+
+    fn light_at(light: &ChunkLight, sky: bool, x: usize, y: usize, z: usize) -> u8 {
+        let section = (y / 16) as i8 + (light.min_y / 16) as i8;
+        let (sky_data, block_data) = light.section_nbt(section).unwrap();
+        let Value::ByteArray(data) = (if sky { sky_data } else { block_data }) else {
+            unreachable!()
+        };
+        let i = (y % 16) * 256 + z * 16 + x;
+        (data[i / 2] as u8 >> (4 * (i % 2))) & 15
+    }
+
+    #[test]
+    fn test_classification() {
+        assert_eq!(LightProps::of("minecraft:stone").opacity, 15);
+        assert_eq!(LightProps::of("minecraft:glass").opacity, 0);
+        assert_eq!(LightProps::of("minecraft:tinted_glass").opacity, 15);
+        assert_eq!(LightProps::of("oak_leaves").opacity, 1);
+        assert_eq!(LightProps::of("minecraft:glowstone").emission, 15);
+        assert!(!LightProps::of("minecraft:poppy").motion_blocking);
+        assert!(LightProps::of("minecraft:oak_fence").motion_blocking);
+        assert!(LightProps::of("minecraft:air").is_air);
+    }
+
+    #[test]
+    fn test_sky_light_under_roof() {
+        // Stone floor at y 0 and a roof at y 4 covering x 0-7 of the chunk
+        let mut grid = LightGrid::new(0, 0);
+        grid.set_section(0, 0, 0, |i| {
+            let (x, y) = (i & 15, i >> 8);
+            if y == 0 || (y == 4 && x < 8) {
+                STONE
+            } else {
+                LightProps::AIR
+            }
+        });
+        let light = grid.compute();
+
+        assert_eq!(light_at(&light, true, 12, 1, 5), 15);
+        assert_eq!(light_at(&light, true, 0, 0, 5), 0);
+        // Light spreads sideways under the roof from both open sides, including the
+        // neighboring chunk, losing one level per block
+        assert_eq!(light_at(&light, true, 7, 2, 5), 14);
+        assert_eq!(light_at(&light, true, 0, 2, 5), 14);
+        assert_eq!(light_at(&light, true, 3, 2, 5), 11);
+        assert_eq!(light_at(&light, true, 4, 3, 5), 11);
+    }
+
+    #[test]
+    fn test_block_light() {
+        let glowstone = LightProps::of("minecraft:glowstone");
+        let mut grid = LightGrid::new(0, 0);
+        grid.set_section(0, 0, 0, |i| {
+            if i == 8 * 256 + 8 * 16 + 8 {
+                glowstone
+            } else {
+                LightProps::AIR
+            }
+        });
+        let light = grid.compute();
+        assert_eq!(light_at(&light, false, 8, 8, 8), 15);
+        assert_eq!(light_at(&light, false, 8, 8, 11), 12);
+        assert_eq!(light_at(&light, false, 10, 9, 8), 12);
+    }
+
+    #[test]
+    fn test_heightmaps() {
+        let mut heightmaps = Heightmaps::default();
+        heightmaps.add_section(-4, |i| if i >> 8 == 0 { STONE } else { LightProps::AIR });
+        heightmaps.add_section(0, |i| {
+            if i == 256 + 3 {
+                LightProps::of("minecraft:poppy")
+            } else {
+                LightProps::AIR
+            }
+        });
+
+        assert_eq!(heightmaps.world_surface[0], 1);
+        assert_eq!(heightmaps.world_surface[3], 66);
+        assert_eq!(heightmaps.motion_blocking[3], 1);
+
+        let Value::Compound(nbt) = heightmaps.to_nbt() else {
+            unreachable!()
+        };
+        let Value::LongArray(surface) = &nbt["WORLD_SURFACE"] else {
+            unreachable!()
+        };
+        assert_eq!(surface.len(), 37);
+        assert_eq!(surface[0] >> 27 & 511, 66);
+    }
+}
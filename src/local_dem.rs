@@ -0,0 +1,656 @@
+//! Local elevation sources for `--dem`, sampled onto the same grid as the downloaded
+//! Terrarium tiles (see `elevation_data::HeightGrid`).
+//!
+//! Supported are GeoTIFF, SRTM HGT and ESRI ASCII grid files with heights in meters, either as
+//! a single file or a directory of files, and directories of Terrarium or Mapbox RGB tiles laid
+//! out as `{z}/{x}/{y}.png`. Files in projected coordinate systems are supported for UTM zones,
+//! see `Crs`. Only the part of a file covering the bounding box is read where the format allows.
+
+use crate::coordinate_system::crs::Crs;
+use crate::coordinate_system::geographic::{LLBBox, LLPoint};
+use crate::elevation_data::{decode_terrarium, get_tile_coordinates, sample_rgb_tile, HeightGrid};
+use image::Rgb;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use tiff::decoder::{Decoder, DecodingResult, Limits};
+use tiff::tags::Tag;
+use tiff::ColorType;
+
+/// GeoTIFF keys, see the GeoTIFF specification
+const GEO_KEY_RASTER_TYPE: u16 = 1025;
+const GEO_KEY_GEOGRAPHIC_TYPE: u16 = 2048;
+const GEO_KEY_PROJECTED_CS_TYPE: u16 = 3072;
+const RASTER_PIXEL_IS_POINT: u16 = 2;
+
+/// Missing samples in SRTM files
+const HGT_VOID: f64 = -32768.0;
+
+/// Pixels of a Terrarium tile have a red value around 128 for common heights, those of a
+/// Mapbox RGB tile one of 1 or 2
+const TERRARIUM_MIN_MEAN_RED: f64 = 64.0;
+
+type DemResult<T> = Result<T, Box<dyn Error>>;
+
+/// Samples the elevation data at `path` onto the grid. `crs` overrides the coordinate system
+/// of the files.
+pub fn sample_local_dem(path: &Path, crs: Option<Crs>, grid: &mut HeightGrid) -> DemResult<()> {
+    if !path.is_dir() {
+        return sample_file(path, crs, grid);
+    }
+
+    if !tile_zoom_levels(path)?.is_empty() {
+        return sample_tile_directory(path, grid);
+    }
+
+    let mut files: Vec<PathBuf> = fs::read_dir(path)?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|file| DemFormat::from_path(file).is_some())
+        .collect();
+    files.sort();
+    if files.is_empty() {
+        return Err(format!(
+            "No elevation files (.tif, .hgt, .asc) or tiles found in {}",
+            path.display()
+        )
+        .into());
+    }
+    for file in files {
+        sample_file(&file, crs, grid)?;
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DemFormat {
+    GeoTiff,
+    Hgt,
+    AsciiGrid,
+}
+
+impl DemFormat {
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "tif" | "tiff" => Some(Self::GeoTiff),
+            "hgt" => Some(Self::Hgt),
+            "asc" => Some(Self::AsciiGrid),
+            _ => None,
+        }
+    }
+}
+
+fn sample_file(path: &Path, crs: Option<Crs>, grid: &mut HeightGrid) -> DemResult<()> {
+    println!("Reading elevation data from {}", path.display());
+    let result = match DemFormat::from_path(path) {
+        Some(DemFormat::GeoTiff) => sample_geotiff(path, crs, grid),
+        Some(DemFormat::Hgt) => sample_hgt(path, grid),
+        Some(DemFormat::AsciiGrid) => sample_ascii_grid(path, crs, grid),
+        None => Err("unknown file type, expected .tif, .hgt or .asc".into()),
+    };
+    result.map_err(|e| format!("Failed to read elevation data from {}: {e}", path.display()).into())
+}
+
+/// Affine transformation from raster coordinates (column, row), with pixel corners at whole
+/// numbers, to coordinates of the coordinate system of the raster
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct GeoTransform {
+    /// Offset, factor of the column and factor of the row of x
+    x: [f64; 3],
+    /// Offset, factor of the column and factor of the row of y
+    y: [f64; 3],
+}
+
+impl GeoTransform {
+    /// Raster with the north west corner at `(x, y)` and square pixels of `size`
+    fn north_up(x: f64, y: f64, size_x: f64, size_y: f64) -> Self {
+        Self {
+            x: [x, size_x, 0.0],
+            y: [y, 0.0, -size_y],
+        }
+    }
+
+    fn apply(&self, col: f64, row: f64) -> (f64, f64) {
+        (
+            self.x[0] + self.x[1] * col + self.x[2] * row,
+            self.y[0] + self.y[1] * col + self.y[2] * row,
+        )
+    }
+
+    fn invert(&self, x: f64, y: f64) -> (f64, f64) {
+        let det = self.x[1] * self.y[2] - self.x[2] * self.y[1];
+        let (dx, dy) = (x - self.x[0], y - self.y[0]);
+        (
+            (self.y[2] * dx - self.x[2] * dy) / det,
+            (self.x[1] * dy - self.y[1] * dx) / det,
+        )
+    }
+
+    /// Moves the raster by a fraction of a pixel
+    fn shifted(self, d_col: f64, d_row: f64) -> Self {
+        let (x, y) = self.apply(d_col, d_row);
+        Self {
+            x: [x, self.x[1], self.x[2]],
+            y: [y, self.y[1], self.y[2]],
+        }
+    }
+}
+
+/// Geographic placement of a raster
+struct Georeference {
+    transform: GeoTransform,
+    crs: Crs,
+    nodata: Option<f64>,
+}
+
+impl Georeference {
+    /// Columns and rows of a raster of the given size that cover the bbox, `None` if the
+    /// raster lies outside of it
+    fn window(
+        &self,
+        width: usize,
+        height: usize,
+        bbox: &LLBBox,
+    ) -> Option<(Range<usize>, Range<usize>)> {
+        let (mut min_col, mut min_row) = (f64::MAX, f64::MAX);
+        let (mut max_col, mut max_row) = (f64::MIN, f64::MIN);
+        for lat_t in [0.0, 0.5, 1.0] {
+            for lng_t in [0.0, 0.5, 1.0] {
+                let lat = bbox.min().lat() + lat_t * (bbox.max().lat() - bbox.min().lat());
+                let lng = bbox.min().lng() + lng_t * (bbox.max().lng() - bbox.min().lng());
+                let (x, y) = self.crs.to_xy(LLPoint::new(lat, lng).ok()?);
+                let (col, row) = self.transform.invert(x, y);
+                (min_col, min_row) = (min_col.min(col), min_row.min(row));
+                (max_col, max_row) = (max_col.max(col), max_row.max(row));
+            }
+        }
+
+        // Edges of the bbox may be curved in the coordinate system of the raster
+        let margin_cols = 1.0 + (max_col - min_col) * 0.02;
+        let margin_rows = 1.0 + (max_row - min_row) * 0.02;
+        let clamp = |value: f64, len: usize| value.clamp(0.0, len as f64) as usize;
+        let cols = clamp(min_col - margin_cols, width)..clamp(max_col + margin_cols, width);
+        let rows = clamp(min_row - margin_rows, height)..clamp(max_row + margin_rows, height);
+        (!cols.is_empty() && !rows.is_empty()).then_some((cols, rows))
+    }
+
+    /// Adds the height of the pixel in the given column and row to the grid
+    fn add_pixel(&self, grid: &mut HeightGrid, col: usize, row: usize, height: f64) {
+        let is_nodata = self
+            .nodata
+            .is_some_and(|nodata| (height - nodata).abs() <= nodata.abs() * 1e-6);
+        if height.is_nan() || is_nodata {
+            return;
+        }
+        let (x, y) = self.transform.apply(col as f64 + 0.5, row as f64 + 0.5);
+        if let Ok(llpoint) = self.crs.to_llpoint(x, y) {
+            grid.add_sample(llpoint.lat(), llpoint.lng(), height);
+        }
+    }
+}
+
+fn sample_geotiff(path: &Path, crs: Option<Crs>, grid: &mut HeightGrid) -> DemResult<()> {
+    let mut decoder =
+        Decoder::new(BufReader::new(File::open(path)?))?.with_limits(Limits::unlimited());
+    let (width, height) = decoder.dimensions()?;
+    if !matches!(decoder.colortype()?, ColorType::Gray(_)) {
+        return Err("only single band GeoTIFFs are supported".into());
+    }
+
+    // Keys are stored after a header of 4 values, each as (key, location, count, value).
+    // Location 0 means the value is stored inline.
+    let geo_keys: Vec<u16> = decoder
+        .find_tag_unsigned_vec(Tag::GeoKeyDirectoryTag)?
+        .unwrap_or_default();
+    let geo_key = |key: u16| {
+        geo_keys
+            .get(4..)?
+            .chunks_exact(4)
+            .find(|entry| entry[0] == key && entry[1] == 0)
+            .map(|entry| entry[3])
+    };
+
+    let crs = match crs {
+        Some(crs) => crs,
+        None => match geo_key(GEO_KEY_PROJECTED_CS_TYPE).or(geo_key(GEO_KEY_GEOGRAPHIC_TYPE)) {
+            Some(code) => Crs::from_epsg(u32::from(code))?,
+            None => return Err("no coordinate system found, set it with --dem-crs".into()),
+        },
+    };
+
+    let transform = match decoder.find_tag(Tag::ModelTransformationTag)? {
+        Some(matrix) => {
+            let m = matrix.into_f64_vec()?;
+            if m.len() < 8 {
+                return Err("invalid ModelTransformationTag".into());
+            }
+            GeoTransform {
+                x: [m[3], m[0], m[1]],
+                y: [m[7], m[4], m[5]],
+            }
+        }
+        None => {
+            let scale = decoder.get_tag_f64_vec(Tag::ModelPixelScaleTag)?;
+            let tiepoint = decoder.get_tag_f64_vec(Tag::ModelTiepointTag)?;
+            if scale.len() < 2 || tiepoint.len() < 6 {
+                return Err("invalid ModelPixelScaleTag or ModelTiepointTag".into());
+            }
+            // The tiepoint places raster coordinates (i, j) at model coordinates (x, y)
+            GeoTransform::north_up(
+                tiepoint[3] - tiepoint[0] * scale[0],
+                tiepoint[4] + tiepoint[1] * scale[1],
+                scale[0],
+                scale[1],
+            )
+        }
+    };
+    // With PixelIsPoint, raster coordinates refer to the center of pixels
+    let transform = if geo_key(GEO_KEY_RASTER_TYPE) == Some(RASTER_PIXEL_IS_POINT) {
+        transform.shifted(-0.5, -0.5)
+    } else {
+        transform
+    };
+
+    let nodata = match decoder.find_tag(Tag::GdalNodata)? {
+        Some(value) => value
+            .into_string()?
+            .trim_matches(char::from(0))
+            .trim()
+            .parse()
+            .ok(),
+        None => None,
+    };
+    let georeference = Georeference {
+        transform,
+        crs,
+        nodata,
+    };
+
+    let Some((cols, rows)) = georeference.window(width as usize, height as usize, grid.bbox())
+    else {
+        return Ok(());
+    };
+
+    // Read only the strips or tiles overlapping the window
+    let (chunk_width, chunk_height) = decoder.chunk_dimensions();
+    let (chunk_width, chunk_height) = (chunk_width as usize, chunk_height as usize);
+    let chunks_across = (width as usize).div_ceil(chunk_width);
+    for chunk_row in rows.start / chunk_height..=(rows.end - 1) / chunk_height {
+        for chunk_col in cols.start / chunk_width..=(cols.end - 1) / chunk_width {
+            let index = (chunk_row * chunks_across + chunk_col) as u32;
+            let (data_width, data_height) = decoder.chunk_data_dimensions(index);
+            let values = decoding_result_to_f64(decoder.read_chunk(index)?);
+
+            for r in 0..data_height as usize {
+                let row = chunk_row * chunk_height + r;
+                if !rows.contains(&row) {
+                    continue;
+                }
+                for c in 0..data_width as usize {
+                    let col = chunk_col * chunk_width + c;
+                    if cols.contains(&col) {
+                        let height = values[r * data_width as usize + c];
+                        georeference.add_pixel(grid, col, row, height);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn decoding_result_to_f64(result: DecodingResult) -> Vec<f64> {
+    match result {
+        DecodingResult::U8(values) => values.into_iter().map(f64::from).collect(),
+        DecodingResult::U16(values) => values.into_iter().map(f64::from).collect(),
+        DecodingResult::U32(values) => values.into_iter().map(f64::from).collect(),
+        DecodingResult::U64(values) => values.into_iter().map(|v| v as f64).collect(),
+        DecodingResult::F32(values) => values.into_iter().map(f64::from).collect(),
+        DecodingResult::F64(values) => values,
+        DecodingResult::I8(values) => values.into_iter().map(f64::from).collect(),
+        DecodingResult::I16(values) => values.into_iter().map(f64::from).collect(),
+        DecodingResult::I32(values) => values.into_iter().map(f64::from).collect(),
+        DecodingResult::I64(values) => values.into_iter().map(|v| v as f64).collect(),
+    }
+}
+
+/// Parses the south west corner from the name of an SRTM file, e.g. "N48E011.hgt"
+fn hgt_corner(path: &Path) -> Option<(f64, f64)> {
+    let name = path.file_stem()?.to_str()?.to_uppercase();
+    let (lat_sign, rest) = match name.split_at_checked(1)? {
+        ("N", rest) => (1.0, rest),
+        ("S", rest) => (-1.0, rest),
+        _ => return None,
+    };
+    let (lat, rest) = rest.split_at_checked(2)?;
+    let (lng_sign, lng) = match rest.split_at_checked(1)? {
+        ("E", lng) => (1.0, lng),
+        ("W", lng) => (-1.0, lng),
+        _ => return None,
+    };
+    let lng = lng.get(..3)?;
+    Some((
+        lat_sign * lat.parse::<f64>().ok()?,
+        lng_sign * lng.parse::<f64>().ok()?,
+    ))
+}
+
+fn sample_hgt(path: &Path, grid: &mut HeightGrid) -> DemResult<()> {
+    let (lat, lng) =
+        hgt_corner(path).ok_or("file name should name the south west corner, e.g. N48E011.hgt")?;
+    let data = fs::read(path)?;
+
+    // Square grid of big endian 16 bit samples, overlapping neighbouring files by one sample
+    let size = ((data.len() / 2) as f64).sqrt() as usize;
+    if size < 2 || size * size * 2 != data.len() {
+        return Err("unexpected file size for SRTM data".into());
+    }
+    let step = 1.0 / (size - 1) as f64;
+
+    // Samples lie on whole multiples of the step, so the pixel around the first one starts
+    // half a step north west of the corner
+    let georeference = Georeference {
+        transform: GeoTransform::north_up(lng - step / 2.0, lat + 1.0 + step / 2.0, step, step),
+        crs: Crs::Geographic,
+        nodata: Some(HGT_VOID),
+    };
+
+    let Some((cols, rows)) = georeference.window(size, size, grid.bbox()) else {
+        return Ok(());
+    };
+    for row in rows {
+        for col in cols.clone() {
+            let index = 2 * (row * size + col);
+            let height = i16::from_be_bytes([data[index], data[index + 1]]);
+            georeference.add_pixel(grid, col, row, f64::from(height));
+        }
+    }
+    Ok(())
+}
+
+/// Header and values of an ESRI ASCII grid
+struct AsciiGrid {
+    ncols: usize,
+    nrows: usize,
+    transform: GeoTransform,
+    nodata: Option<f64>,
+    /// Rows from north to south
+    values: Vec<f64>,
+}
+
+fn parse_ascii_grid(text: &str) -> Result<AsciiGrid, String> {
+    let mut tokens = text.split_whitespace().peekable();
+    let mut header = std::collections::HashMap::new();
+    while let Some(key) = tokens.next_if(|token| token.starts_with(|c: char| c.is_alphabetic())) {
+        let value: f64 = tokens
+            .next()
+            .and_then(|value| value.parse().ok())
+            .ok_or(format!("invalid header value of {key}"))?;
+        header.insert(key.to_lowercase(), value);
+    }
+
+    let get = |key: &str| header.get(key).copied();
+    let (Some(ncols), Some(nrows)) = (get("ncols"), get("nrows")) else {
+        return Err("missing ncols or nrows".to_string());
+    };
+    let (ncols, nrows) = (ncols as usize, nrows as usize);
+    let (size_x, size_y) = match (get("cellsize"), get("dx"), get("dy")) {
+        (Some(size), _, _) => (size, size),
+        (None, Some(dx), Some(dy)) => (dx, dy),
+        _ => return Err("missing cellsize".to_string()),
+    };
+    // The lower left corner, or the center of the lower left pixel
+    let (west, south) = match (get("xllcorner"), get("yllcorner")) {
+        (Some(x), Some(y)) => (x, y),
+        _ => match (get("xllcenter"), get("yllcenter")) {
+            (Some(x), Some(y)) => (x - size_x / 2.0, y - size_y / 2.0),
+            _ => return Err("missing xllcorner and yllcorner".to_string()),
+        },
+    };
+
+    let values = tokens
+        .map(|token| token.parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("invalid value: {e}"))?;
+    if values.len() != ncols * nrows {
+        return Err(format!(
+            "expected {} values, found {}",
+            ncols * nrows,
+            values.len()
+        ));
+    }
+
+    Ok(AsciiGrid {
+        ncols,
+        nrows,
+        transform: GeoTransform::north_up(west, south + nrows as f64 * size_y, size_x, size_y),
+        nodata: get("nodata_value"),
+        values,
+    })
+}
+
+/// Reads the coordinate system from WKT, as in the .prj file next to an ASCII grid
+fn crs_from_wkt(wkt: &str) -> Result<Crs, String> {
+    // The authority of the whole coordinate system is the last one
+    for marker in ["AUTHORITY[\"EPSG\",", "ID[\"EPSG\","] {
+        if let Some(start) = wkt.rfind(marker) {
+            let code: String = wkt[start + marker.len()..]
+                .chars()
+                .filter(|c| *c != '"')
+                .take_while(char::is_ascii_digit)
+                .collect();
+            if let Ok(code) = code.parse() {
+                return Crs::from_epsg(code);
+            }
+        }
+    }
+
+    // Names like "WGS 84 / UTM zone 32N" or "WGS_1984_UTM_Zone_32N"
+    let name = wkt.to_uppercase().replace('_', " ");
+    if let Some(start) = name.find("UTM ZONE ") {
+        let zone: String = name[start + 9..]
+            .chars()
+            .take_while(char::is_ascii_alphanumeric)
+            .collect();
+        let north = zone.ends_with('N');
+        if let Ok(zone) = zone.trim_end_matches(['N', 'S']).parse() {
+            return Ok(Crs::Utm { zone, north });
+        }
+    }
+    if wkt.trim_start().starts_with("GEOGCS") || wkt.trim_start().starts_with("GEOGCRS") {
+        return Ok(Crs::Geographic);
+    }
+    Err("unsupported coordinate system in .prj file, set it with --dem-crs".to_string())
+}
+
+fn sample_ascii_grid(path: &Path, crs: Option<Crs>, grid: &mut HeightGrid) -> DemResult<()> {
+    let ascii_grid = parse_ascii_grid(&fs::read_to_string(path)?)?;
+
+    let prj_path = path.with_extension("prj");
+    let crs = match crs {
+        Some(crs) => crs,
+        None if prj_path.exists() => crs_from_wkt(&fs::read_to_string(prj_path)?)?,
+        None => {
+            // Without .prj file only plain latitude and longitude can be recognized
+            let (west, north) = ascii_grid.transform.apply(0.0, 0.0);
+            let (east, south) = ascii_grid
+                .transform
+                .apply(ascii_grid.ncols as f64, ascii_grid.nrows as f64);
+            if west < -180.0 || east > 180.0 || south < -90.0 || north > 90.0 {
+                return Err("no .prj file found, set the coordinate system with --dem-crs".into());
+            }
+            Crs::Geographic
+        }
+    };
+
+    let georeference = Georeference {
+        transform: ascii_grid.transform,
+        crs,
+        nodata: ascii_grid.nodata,
+    };
+    let Some((cols, rows)) = georeference.window(ascii_grid.ncols, ascii_grid.nrows, grid.bbox())
+    else {
+        return Ok(());
+    };
+    for row in rows {
+        for col in cols.clone() {
+            let height = ascii_grid.values[row * ascii_grid.ncols + col];
+            georeference.add_pixel(grid, col, row, height);
+        }
+    }
+    Ok(())
+}
+
+/// Zoom levels of a directory of `{z}/{x}/{y}` tiles, highest first
+fn tile_zoom_levels(dir: &Path) -> DemResult<Vec<u8>> {
+    let mut zooms: Vec<u8> = fs::read_dir(dir)?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            entry.file_type().ok()?.is_dir().then_some(())?;
+            entry.file_name().to_str()?.parse().ok()
+        })
+        .collect();
+    zooms.sort_unstable_by(|a, b| b.cmp(a));
+    Ok(zooms)
+}
+
+fn find_tile(dir: &Path, zoom: u8, x: u32, y: u32) -> Option<PathBuf> {
+    ["png", "pngraw", "webp"]
+        .iter()
+        .map(|extension| dir.join(format!("{zoom}/{x}/{y}.{extension}")))
+        .find(|path| path.exists())
+}
+
+/// Decodes a pixel of a Mapbox RGB tile: -10000 + (R * 65536 + G * 256 + B) * 0.1
+fn decode_mapbox(pixel: &Rgb<u8>) -> f64 {
+    -10000.0 + (pixel[0] as f64 * 65536.0 + pixel[1] as f64 * 256.0 + pixel[2] as f64) * 0.1
+}
+
+/// Samples the highest zoom level that has all tiles covering the bbox
+fn sample_tile_directory(dir: &Path, grid: &mut HeightGrid) -> DemResult<()> {
+    let bbox = *grid.bbox();
+    let (zoom, tiles) = tile_zoom_levels(dir)?
+        .into_iter()
+        .find_map(|zoom| {
+            let tiles = get_tile_coordinates(&bbox, zoom)
+                .into_iter()
+                .map(|(x, y)| Some(((x, y), find_tile(dir, zoom, x, y)?)))
+                .collect::<Option<Vec<_>>>()?;
+            Some((zoom, tiles))
+        })
+        .ok_or(format!(
+            "No zoom level in {} has all tiles covering the bounding box",
+            dir.display()
+        ))?;
+
+    for (tile, path) in tiles {
+        println!("Loading tile {} at zoom {zoom}", path.display());
+        let rgb_img = image::open(&path)?.to_rgb8();
+        let pixels = (rgb_img.width() * rgb_img.height()).max(1) as f64;
+        let mean_red = rgb_img.pixels().map(|pixel| pixel[0] as f64).sum::<f64>() / pixels;
+        let decode = if mean_red >= TERRARIUM_MIN_MEAN_RED {
+            decode_terrarium
+        } else {
+            decode_mapbox
+        };
+        sample_rgb_tile(grid, &rgb_img, tile, zoom, decode);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::Args;
+    use clap::Parser;
+    use tiff::encoder::{colortype, TiffEncoder};
+
+    fn grid(bbox: &str, dir: &Path) -> HeightGrid {
+        let args = Args::try_parse_from(["arnis", "--bbox", bbox, "--path", dir.to_str().unwrap()])
+            .unwrap();
+        HeightGrid::new(&args).unwrap()
+    }
+
+    #[test]
+    fn test_geotiff_utm() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dem.tif");
+
+        // 200 m x 200 m around 48.1°N 11.5°E in UTM zone 32N, heights rising to the east
+        let crs = Crs::Utm {
+            zone: 32,
+            north: true,
+        };
+        let (x, y) = crs.to_xy(LLPoint::new(48.1, 11.5).unwrap());
+        let (west, north) = (x.floor() - 100.0, y.floor() + 100.0);
+        let heights: Vec<f32> = (0..200 * 200).map(|i| 500.0 + (i % 200) as f32).collect();
+
+        let mut encoder = TiffEncoder::new(File::create(&path).unwrap()).unwrap();
+        let mut image = encoder
+            .new_image::<colortype::Gray32Float>(200, 200)
+            .unwrap();
+        let tags = image.encoder();
+        tags.write_tag(Tag::ModelPixelScaleTag, &[1.0, 1.0, 0.0][..])
+            .unwrap();
+        tags.write_tag(
+            Tag::ModelTiepointTag,
+            &[0.0, 0.0, 0.0, west, north, 0.0][..],
+        )
+        .unwrap();
+        tags.write_tag(
+            Tag::GeoKeyDirectoryTag,
+            &[1u16, 1, 0, 2, 1024, 0, 1, 1, 3072, 0, 1, 32632][..],
+        )
+        .unwrap();
+        image.write_data(&heights).unwrap();
+
+        let mut grid = grid("48.0995,11.4995,48.1005,11.5005", dir.path());
+        sample_local_dem(&path, None, &mut grid).unwrap();
+
+        let row = &grid.heights[grid.height / 2];
+        let (first, last) = (row[1], row[grid.width - 2]);
+        assert!((500.0..700.0).contains(&first), "{first}");
+        // The bbox is about 74 m wide
+        assert!((last - first - 72.0).abs() < 4.0, "{first} {last}");
+        // Pixels and blocks have about the same size, so only a few blocks get no sample
+        let missing = grid.heights.iter().flatten().filter(|h| h.is_nan()).count();
+        assert!(missing < grid.width * grid.height / 5, "{missing}");
+    }
+
+    #[test]
+    fn test_ascii_grid_and_hgt() {
+        let ascii_grid = parse_ascii_grid(
+            "ncols 3\nnrows 2\nxllcenter 10.5\nyllcenter 20.5\ncellsize 1\n\
+             NODATA_value -9999\n1 2 3\n4 5 -9999\n",
+        )
+        .unwrap();
+        assert_eq!(ascii_grid.transform.apply(0.0, 0.0), (10.0, 22.0));
+        assert_eq!(ascii_grid.values[5], -9999.0);
+        assert_eq!(ascii_grid.nodata, Some(-9999.0));
+        assert!(parse_ascii_grid("ncols 2\nnrows 2\ncellsize 1\n1 2 3 4").is_err());
+
+        assert_eq!(hgt_corner(Path::new("N48E011.hgt")), Some((48.0, 11.0)));
+        assert_eq!(hgt_corner(Path::new("s34w071.HGT")), Some((-34.0, -71.0)));
+        assert_eq!(hgt_corner(Path::new("dem.hgt")), None);
+
+        assert_eq!(
+            crs_from_wkt(
+                r#"PROJCS["ETRS89 / UTM zone 32N",GEOGCS["ETRS89",AUTHORITY["EPSG","4258"]],AUTHORITY["EPSG","25832"]]"#
+            ),
+            Ok(Crs::Utm {
+                zone: 32,
+                north: true
+            })
+        );
+        assert_eq!(
+            crs_from_wkt(r#"PROJCS["WGS_1984_UTM_Zone_33S",GEOGCS["GCS_WGS_1984"]]"#),
+            Ok(Crs::Utm {
+                zone: 33,
+                north: false
+            })
+        );
+        assert!(crs_from_wkt(r#"PROJCS["Lambert_Conformal_Conic"]"#).is_err());
+    }
+}
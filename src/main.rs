@@ -1,28 +1,44 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod args;
+mod bedrock;
+mod biomes;
 mod block_definitions;
 mod bresenham;
+mod checkpoint;
 mod colors;
 mod coordinate_system;
 mod data_processing;
 mod element_processing;
+mod entities;
 mod floodfill;
 mod ground;
+mod lighting;
+mod local_dem;
 mod map_transformation;
+mod mc_version;
+mod osm_file;
 mod osm_parser;
 #[cfg(feature = "gui")]
 mod progress;
+mod render;
+mod report;
 mod retrieve_data;
+mod schematic;
+mod style;
 #[cfg(test)]
 mod test_utilities;
+mod tiling;
 mod version_check;
 mod world_editor;
 
 use args::Args;
+use checkpoint::Checkpoint;
 use clap::Parser;
 use colored::*;
+use coordinate_system::cartesian::XZBBox;
 use std::{env, fs, io::Write};
+use world_editor::WorldFormat;
 
 mod elevation_data;
 #[cfg(feature = "gui")]
@@ -74,23 +90,168 @@ fn run_cli() {
     // Parse input arguments
     let args: Args = Args::parse();
 
-    // Fetch data
-    let raw_data = match &args.file {
-        Some(file) => retrieve_data::fetch_data_from_file(file),
-        None => retrieve_data::fetch_data_from_overpass(
-            args.bbox,
-            args.debug,
-            args.downloader.as_str(),
-            args.save_json_file.as_deref(),
-        ),
+    if args.export.is_some() && args.tile_size.is_some() {
+        eprintln!(
+            "{}: --export needs the whole area in memory and cannot be combined with --tile-size",
+            "Error".red().bold()
+        );
+        std::process::exit(1);
+    }
+
+    if (args.render.is_some() || args.render_tiles.is_some()) && args.tile_size.is_some() {
+        eprintln!(
+            "{}: --render and --render-tiles need the whole area in memory and cannot be combined with --tile-size",
+            "Error".red().bold()
+        );
+        std::process::exit(1);
     }
-    .expect("Failed to fetch data");
 
-    let mut ground = ground::generate_ground_data(&args);
+    match args.format {
+        _ if args.export_only => {}
+        WorldFormat::Java => {
+            let region = args.path.join("region");
+            if !region.is_dir() {
+                eprintln!(
+                    "{}: No Minecraft world found at {region:?}",
+                    "Error".red().bold()
+                );
+                std::process::exit(1);
+            }
+        }
+        WorldFormat::Bedrock => {
+            if args.update || args.resume {
+                eprintln!(
+                    "{}: --update and --resume are not supported for Bedrock worlds",
+                    "Error".red().bold()
+                );
+                std::process::exit(1);
+            }
+            let db = args.path.join("db");
+            if db.exists() {
+                eprintln!(
+                    "{}: {db:?} already contains a world, choose an empty folder for Bedrock worlds",
+                    "Error".red().bold()
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if args.update && args.mc_version.is_legacy() {
+        eprintln!(
+            "{}: --update requires --mc-version 1.18 or newer",
+            "Error".red().bold()
+        );
+        std::process::exit(1);
+    }
+    if args.format == WorldFormat::Java && !args.export_only {
+        mc_version::check_world_version(&args.path, args.mc_version);
+    }
+
+    if let Some(blocks_path) = &args.blocks {
+        if let Err(e) = block_definitions::load_blocks(blocks_path) {
+            eprintln!("{}: {}", "Error".red().bold(), e);
+            std::process::exit(1);
+        }
+    }
+
+    if let Err(e) = style::init(args.style.as_deref()) {
+        eprintln!("{}: {}", "Error".red().bold(), e);
+        std::process::exit(1);
+    }
+
+    let checkpoint = if args.checkpoint || args.resume {
+        match Checkpoint::open(&args) {
+            Ok(checkpoint) => Some(checkpoint),
+            Err(e) => {
+                eprintln!("Warning: Failed to open checkpoint: {e}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let (parsed_elements, xzbbox, ground) = match checkpoint
+        .as_ref()
+        .and_then(|checkpoint| checkpoint.load_prepared_data())
+    {
+        Some(prepared) => {
+            println!("{}", "Resuming from checkpoint...".bold());
+            (prepared.elements, prepared.xzbbox, prepared.ground)
+        }
+        None => prepare_data(&args, checkpoint.as_ref()),
+    };
+
+    // Generate world
+    let result = data_processing::generate_world(
+        parsed_elements,
+        xzbbox,
+        args.bbox,
+        ground,
+        &args,
+        checkpoint.as_ref(),
+    );
+
+    if let Err(e) = result {
+        eprintln!("{}: {}", "Error".red().bold(), e);
+        std::process::exit(1);
+    }
+
+    if let Some(checkpoint) = checkpoint {
+        if let Err(e) = checkpoint.remove() {
+            eprintln!("Warning: Failed to remove checkpoint: {e}");
+        }
+    }
+}
+
+/// Fetches, parses and transforms the OSM data and generates the ground, saving each stage
+/// to the checkpoint if there is one
+fn prepare_data(
+    args: &Args,
+    checkpoint: Option<&Checkpoint>,
+) -> (Vec<osm_parser::ProcessedElement>, XZBBox, ground::Ground) {
+    // Fetch data
+    let raw_data = match checkpoint.and_then(|checkpoint| checkpoint.load_osm_data()) {
+        Some(raw_data) => raw_data,
+        None => {
+            let raw_data = match &args.file {
+                Some(file) => retrieve_data::fetch_data_from_file(file, &args.bbox),
+                None => retrieve_data::OverpassSettings::from_args(args).and_then(|settings| {
+                    retrieve_data::fetch_data_from_overpass(
+                        args.bbox,
+                        args.debug,
+                        args.downloader.as_str(),
+                        args.save_json_file.as_deref(),
+                        &settings,
+                    )
+                }),
+            }
+            .expect("Failed to fetch data");
+
+            if let Some(checkpoint) = checkpoint {
+                if let Err(e) = checkpoint.save_osm_data(&raw_data) {
+                    eprintln!("Warning: Failed to save checkpoint: {e}");
+                }
+            }
+            raw_data
+        }
+    };
+
+    let mut ground = ground::generate_ground_data(args);
+
+    let area = retrieve_data::fetch_area_polygon(args).expect("Failed to load the generation area");
 
     // Parse raw data
-    let (mut parsed_elements, mut xzbbox) =
-        osm_parser::parse_osm_data(raw_data, args.bbox, args.scale, args.debug);
+    let (mut parsed_elements, mut xzbbox) = osm_parser::parse_osm_data(
+        raw_data,
+        args.bbox,
+        area.as_ref(),
+        args.scale,
+        args.projection,
+        args.origin,
+        args.debug,
+    );
     parsed_elements
         .sort_by_key(|element: &osm_parser::ProcessedElement| osm_parser::get_priority(element));
 
@@ -114,8 +275,13 @@ fn run_cli() {
     // Transform map (parsed_elements). Operations are defined in a json file
     map_transformation::transform_map(&mut parsed_elements, &mut xzbbox, &mut ground);
 
-    // Generate world
-    let _ = data_processing::generate_world(parsed_elements, xzbbox, args.bbox, ground, &args);
+    if let Some(checkpoint) = checkpoint {
+        if let Err(e) = checkpoint.save_prepared_data(&parsed_elements, &xzbbox, &ground) {
+            eprintln!("Warning: Failed to save checkpoint: {e}");
+        }
+    }
+
+    (parsed_elements, xzbbox, ground)
 }
 
 fn main() {
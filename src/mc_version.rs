@@ -0,0 +1,208 @@
+//! Minecraft Java Edition versions that generated worlds can be written for.
+//!
+//! The chunk layout differs between versions: before 1.18 chunk data is nested in a `Level`
+//! compound and the world spans Y 0 to 255, newer versions store it at the root and reach
+//! down to Y -64. Blocks that do not exist in the target version are replaced when the
+//! chunks are written.
+
+use clap::ValueEnum;
+use fastnbt::Value;
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::Path;
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize, Deserialize,
+)]
+pub enum McVersion {
+    #[value(name = "1.16")]
+    V1_16,
+    #[value(name = "1.17")]
+    V1_17,
+    #[value(name = "1.18")]
+    V1_18,
+    #[value(name = "1.19")]
+    V1_19,
+    #[value(name = "1.20")]
+    V1_20,
+    #[value(name = "1.20.5")]
+    V1_20_5,
+    #[default]
+    #[value(name = "latest", alias = "1.21")]
+    Latest,
+}
+
+/// Blocks that were added or renamed in a version, with the block used for older versions
+const SUBSTITUTIONS: &[(&str, McVersion, &str)] = &[
+    ("copper_ore", McVersion::V1_17, "minecraft:iron_ore"),
+    (
+        "deepslate_bricks",
+        McVersion::V1_17,
+        "minecraft:stone_bricks",
+    ),
+    ("dirt_path", McVersion::V1_17, "minecraft:grass_path"),
+    ("moss_block", McVersion::V1_17, "minecraft:green_wool"),
+    ("oxidized_copper", McVersion::V1_17, "minecraft:prismarine"),
+    (
+        "polished_deepslate",
+        McVersion::V1_17,
+        "minecraft:polished_andesite",
+    ),
+    (
+        "tinted_glass",
+        McVersion::V1_17,
+        "minecraft:gray_stained_glass",
+    ),
+    ("mud", McVersion::V1_19, "minecraft:dirt"),
+    ("mud_bricks", McVersion::V1_19, "minecraft:terracotta"),
+    (
+        "mud_brick_stairs",
+        McVersion::V1_19,
+        "minecraft:brick_stairs",
+    ),
+    // Renamed in 1.20.3
+    ("short_grass", McVersion::V1_20, "minecraft:grass"),
+];
+
+impl McVersion {
+    /// The data version written to chunks, identifies the exact game release
+    pub fn data_version(self) -> i32 {
+        match self {
+            McVersion::V1_16 => 2586,
+            McVersion::V1_17 => 2730,
+            McVersion::V1_18 => 2975,
+            McVersion::V1_19 => 3337,
+            McVersion::V1_20 => 3700,
+            McVersion::V1_20_5 => 3839,
+            McVersion::Latest => 4189,
+        }
+    }
+
+    /// The game release the chunks are written for
+    pub fn release_name(self) -> &'static str {
+        match self {
+            McVersion::V1_16 => "1.16.5",
+            McVersion::V1_17 => "1.17.1",
+            McVersion::V1_18 => "1.18.2",
+            McVersion::V1_19 => "1.19.4",
+            McVersion::V1_20 => "1.20.4",
+            McVersion::V1_20_5 => "1.20.6",
+            McVersion::Latest => "1.21.4",
+        }
+    }
+
+    /// Versions before 1.18 nest chunk data in a `Level` compound and have no negative Y
+    pub fn is_legacy(self) -> bool {
+        self < McVersion::V1_18
+    }
+
+    /// Offset added to all Y coordinates, legacy worlds are shifted up to keep the
+    /// generated terrain above their lowest block at Y 0
+    pub fn y_offset(self) -> i32 {
+        if self.is_legacy() {
+            64
+        } else {
+            0
+        }
+    }
+
+    /// Range of section Y indices that exist in this version
+    pub fn section_range(self) -> std::ops::RangeInclusive<i8> {
+        if self.is_legacy() {
+            0..=15
+        } else {
+            -4..=19
+        }
+    }
+
+    /// Entities are stored in their own region files since 1.17
+    pub fn has_entity_regions(self) -> bool {
+        self >= McVersion::V1_17
+    }
+
+    /// Sign texts are stored per side since 1.20
+    pub fn has_sign_sides(self) -> bool {
+        self >= McVersion::V1_20
+    }
+
+    /// Returns the block to write instead of the given namespaced block, if it is not
+    /// available in this version
+    pub fn substitute_block(self, name: &str) -> Option<&'static str> {
+        let name = name.strip_prefix("minecraft:").unwrap_or(name);
+        SUBSTITUTIONS
+            .iter()
+            .find(|(block, added_in, _)| *block == name && self < *added_in)
+            .map(|(_, _, replacement)| *replacement)
+    }
+}
+
+/// Warns if the world was created with an older game version than the chunks are written
+/// for, as the game refuses to load chunks from the future
+pub fn check_world_version(world_dir: &Path, version: McVersion) {
+    let Some(world_data_version) = read_world_data_version(world_dir) else {
+        return;
+    };
+
+    if world_data_version < version.data_version() {
+        eprintln!(
+            "Warning: The world was created with an older Minecraft version than {}. \
+             Open it with Minecraft {} or newer, or choose an older version with --mc-version.",
+            version.release_name(),
+            version.release_name()
+        );
+    }
+}
+
+fn read_world_data_version(world_dir: &Path) -> Option<i32> {
+    let compressed = std::fs::read(world_dir.join("level.dat")).ok()?;
+    let mut data = Vec::new();
+    GzDecoder::new(compressed.as_slice())
+        .read_to_end(&mut data)
+        .ok()?;
+
+    let Value::Compound(root) = fastnbt::from_bytes::<Value>(&data).ok()? else {
+        return None;
+    };
+    let Some(Value::Compound(level)) = root.get("Data") else {
+        return None;
+    };
+    match level.get("DataVersion") {
+        Some(Value::Int(data_version)) => Some(*data_version),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substitute_block() {
+        assert_eq!(
+            McVersion::V1_16.substitute_block("minecraft:dirt_path"),
+            Some("minecraft:grass_path")
+        );
+        assert_eq!(
+            McVersion::V1_17.substitute_block("minecraft:dirt_path"),
+            None
+        );
+        assert_eq!(
+            McVersion::V1_19.substitute_block("minecraft:short_grass"),
+            Some("minecraft:grass")
+        );
+        assert_eq!(
+            McVersion::Latest.substitute_block("minecraft:short_grass"),
+            None
+        );
+        assert_eq!(McVersion::V1_16.substitute_block("minecraft:stone"), None);
+    }
+
+    #[test]
+    fn test_version_order() {
+        assert!(McVersion::V1_17.is_legacy());
+        assert!(!McVersion::V1_18.is_legacy());
+        assert!(McVersion::V1_20_5 < McVersion::Latest);
+        assert!(McVersion::V1_20_5.data_version() < McVersion::Latest.data_version());
+    }
+}
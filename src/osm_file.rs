@@ -0,0 +1,526 @@
+use crate::coordinate_system::geographic::{LLBBox, LLPoint};
+use osmpbf::{Element, ElementReader, RelMemberType};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Supported formats for local OSM data files
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OsmFileFormat {
+    /// Overpass API style JSON (`{"elements": [...]}`)
+    Json,
+    /// OSM XML (`.osm`)
+    Xml,
+    /// OSM Protocolbuffer Binary Format (`.osm.pbf`)
+    Pbf,
+}
+
+impl OsmFileFormat {
+    /// Detects the file format from the file extension, defaulting to JSON
+    pub fn from_path(path: &str) -> Self {
+        let lower = path.to_lowercase();
+        if lower.ends_with(".pbf") {
+            Self::Pbf
+        } else if lower.ends_with(".osm") || lower.ends_with(".xml") {
+            Self::Xml
+        } else {
+            Self::Json
+        }
+    }
+}
+
+struct RawNode {
+    id: u64,
+    lat: f64,
+    lon: f64,
+    tags: Vec<(String, String)>,
+}
+
+struct RawWay {
+    id: u64,
+    nodes: Vec<u64>,
+    tags: Vec<(String, String)>,
+}
+
+struct RawMember {
+    r#type: String,
+    r#ref: u64,
+    role: String,
+}
+
+struct RawRelation {
+    id: u64,
+    members: Vec<RawMember>,
+    tags: Vec<(String, String)>,
+}
+
+#[derive(Default)]
+struct RawOsmData {
+    nodes: Vec<RawNode>,
+    ways: Vec<RawWay>,
+    relations: Vec<RawRelation>,
+}
+
+fn in_bbox(bbox: &LLBBox, lat: f64, lon: f64) -> bool {
+    LLPoint::new(lat, lon).is_ok_and(|point| bbox.contains(&point))
+}
+
+/// Reads an OSM XML file and crops it to the bounding box.
+/// The result has the same shape as an Overpass API JSON response.
+pub fn read_osm_xml(path: &Path, bbox: &LLBBox) -> Result<Value, Box<dyn std::error::Error>> {
+    let file = File::open(path)?;
+    let mut reader = Reader::from_reader(BufReader::new(file));
+    reader.config_mut().trim_text(true);
+
+    let mut data = RawOsmData::default();
+    let mut buf: Vec<u8> = Vec::new();
+
+    let mut current = Current::None;
+
+    loop {
+        let event = reader.read_event_into(&mut buf)?;
+        let is_empty = matches!(event, Event::Empty(_));
+
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let attrs = xml_attributes(e)?;
+                let attr_u64 = |key: &str| -> Option<u64> {
+                    attrs
+                        .get(key)
+                        .and_then(|v| v.parse::<i64>().ok())
+                        .map(|v| v as u64)
+                };
+                let attr_f64 =
+                    |key: &str| -> Option<f64> { attrs.get(key).and_then(|v| v.parse().ok()) };
+
+                match e.name().as_ref() {
+                    b"node" => {
+                        if let (Some(id), Some(lat), Some(lon)) =
+                            (attr_u64("id"), attr_f64("lat"), attr_f64("lon"))
+                        {
+                            current = Current::Node(RawNode {
+                                id,
+                                lat,
+                                lon,
+                                tags: Vec::new(),
+                            });
+                        }
+                    }
+                    b"way" => {
+                        if let Some(id) = attr_u64("id") {
+                            current = Current::Way(RawWay {
+                                id,
+                                nodes: Vec::new(),
+                                tags: Vec::new(),
+                            });
+                        }
+                    }
+                    b"relation" => {
+                        if let Some(id) = attr_u64("id") {
+                            current = Current::Relation(RawRelation {
+                                id,
+                                members: Vec::new(),
+                                tags: Vec::new(),
+                            });
+                        }
+                    }
+                    b"tag" => {
+                        if let (Some(k), Some(v)) = (attrs.get("k"), attrs.get("v")) {
+                            let tag = (k.clone(), v.clone());
+                            match &mut current {
+                                Current::Node(n) => n.tags.push(tag),
+                                Current::Way(w) => w.tags.push(tag),
+                                Current::Relation(r) => r.tags.push(tag),
+                                Current::None => {}
+                            }
+                        }
+                    }
+                    b"nd" => {
+                        if let (Current::Way(w), Some(node_ref)) = (&mut current, attr_u64("ref")) {
+                            w.nodes.push(node_ref);
+                        }
+                    }
+                    b"member" => {
+                        if let (Current::Relation(r), Some(member_ref)) =
+                            (&mut current, attr_u64("ref"))
+                        {
+                            r.members.push(RawMember {
+                                r#type: attrs.get("type").cloned().unwrap_or_default(),
+                                r#ref: member_ref,
+                                role: attrs.get("role").cloned().unwrap_or_default(),
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+
+                // Self-closing elements (e.g. untagged nodes) have no matching end event
+                if is_empty && matches!(e.name().as_ref(), b"node" | b"way" | b"relation") {
+                    finish_xml_element(&mut data, std::mem::replace(&mut current, Current::None));
+                }
+            }
+            Event::End(ref e) => {
+                if matches!(e.name().as_ref(), b"node" | b"way" | b"relation") {
+                    finish_xml_element(&mut data, std::mem::replace(&mut current, Current::None));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(crop_to_bbox(data, bbox))
+}
+
+/// The XML element whose children (tags, node refs, members) are currently being read
+enum Current {
+    None,
+    Node(RawNode),
+    Way(RawWay),
+    Relation(RawRelation),
+}
+
+fn finish_xml_element(data: &mut RawOsmData, element: Current) {
+    match element {
+        Current::Node(n) => data.nodes.push(n),
+        Current::Way(w) => data.ways.push(w),
+        Current::Relation(r) => data.relations.push(r),
+        Current::None => {}
+    }
+}
+
+fn xml_attributes(
+    element: &BytesStart,
+) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+    let mut attrs = HashMap::new();
+    for attr in element.attributes() {
+        let attr = attr?;
+        let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+        let value = attr.unescape_value()?.to_string();
+        attrs.insert(key, value);
+    }
+    Ok(attrs)
+}
+
+/// Reads an OSM PBF file and crops it to the bounding box.
+/// The file is streamed in up to three passes so that country-sized extracts never have to be
+/// held in memory: the first pass collects everything touching the bbox, the second pass the
+/// other member ways of the relations found, and the last pass the outside nodes that are
+/// needed to keep clipped ways intact.
+pub fn read_osm_pbf(path: &Path, bbox: &LLBBox) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut data = RawOsmData::default();
+    let mut nodes_in_bbox: HashSet<u64> = HashSet::new();
+    let mut kept_ways: HashSet<u64> = HashSet::new();
+
+    // First pass: nodes inside the bbox, ways referencing them and relations referencing those ways
+    ElementReader::from_path(path)?.for_each(|element| match element {
+        Element::Node(node) => {
+            if in_bbox(bbox, node.lat(), node.lon()) {
+                nodes_in_bbox.insert(node.id() as u64);
+                data.nodes.push(RawNode {
+                    id: node.id() as u64,
+                    lat: node.lat(),
+                    lon: node.lon(),
+                    tags: pbf_tags(node.tags()),
+                });
+            }
+        }
+        Element::DenseNode(node) => {
+            if in_bbox(bbox, node.lat(), node.lon()) {
+                nodes_in_bbox.insert(node.id() as u64);
+                data.nodes.push(RawNode {
+                    id: node.id() as u64,
+                    lat: node.lat(),
+                    lon: node.lon(),
+                    tags: pbf_tags(node.tags()),
+                });
+            }
+        }
+        Element::Way(way) => {
+            let refs: Vec<u64> = way.refs().map(|r| r as u64).collect();
+            if refs.iter().any(|r| nodes_in_bbox.contains(r)) {
+                kept_ways.insert(way.id() as u64);
+                data.ways.push(RawWay {
+                    id: way.id() as u64,
+                    nodes: refs,
+                    tags: pbf_tags(way.tags()),
+                });
+            }
+        }
+        Element::Relation(rel) => {
+            let members: Vec<RawMember> = rel
+                .members()
+                .map(|m| RawMember {
+                    r#type: match m.member_type {
+                        RelMemberType::Node => "node",
+                        RelMemberType::Way => "way",
+                        RelMemberType::Relation => "relation",
+                    }
+                    .to_string(),
+                    r#ref: m.member_id as u64,
+                    role: m.role().unwrap_or_default().to_string(),
+                })
+                .collect();
+            if members
+                .iter()
+                .any(|m| m.r#type == "way" && kept_ways.contains(&m.r#ref))
+            {
+                data.relations.push(RawRelation {
+                    id: rel.id() as u64,
+                    members,
+                    tags: pbf_tags(rel.tags()),
+                });
+            }
+        }
+    })?;
+
+    // Second pass: member ways of kept relations that lie outside the bbox. Relations come
+    // after the ways in a PBF file, so these ways weren't known to be needed in the first pass.
+    let missing_ways: HashSet<u64> = data
+        .relations
+        .iter()
+        .flat_map(|r| r.members.iter())
+        .filter(|m| m.r#type == "way" && !kept_ways.contains(&m.r#ref))
+        .map(|m| m.r#ref)
+        .collect();
+
+    if !missing_ways.is_empty() {
+        ElementReader::from_path(path)?.for_each(|element| {
+            if let Element::Way(way) = element {
+                if missing_ways.contains(&(way.id() as u64)) {
+                    data.ways.push(RawWay {
+                        id: way.id() as u64,
+                        nodes: way.refs().map(|r| r as u64).collect(),
+                        tags: pbf_tags(way.tags()),
+                    });
+                }
+            }
+        })?;
+    }
+
+    // Third pass: way nodes that lie outside the bbox
+    let missing_nodes: HashSet<u64> = data
+        .ways
+        .iter()
+        .flat_map(|w| w.nodes.iter().copied())
+        .filter(|id| !nodes_in_bbox.contains(id))
+        .collect();
+
+    if !missing_nodes.is_empty() {
+        ElementReader::from_path(path)?.for_each(|element| {
+            let (id, lat, lon) = match &element {
+                Element::Node(node) => (node.id() as u64, node.lat(), node.lon()),
+                Element::DenseNode(node) => (node.id() as u64, node.lat(), node.lon()),
+                _ => return,
+            };
+            if missing_nodes.contains(&id) {
+                data.nodes.push(RawNode {
+                    id,
+                    lat,
+                    lon,
+                    tags: Vec::new(),
+                });
+            }
+        })?;
+    }
+
+    Ok(crop_to_bbox(data, bbox))
+}
+
+fn pbf_tags<'a>(tags: impl Iterator<Item = (&'a str, &'a str)>) -> Vec<(String, String)> {
+    tags.map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+/// Drops everything that does not touch the bounding box and converts the remaining
+/// elements into the Overpass JSON layout expected by `osm_parser::parse_osm_data`.
+///
+/// Ways are kept in full if at least one of their nodes lies inside the bbox (clipping happens
+/// later in the parser), relations are kept if at least one of their member ways is kept. All
+/// member ways of kept relations are kept as well, so their rings stay closed.
+fn crop_to_bbox(data: RawOsmData, bbox: &LLBBox) -> Value {
+    let nodes_in_bbox: HashSet<u64> = data
+        .nodes
+        .iter()
+        .filter(|n| in_bbox(bbox, n.lat, n.lon))
+        .map(|n| n.id)
+        .collect();
+
+    let ways_in_bbox: HashSet<u64> = data
+        .ways
+        .iter()
+        .filter(|w| w.nodes.iter().any(|id| nodes_in_bbox.contains(id)))
+        .map(|w| w.id)
+        .collect();
+
+    let relations: Vec<RawRelation> = data
+        .relations
+        .into_iter()
+        .filter(|r| {
+            r.members
+                .iter()
+                .any(|m| m.r#type == "way" && ways_in_bbox.contains(&m.r#ref))
+        })
+        .collect();
+    let member_ways: HashSet<u64> = relations
+        .iter()
+        .flat_map(|r| r.members.iter())
+        .filter(|m| m.r#type == "way")
+        .map(|m| m.r#ref)
+        .collect();
+
+    let ways: Vec<RawWay> = data
+        .ways
+        .into_iter()
+        .filter(|w| ways_in_bbox.contains(&w.id) || member_ways.contains(&w.id))
+        .collect();
+
+    let way_nodes: HashSet<u64> = ways.iter().flat_map(|w| w.nodes.iter().copied()).collect();
+
+    let mut elements: Vec<Value> = Vec::new();
+
+    for node in data.nodes {
+        if nodes_in_bbox.contains(&node.id) || way_nodes.contains(&node.id) {
+            let mut element = json!({
+                "type": "node",
+                "id": node.id,
+                "lat": node.lat,
+                "lon": node.lon,
+            });
+            if !node.tags.is_empty() {
+                element["tags"] = tags_to_json(node.tags);
+            }
+            elements.push(element);
+        }
+    }
+
+    for way in ways {
+        elements.push(json!({
+            "type": "way",
+            "id": way.id,
+            "nodes": way.nodes,
+            "tags": tags_to_json(way.tags),
+        }));
+    }
+
+    for rel in relations {
+        let members: Vec<Value> = rel
+            .members
+            .into_iter()
+            .map(|m| json!({"type": m.r#type, "ref": m.r#ref, "role": m.role}))
+            .collect();
+        elements.push(json!({
+            "type": "relation",
+            "id": rel.id,
+            "members": members,
+            "tags": tags_to_json(rel.tags),
+        }));
+    }
+
+    json!({ "elements": elements })
+}
+
+fn tags_to_json(tags: Vec<(String, String)>) -> Value {
+    Value::Object(
+        tags.into_iter()
+            .map(|(k, v)| (k, Value::String(v)))
+            .collect::<Map<String, Value>>(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_format_detection() {
+        assert_eq!(OsmFileFormat::from_path("data.json"), OsmFileFormat::Json);
+        assert_eq!(OsmFileFormat::from_path("map.osm"), OsmFileFormat::Xml);
+        assert_eq!(
+            OsmFileFormat::from_path("schleswig-holstein-latest.osm.pbf"),
+            OsmFileFormat::Pbf
+        );
+        assert_eq!(
+            OsmFileFormat::from_path("EXTRACT.OSM.PBF"),
+            OsmFileFormat::Pbf
+        );
+    }
+
+    #[test]
+    fn test_xml_crop_to_bbox() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<osm version="0.6">
+  <node id="1" lat="54.630" lon="9.930"/>
+  <node id="2" lat="54.631" lon="9.931"><tag k="natural" v="tree"/></node>
+  <node id="3" lat="55.000" lon="9.931"/>
+  <node id="4" lat="56.000" lon="10.500"/>
+  <node id="5" lat="56.001" lon="10.501"/>
+  <node id="6" lat="55.500" lon="9.500"/>
+  <node id="7" lat="55.501" lon="9.501"/>
+  <way id="10">
+    <nd ref="1"/><nd ref="3"/>
+    <tag k="highway" v="residential"/>
+  </way>
+  <way id="11">
+    <nd ref="4"/><nd ref="5"/>
+    <tag k="highway" v="primary"/>
+  </way>
+  <way id="12">
+    <nd ref="6"/><nd ref="7"/><nd ref="6"/>
+    <tag k="building" v="yes"/>
+  </way>
+  <relation id="20">
+    <member type="way" ref="10" role="outer"/>
+    <member type="way" ref="12" role="inner"/>
+    <tag k="type" v="multipolygon"/>
+  </relation>
+  <relation id="21">
+    <member type="way" ref="11" role="outer"/>
+    <tag k="type" v="multipolygon"/>
+  </relation>
+</osm>"#;
+        let mut file = tempfile::Builder::new().suffix(".osm").tempfile().unwrap();
+        file.write_all(xml.as_bytes()).unwrap();
+
+        let bbox = LLBBox::new(54.627, 9.927, 54.635, 9.938).unwrap();
+        assert_cropped(&read_osm_xml(file.path(), &bbox).unwrap());
+    }
+
+    #[test]
+    fn test_pbf_crop_to_bbox() {
+        // The same data as in test_xml_crop_to_bbox, with dense nodes
+        let bbox = LLBBox::new(54.627, 9.927, 54.635, 9.938).unwrap();
+        let data = read_osm_pbf(Path::new("tests/osm_file/crop.osm.pbf"), &bbox).unwrap();
+        assert_cropped(&data);
+    }
+
+    fn assert_cropped(data: &Value) {
+        let elements = data["elements"].as_array().unwrap();
+
+        let ids = |kind: &str| -> Vec<u64> {
+            let mut ids: Vec<u64> = elements
+                .iter()
+                .filter(|e| e["type"] == kind)
+                .map(|e| e["id"].as_u64().unwrap())
+                .collect();
+            ids.sort_unstable();
+            ids
+        };
+
+        // Node 3 lies outside the bbox but is kept because way 10 references it. Way 12 and
+        // its nodes lie outside as well, but are a member of relation 20.
+        assert_eq!(ids("node"), vec![1, 2, 3, 6, 7]);
+        assert_eq!(ids("way"), vec![10, 12]);
+        assert_eq!(ids("relation"), vec![20]);
+
+        let tree = elements.iter().find(|e| e["id"] == 2).unwrap();
+        assert_eq!(tree["tags"]["natural"], "tree");
+        let node = elements.iter().find(|e| e["id"] == 6).unwrap();
+        assert!((node["lat"].as_f64().unwrap() - 55.5).abs() < 1e-6);
+    }
+}
@@ -1,9 +1,13 @@
+use crate::bresenham::bresenham_line;
 use crate::coordinate_system::cartesian::{XZBBox, XZPoint};
-use crate::coordinate_system::geographic::{LLBBox, LLPoint};
+use crate::coordinate_system::geographic::{LLBBox, LLPoint, LLPolygon};
+use crate::coordinate_system::projection::Projection;
 use crate::coordinate_system::transformation::CoordTransformer;
+use crate::element_processing::coastline;
 use crate::progress::emit_gui_progress_update;
 use colored::Colorize;
-use serde::Deserialize;
+use geo::{Contains, LineString, Point, Polygon};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
@@ -76,7 +80,7 @@ fn parse_raw_osm_data(json_data: Value) -> Result<SplitOsmData, serde_json::Erro
 
 // Normalized data that we can use
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProcessedNode {
     pub id: u64,
     pub tags: HashMap<String, String>,
@@ -95,33 +99,33 @@ impl ProcessedNode {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProcessedWay {
     pub id: u64,
     pub nodes: Vec<ProcessedNode>,
     pub tags: HashMap<String, String>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum ProcessedMemberRole {
     Outer,
     Inner,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProcessedMember {
     pub role: ProcessedMemberRole,
     pub way: ProcessedWay,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProcessedRelation {
     pub id: u64,
     pub tags: HashMap<String, String>,
     pub members: Vec<ProcessedMember>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ProcessedElement {
     Node(ProcessedNode),
     Way(ProcessedWay),
@@ -145,7 +149,7 @@ impl ProcessedElement {
         }
     }
 
-    pub fn kind(&self) -> &str {
+    pub fn kind(&self) -> &'static str {
         match self {
             ProcessedElement::Node(_) => "node",
             ProcessedElement::Way(_) => "way",
@@ -162,10 +166,15 @@ impl ProcessedElement {
     }
 }
 
+/// Converts the OSM data to elements in Minecraft coordinates, clipped to the bounding box.
+/// With an area outline generation is limited to the blocks inside it.
 pub fn parse_osm_data(
     json_data: Value,
     bbox: LLBBox,
+    area: Option<&LLPolygon>,
     scale: f64,
+    projection: Projection,
+    origin: Option<LLPoint>,
     debug: bool,
 ) -> (Vec<ProcessedElement>, XZBBox) {
     println!("{} Parsing data...", "[2/7]".bold());
@@ -175,11 +184,31 @@ pub fn parse_osm_data(
     // Deserialize the JSON data into the OSMData structure
     let data = parse_raw_osm_data(json_data).expect("Failed to parse OSM data");
 
-    let (coord_transformer, xzbbox) = CoordTransformer::llbbox_to_xzbbox(&bbox, scale)
-        .unwrap_or_else(|e| {
-            eprintln!("Error in defining coordinate transformation:\n{e}");
-            panic!();
-        });
+    let (coord_transformer, xzbbox) =
+        CoordTransformer::llbbox_to_xzbbox_projected(&bbox, scale, projection, origin)
+            .unwrap_or_else(|e| {
+                eprintln!("Error in defining coordinate transformation:\n{e}");
+                panic!();
+            });
+
+    let xzbbox = match area {
+        Some(area) => {
+            let rings = area
+                .rings()
+                .iter()
+                .map(|ring| {
+                    ring.iter()
+                        .map(|&llpoint| coord_transformer.transform_point(llpoint))
+                        .collect()
+                })
+                .collect();
+            XZBBox::polygon_from_rings(rings, &xzbbox).unwrap_or_else(|e| {
+                eprintln!("Error in defining the generation area:\n{e}");
+                panic!();
+            })
+        }
+        None => xzbbox,
+    };
 
     if debug {
         println!("Total elements: {}", data.total_count());
@@ -318,9 +347,50 @@ pub fn get_priority(element: &ProcessedElement) -> usize {
     PRIORITY_ORDER.len()
 }
 
+/// Clips a way to the bounding box. Polygon shaped bboxes clip ways to their circumscribed
+/// rectangle and drop the ways that do not reach into the polygon, blocks outside of it are
+/// skipped when they are placed.
+fn clip_way_to_bbox(
+    nodes: &[ProcessedNode],
+    xzbbox: &XZBBox,
+    tags: &HashMap<String, String>,
+) -> Vec<ProcessedNode> {
+    let clipped = clip_way_to_rect(nodes, xzbbox, tags);
+    if matches!(xzbbox, XZBBox::Polygon(_)) && !way_reaches_into(&clipped, xzbbox) {
+        return Vec::new();
+    }
+    clipped
+}
+
+/// Checks whether a way passes a block covered by the bbox or, if it is closed, encloses one
+fn way_reaches_into(nodes: &[ProcessedNode], xzbbox: &XZBBox) -> bool {
+    let (Some(first), Some(last)) = (nodes.first(), nodes.last()) else {
+        return false;
+    };
+
+    let passes = nodes.iter().zip(nodes.iter().skip(1)).any(|(a, b)| {
+        bresenham_line(a.x, 0, a.z, b.x, 0, b.z)
+            .into_iter()
+            .any(|(x, _, z)| xzbbox.contains(&XZPoint::new(x, z)))
+    });
+    if passes || xzbbox.contains(&XZPoint::new(first.x, first.z)) {
+        return true;
+    }
+
+    let is_closed = nodes.len() > 2 && (first.x, first.z) == (last.x, last.z);
+    let outline: LineString<f64> = nodes
+        .iter()
+        .map(|node| (f64::from(node.x), f64::from(node.z)))
+        .collect();
+    let block = xzbbox.first_block();
+    is_closed
+        && Polygon::new(outline, vec![])
+            .contains(&Point::new(f64::from(block.x), f64::from(block.z)))
+}
+
 /// Clips a way to the bounding box boundaries using Sutherland-Hodgman algorithm for polygons
 /// or simple line clipping for polylines
-fn clip_way_to_bbox(
+fn clip_way_to_rect(
     nodes: &[ProcessedNode],
     xzbbox: &XZBBox,
     tags: &HashMap<String, String>,
@@ -329,6 +399,11 @@ fn clip_way_to_bbox(
         return Vec::new();
     }
 
+    // Coastlines are joined by their node ids and clipped as a whole, see coastline
+    if coastline::is_coastline(tags) {
+        return nodes.to_vec();
+    }
+
     // For certain tags, use simple line clipping instead of polygon clipping
     if ["waterway", "highway", "barrier", "railway", "service"]
         .iter()
@@ -0,0 +1,371 @@
+//! Top-down renders of generated areas, to review them without launching the game.
+//!
+//! Every block column is drawn in the color of its topmost block that is solid or contains
+//! fluid, shaded like in-game maps: brighter where the column is higher than its northern
+//! neighbor and darker where it is lower. Water gets darker with depth. Renders are saved as
+//! a single PNG or as a pyramid of map tiles (`{zoom}/{x}/{y}.png`) with an HTML viewer.
+
+use crate::block_definitions::Block;
+use image::imageops::{self, FilterType};
+use image::{Rgba, RgbaImage};
+use std::fs;
+use std::path::Path;
+
+/// Width and height of map tiles in pixels
+const TILE_SIZE: u32 = 256;
+
+/// Blocks without an entry are colored by `keyword_color`
+const BLOCK_COLORS: &[(&str, [u8; 3])] = &[
+    ("grass_block", [109, 153, 48]),
+    ("moss_block", [89, 109, 45]),
+    ("dirt", [134, 96, 67]),
+    ("coarse_dirt", [119, 85, 59]),
+    ("rooted_dirt", [144, 103, 76]),
+    ("podzol", [91, 63, 24]),
+    ("farmland", [94, 62, 37]),
+    ("dirt_path", [148, 122, 65]),
+    ("mud", [60, 57, 61]),
+    ("sand", [219, 207, 163]),
+    ("red_sand", [190, 102, 33]),
+    ("gravel", [136, 126, 126]),
+    ("clay", [160, 166, 179]),
+    ("stone", [125, 125, 125]),
+    ("smooth_stone", [158, 158, 158]),
+    ("cobblestone", [122, 122, 122]),
+    ("mossy_cobblestone", [110, 118, 94]),
+    ("granite", [149, 103, 85]),
+    ("polished_granite", [154, 106, 89]),
+    ("diorite", [188, 188, 188]),
+    ("polished_diorite", [192, 193, 194]),
+    ("terracotta", [152, 94, 67]),
+    ("water", [63, 118, 228]),
+    ("lava", [207, 92, 20]),
+    ("ice", [145, 183, 253]),
+    ("packed_ice", [141, 180, 250]),
+    ("blue_ice", [116, 167, 253]),
+    ("snow_block", [249, 254, 254]),
+    ("hay_block", [166, 136, 38]),
+    ("melon", [111, 145, 30]),
+    ("pumpkin", [198, 118, 24]),
+    ("glowstone", [171, 131, 84]),
+    ("iron_block", [220, 220, 220]),
+    ("gold_block", [246, 208, 61]),
+    ("bedrock", [85, 85, 85]),
+    ("obsidian", [15, 10, 24]),
+];
+
+/// Colors of blocks named after a dye color, such as concrete, wool and terracotta
+const DYE_COLORS: &[(&str, [u8; 3])] = &[
+    ("white", [233, 236, 236]),
+    ("orange", [240, 118, 19]),
+    ("magenta", [189, 68, 179]),
+    ("light_blue", [58, 175, 217]),
+    ("yellow", [248, 197, 39]),
+    ("lime", [112, 185, 25]),
+    ("pink", [237, 141, 172]),
+    ("gray", [62, 68, 71]),
+    ("light_gray", [142, 142, 134]),
+    ("cyan", [21, 137, 145]),
+    ("purple", [121, 42, 172]),
+    ("blue", [53, 57, 157]),
+    ("brown", [114, 71, 40]),
+    ("green", [84, 109, 27]),
+    ("red", [160, 39, 34]),
+    ("black", [20, 21, 25]),
+];
+
+/// Colors of blocks named after a wood type, such as planks, logs and stairs
+const WOOD_COLORS: &[(&str, [u8; 3])] = &[
+    ("oak", [162, 130, 78]),
+    ("spruce", [114, 84, 48]),
+    ("birch", [196, 179, 123]),
+    ("jungle", [160, 115, 80]),
+    ("acacia", [168, 90, 50]),
+    ("dark_oak", [66, 43, 20]),
+    ("mangrove", [117, 54, 48]),
+    ("cherry", [226, 178, 172]),
+    ("bamboo", [193, 173, 80]),
+    ("crimson", [101, 48, 70]),
+    ("warped", [43, 104, 99]),
+];
+
+const LEAVES_COLOR: [u8; 3] = [60, 110, 40];
+const UNKNOWN_COLOR: [u8; 3] = [128, 128, 128];
+
+/// The visible block of a column
+#[derive(Clone, Copy, Debug)]
+pub struct SurfaceBlock {
+    pub block: Block,
+    pub y: i32,
+    /// Number of water blocks from the surface down, 0 for other blocks
+    pub water_depth: i32,
+}
+
+/// The map color of a block, by its name with or without namespace
+pub fn block_color(name: &str) -> [u8; 3] {
+    let name = name.rsplit(':').next().unwrap_or(name);
+    BLOCK_COLORS
+        .iter()
+        .find(|(block, _)| *block == name)
+        .map(|(_, color)| *color)
+        .unwrap_or_else(|| keyword_color(name))
+}
+
+fn keyword_color(name: &str) -> [u8; 3] {
+    let has_prefix = |prefix: &str| {
+        name.strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('_'))
+    };
+
+    if name.ends_with("_leaves") {
+        return LEAVES_COLOR;
+    }
+    // Materials whose name starts with a dye color, but have a color of their own
+    let dyed_materials: &[(&str, [u8; 3])] = &[
+        ("red_sandstone", [181, 97, 31]),
+        ("red_nether_brick", [69, 7, 9]),
+        ("sandstone", [216, 203, 155]),
+        ("nether_brick", [44, 21, 26]),
+    ];
+    if let Some((_, color)) = dyed_materials
+        .iter()
+        .find(|(material, _)| name.contains(material))
+    {
+        return *color;
+    }
+    if let Some((_, color)) = DYE_COLORS.iter().find(|(dye, _)| has_prefix(dye)) {
+        // Terracotta is a muted version of the dye color
+        return if name.contains("terracotta") {
+            mix(*color, block_color("terracotta"))
+        } else {
+            *color
+        };
+    }
+    if let Some((_, color)) = WOOD_COLORS.iter().find(|(wood, _)| has_prefix(wood)) {
+        return *color;
+    }
+
+    let keywords: &[(&str, [u8; 3])] = &[
+        ("stone_brick", [122, 121, 122]),
+        ("brick", [150, 97, 83]),
+        ("quartz", [235, 229, 222]),
+        ("copper", [192, 107, 79]),
+        ("deepslate", [80, 80, 82]),
+        ("blackstone", [42, 36, 41]),
+        ("glass", [175, 213, 219]),
+        ("ice", [145, 183, 253]),
+        ("snow", [249, 254, 254]),
+        ("andesite", [136, 136, 137]),
+        ("stone", [125, 125, 125]),
+    ];
+    keywords
+        .iter()
+        .find(|(keyword, _)| name.contains(keyword))
+        .map_or(UNKNOWN_COLOR, |(_, color)| *color)
+}
+
+fn mix(a: [u8; 3], b: [u8; 3]) -> [u8; 3] {
+    std::array::from_fn(|i| ((u16::from(a[i]) + u16::from(b[i])) / 2) as u8)
+}
+
+fn shade(color: [u8; 3], factor: f32) -> Rgba<u8> {
+    let [r, g, b] = color.map(|c| (f32::from(c) * factor).round().min(255.0) as u8);
+    Rgba([r, g, b, 255])
+}
+
+/// Draws the columns of an area, given row by row from north to south. Columns without
+/// blocks stay transparent.
+pub fn render_surface(width: u32, height: u32, columns: &[Option<SurfaceBlock>]) -> RgbaImage {
+    let mut colors: Vec<Option<[u8; 3]>> = Vec::new();
+    let mut color_of = |block: Block| {
+        let id = usize::from(block.id());
+        if id >= colors.len() {
+            colors.resize(id + 1, None);
+        }
+        *colors[id].get_or_insert_with(|| block_color(block.name()))
+    };
+
+    RgbaImage::from_fn(width, height, |x, z| {
+        let index = (z * width + x) as usize;
+        let Some(surface) = columns[index] else {
+            return Rgba([0, 0, 0, 0]);
+        };
+        let color = color_of(surface.block);
+
+        if surface.water_depth > 0 {
+            let depth = surface.water_depth.min(9) - 1;
+            return shade(color, 1.0 - 0.05 * depth as f32);
+        }
+
+        let north = z
+            .checked_sub(1)
+            .and_then(|north| columns[(north * width + x) as usize]);
+        let factor = match north {
+            Some(north) if surface.y > north.y => 1.0,
+            Some(north) if surface.y < north.y => 0.71,
+            _ => 0.86,
+        };
+        shade(color, factor)
+    })
+}
+
+/// Saves a render as tiles of 256 pixels in `{zoom}/{x}/{y}.png` under the directory, along
+/// with an `index.html` that shows them in Leaflet. One pixel is one block at the highest
+/// zoom level, each level below halves the resolution until the render fits one tile.
+/// Tiles without any blocks are skipped. `origin` are the coordinates of the top left block.
+pub fn save_tiles(image: &RgbaImage, dir: &Path, origin: (i32, i32)) -> Result<(), String> {
+    let max_zoom = max_zoom(image.width().max(image.height()));
+
+    let mut level = image.clone();
+    for zoom in (0..=max_zoom).rev() {
+        for tile_x in 0..level.width().div_ceil(TILE_SIZE) {
+            let column_dir = dir.join(zoom.to_string()).join(tile_x.to_string());
+            for tile_y in 0..level.height().div_ceil(TILE_SIZE) {
+                let area = imageops::crop_imm(
+                    &level,
+                    tile_x * TILE_SIZE,
+                    tile_y * TILE_SIZE,
+                    TILE_SIZE,
+                    TILE_SIZE,
+                )
+                .to_image();
+                if area.pixels().all(|pixel| pixel[3] == 0) {
+                    continue;
+                }
+
+                let mut tile = RgbaImage::new(TILE_SIZE, TILE_SIZE);
+                imageops::replace(&mut tile, &area, 0, 0);
+
+                fs::create_dir_all(&column_dir)
+                    .map_err(|e| format!("Failed to create {}: {e}", column_dir.display()))?;
+                let path = column_dir.join(format!("{tile_y}.png"));
+                tile.save(&path)
+                    .map_err(|e| format!("Failed to save tile {}: {e}", path.display()))?;
+            }
+        }
+
+        if zoom > 0 {
+            level = downsample(&level);
+        }
+    }
+
+    let html = VIEWER_HTML
+        .replace("%WIDTH%", &image.width().to_string())
+        .replace("%HEIGHT%", &image.height().to_string())
+        .replace("%MAX_ZOOM%", &max_zoom.to_string())
+        .replace("%ORIGIN_X%", &origin.0.to_string())
+        .replace("%ORIGIN_Z%", &origin.1.to_string());
+    let path = dir.join("index.html");
+    fs::write(&path, html).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Zoom level at which the given number of pixels fits one tile after halving each level
+fn max_zoom(size: u32) -> u32 {
+    let mut zoom = 0;
+    while TILE_SIZE << zoom < size {
+        zoom += 1;
+    }
+    zoom
+}
+
+/// Halves the resolution, odd sizes are padded with a transparent pixel first so pixels of
+/// the levels stay aligned
+fn downsample(image: &RgbaImage) -> RgbaImage {
+    let mut padded = RgbaImage::new(
+        image.width().next_multiple_of(2),
+        image.height().next_multiple_of(2),
+    );
+    imageops::replace(&mut padded, image, 0, 0);
+    imageops::resize(
+        &padded,
+        padded.width() / 2,
+        padded.height() / 2,
+        FilterType::Triangle,
+    )
+}
+
+const VIEWER_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>Arnis map</title>
+  <link rel="stylesheet" href="https://unpkg.com/leaflet@1.9.4/dist/leaflet.css">
+  <script src="https://unpkg.com/leaflet@1.9.4/dist/leaflet.js"></script>
+  <style>html, body, #map { height: 100%; margin: 0; background: #1e1e1e; }</style>
+</head>
+<body>
+  <div id="map"></div>
+  <script>
+    const width = %WIDTH%, height = %HEIGHT%, maxZoom = %MAX_ZOOM%;
+    const originX = %ORIGIN_X%, originZ = %ORIGIN_Z%;
+    const map = L.map("map", { crs: L.CRS.Simple, minZoom: 0, maxZoom: maxZoom + 3 });
+    const bounds = L.latLngBounds(
+      map.unproject([0, height], maxZoom),
+      map.unproject([width, 0], maxZoom)
+    );
+    L.tileLayer("{z}/{x}/{y}.png", {
+      bounds: bounds,
+      maxNativeZoom: maxZoom,
+      maxZoom: maxZoom + 3,
+      noWrap: true,
+    }).addTo(map);
+    map.fitBounds(bounds);
+
+    // Block coordinates of a clicked point
+    map.on("click", (event) => {
+      const point = map.project(event.latlng, maxZoom);
+      const x = originX + Math.floor(point.x), z = originZ + Math.floor(point.y);
+      L.popup().setLatLng(event.latlng).setContent(`X ${x}, Z ${z}`).openOn(map);
+    });
+  </script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_color() {
+        assert_eq!(block_color("minecraft:grass_block"), [109, 153, 48]);
+        assert_eq!(block_color("light_gray_concrete"), [142, 142, 134]);
+        assert_eq!(block_color("gray_wool"), [62, 68, 71]);
+        assert_eq!(block_color("dark_oak_planks"), [66, 43, 20]);
+        assert_eq!(block_color("oak_leaves"), LEAVES_COLOR);
+        assert_eq!(block_color("stone_brick_stairs"), [122, 121, 122]);
+        assert_eq!(block_color("brick_slab"), [150, 97, 83]);
+        assert_eq!(block_color("red_sandstone_wall"), [181, 97, 31]);
+        assert_eq!(block_color("cut_sandstone"), [216, 203, 155]);
+        assert_eq!(block_color("red_nether_bricks"), [69, 7, 9]);
+        assert_eq!(block_color("nether_brick_fence"), [44, 21, 26]);
+        assert_eq!(
+            block_color("red_terracotta"),
+            mix([160, 39, 34], block_color("terracotta"))
+        );
+        assert_eq!(block_color("unknown_block"), UNKNOWN_COLOR);
+    }
+
+    #[test]
+    fn test_save_tiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut image = RgbaImage::new(300, 100);
+        image.put_pixel(299, 99, Rgba([255, 0, 0, 255]));
+        image.put_pixel(0, 0, Rgba([0, 255, 0, 255]));
+
+        save_tiles(&image, dir.path(), (-10, 20)).unwrap();
+
+        assert!(dir.path().join("1/0/0.png").exists());
+        assert!(dir.path().join("1/1/0.png").exists());
+        assert!(dir.path().join("0/0/0.png").exists());
+        let tile = image::open(dir.path().join("1/1/0.png"))
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(tile.dimensions(), (TILE_SIZE, TILE_SIZE));
+        assert_eq!(tile.get_pixel(43, 99), &Rgba([255, 0, 0, 255]));
+
+        let html = fs::read_to_string(dir.path().join("index.html")).unwrap();
+        assert!(html.contains("const width = 300, height = 100, maxZoom = 1;"));
+        assert!(html.contains("const originX = -10, originZ = 20;"));
+    }
+}
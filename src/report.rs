@@ -0,0 +1,166 @@
+//! Machine-readable report of a generation run, written as `report.json` next to the
+//! `metadata.json` of the world.
+//!
+//! Elements are counted once by the category of the processor that generated them, even when
+//! tiled generation processes them in several tiles. Timings add up the time spent in each
+//! processor, summed over all threads with `--parallel`.
+
+use crate::osm_parser::ProcessedElement;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+/// Type and id of an OSM element
+type ElementRef = (&'static str, u64);
+
+#[derive(Default)]
+pub struct Report {
+    /// Elements by the category of their processor
+    elements: BTreeMap<&'static str, BTreeSet<ElementRef>>,
+    /// Elements that matched no processor, with their tag keys
+    unmatched: BTreeMap<ElementRef, Vec<String>>,
+    /// Elements with a flood fill that stopped at the timeout
+    floodfill_timeouts: BTreeSet<ElementRef>,
+    timings: BTreeMap<&'static str, Duration>,
+    /// Placed blocks by namespaced name
+    blocks: BTreeMap<String, u64>,
+    regions: BTreeSet<(i32, i32)>,
+}
+
+impl Report {
+    /// Records the processing of an element, `category` is `None` if no processor matched it
+    pub fn record_element(
+        &mut self,
+        element: &ProcessedElement,
+        category: Option<&'static str>,
+        duration: Duration,
+        floodfill_timeout: bool,
+    ) {
+        let element_ref = (element.kind(), element.id());
+        match category {
+            Some(category) => {
+                self.elements
+                    .entry(category)
+                    .or_default()
+                    .insert(element_ref);
+                *self.timings.entry(category).or_default() += duration;
+            }
+            None => {
+                let mut keys: Vec<String> = element.tags().keys().cloned().collect();
+                keys.sort();
+                self.unmatched.insert(element_ref, keys);
+            }
+        }
+        if floodfill_timeout {
+            self.floodfill_timeouts.insert(element_ref);
+        }
+    }
+
+    pub fn add_blocks(&mut self, name: String, count: u64) {
+        *self.blocks.entry(name).or_default() += count;
+    }
+
+    pub fn add_region(&mut self, region_x: i32, region_z: i32) {
+        self.regions.insert((region_x, region_z));
+    }
+
+    /// Adds everything recorded by another report, e.g. of a tile generated concurrently
+    pub fn merge(&mut self, other: Report) {
+        for (category, elements) in other.elements {
+            self.elements.entry(category).or_default().extend(elements);
+        }
+        self.unmatched.extend(other.unmatched);
+        self.floodfill_timeouts.extend(other.floodfill_timeouts);
+        for (category, duration) in other.timings {
+            *self.timings.entry(category).or_default() += duration;
+        }
+        for (name, count) in other.blocks {
+            self.add_blocks(name, count);
+        }
+        self.regions.extend(other.regions);
+    }
+
+    pub fn to_json(&self) -> Value {
+        let element_json = |(kind, id): &ElementRef| json!({ "type": kind, "id": id });
+
+        let mut unmatched_tags: BTreeMap<&str, usize> = BTreeMap::new();
+        for key in self.unmatched.values().flatten() {
+            *unmatched_tags.entry(key).or_default() += 1;
+        }
+
+        json!({
+            "elements": self
+                .elements
+                .iter()
+                .map(|(category, elements)| (*category, elements.len()))
+                .collect::<BTreeMap<_, _>>(),
+            "unmatched_elements": {
+                "count": self.unmatched.len(),
+                "tag_keys": unmatched_tags,
+                "elements": self
+                    .unmatched
+                    .keys()
+                    .map(element_json)
+                    .collect::<Vec<_>>(),
+            },
+            "floodfill_timeouts": self
+                .floodfill_timeouts
+                .iter()
+                .map(element_json)
+                .collect::<Vec<_>>(),
+            "timings_seconds": self
+                .timings
+                .iter()
+                .map(|(category, duration)| (*category, duration.as_secs_f64()))
+                .collect::<BTreeMap<_, _>>(),
+            "blocks": self.blocks,
+            "regions": self
+                .regions
+                .iter()
+                .map(|&(x, z)| [x, z])
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osm_parser::ProcessedNode;
+    use std::collections::HashMap;
+
+    fn node(id: u64, tag: &str) -> ProcessedElement {
+        ProcessedElement::Node(ProcessedNode {
+            id,
+            tags: HashMap::from([(tag.to_string(), "yes".to_string())]),
+            x: 0,
+            z: 0,
+        })
+    }
+
+    #[test]
+    fn test_report_json() {
+        let mut report = Report::default();
+        let second = Duration::from_secs(1);
+        report.record_element(&node(1, "amenity"), Some("amenity"), second, false);
+        report.record_element(&node(2, "shop"), None, second, true);
+
+        // Tiles processing the same element count it once, but add up the time
+        let mut tile = Report::default();
+        tile.record_element(&node(1, "amenity"), Some("amenity"), second, false);
+        tile.add_blocks("minecraft:stone".to_string(), 3);
+        tile.add_region(0, -1);
+        report.merge(tile);
+        report.add_blocks("minecraft:stone".to_string(), 2);
+
+        let json = report.to_json();
+        assert_eq!(json["elements"]["amenity"], 1);
+        assert_eq!(json["timings_seconds"]["amenity"], 2.0);
+        assert_eq!(json["unmatched_elements"]["count"], 1);
+        assert_eq!(json["unmatched_elements"]["tag_keys"]["shop"], 1);
+        assert_eq!(json["floodfill_timeouts"][0]["id"], 2);
+        assert_eq!(json["floodfill_timeouts"][0]["type"], "node");
+        assert_eq!(json["blocks"]["minecraft:stone"], 5);
+        assert_eq!(json["regions"], json!([[0, -1]]));
+    }
+}
@@ -1,12 +1,15 @@
-use crate::coordinate_system::geographic::LLBBox;
+use crate::args::Args;
+use crate::coordinate_system::geographic::{LLBBox, LLPolygon};
+use crate::osm_file::{self, OsmFileFormat};
 use crate::progress::{emit_gui_error, emit_gui_progress_update, is_running_with_gui};
 use colored::Colorize;
-use rand::seq::SliceRandom;
+use rand::Rng;
 use reqwest::blocking::Client;
 use reqwest::blocking::ClientBuilder;
 use serde_json::Value;
 use std::fs::File;
 use std::io::{self, BufReader, Write};
+use std::path::Path;
 use std::process::Command;
 use std::time::Duration;
 
@@ -79,41 +82,43 @@ fn download_with_wget(url: &str, query: &str) -> io::Result<String> {
     }
 }
 
-pub fn fetch_data_from_file(file: &str) -> Result<Value, Box<dyn std::error::Error>> {
+/// Loads OSM data from a local file.
+/// Overpass JSON is returned as-is, OSM XML and PBF files are cropped to the bounding box
+/// and converted into the same JSON layout.
+pub fn fetch_data_from_file(
+    file: &str,
+    bbox: &LLBBox,
+) -> Result<Value, Box<dyn std::error::Error>> {
     println!("{} Loading data from file...", "[1/7]".bold());
     emit_gui_progress_update(1.0, "Loading data from file...");
 
-    let file: File = File::open(file)?;
-    let reader: BufReader<File> = BufReader::new(file);
-    let data: Value = serde_json::from_reader(reader)?;
+    let data: Value = match OsmFileFormat::from_path(file) {
+        OsmFileFormat::Json => {
+            let file: File = File::open(file)?;
+            let reader: BufReader<File> = BufReader::new(file);
+            serde_json::from_reader(reader)?
+        }
+        OsmFileFormat::Xml => osm_file::read_osm_xml(Path::new(file), bbox)?,
+        OsmFileFormat::Pbf => osm_file::read_osm_pbf(Path::new(file), bbox)?,
+    };
     Ok(data)
 }
 
-/// Main function to fetch data
-pub fn fetch_data_from_overpass(
-    bbox: LLBBox,
-    debug: bool,
-    download_method: &str,
-    save_file: Option<&str>,
-) -> Result<Value, Box<dyn std::error::Error>> {
-    println!("{} Fetching data...", "[1/7]".bold());
-    emit_gui_progress_update(1.0, "Fetching data...");
+/// Default Overpass API servers, one of which is picked at random
+const DEFAULT_API_SERVERS: [&str; 3] = [
+    "https://overpass-api.de/api/interpreter",
+    "https://lz4.overpass-api.de/api/interpreter",
+    "https://z.overpass-api.de/api/interpreter",
+    //"https://overpass.kumi.systems/api/interpreter", // This server is not reliable anymore
+    //"https://overpass.private.coffee/api/interpreter", // This server is not reliable anymore
+];
 
-    // List of Overpass API servers
-    let api_servers: Vec<&str> = vec![
-        "https://overpass-api.de/api/interpreter",
-        "https://lz4.overpass-api.de/api/interpreter",
-        "https://z.overpass-api.de/api/interpreter",
-        //"https://overpass.kumi.systems/api/interpreter", // This server is not reliable anymore
-        //"https://overpass.private.coffee/api/interpreter", // This server is not reliable anymore
-    ];
-    let fallback_api_servers: Vec<&str> =
-        vec!["https://maps.mail.ru/osm/tools/overpass/api/interpreter"];
-    let mut url: &&str = api_servers.choose(&mut rand::thread_rng()).unwrap();
+/// Servers used when a request to one of the default servers failed
+const DEFAULT_FALLBACK_API_SERVERS: [&str; 1] =
+    ["https://maps.mail.ru/osm/tools/overpass/api/interpreter"];
 
-    // Generate Overpass API query for bounding box
-    let query: String = format!(
-        r#"[out:json][timeout:360][bbox:{},{},{},{}];
+/// Default Overpass query. `{bbox}` is replaced by `min_lat,min_lng,max_lat,max_lng`
+pub const DEFAULT_OVERPASS_QUERY: &str = r#"[out:json][timeout:360][bbox:{bbox}];
     (
         nwr["building"];
         nwr["highway"];
@@ -140,41 +145,178 @@ pub fn fetch_data_from_overpass(
     )->.nodesinbbox;
     .relsinbbox out body;
     .waysinbbox out body;
-    .nodesinbbox out skel qt;"#,
-        bbox.min().lat(),
-        bbox.min().lng(),
-        bbox.max().lat(),
-        bbox.max().lng(),
-    );
+    .nodesinbbox out skel qt;"#;
 
-    {
-        // Fetch data from Overpass API
-        let mut attempt = 0;
-        let max_attempts = 1;
-        let response: String = loop {
-            println!("Downloading from {url} with method {download_method}...");
-            let result = match download_method {
-                "requests" => download_with_reqwest(url, &query),
-                "curl" => download_with_curl(url, &query).map_err(|e| e.into()),
-                "wget" => download_with_wget(url, &query).map_err(|e| e.into()),
-                _ => download_with_reqwest(url, &query), // Default to requests
-            };
-
-            match result {
-                Ok(response) => break response,
-                Err(error) => {
-                    if attempt >= max_attempts {
-                        return Err(error);
-                    }
+/// Endpoints, retry behaviour and query used to talk to the Overpass API
+pub struct OverpassSettings {
+    /// Servers tried first
+    pub servers: Vec<String>,
+    /// Whether the first server is picked at random to spread the load, otherwise the
+    /// servers are tried in the given order
+    pub shuffle_servers: bool,
+    /// Servers tried after the first request failed
+    pub fallback_servers: Vec<String>,
+    /// Number of retries after the first failed request
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for every further retry
+    pub backoff: Duration,
+    /// Query template with a `{bbox}` placeholder
+    pub query_template: String,
+}
+
+impl Default for OverpassSettings {
+    fn default() -> Self {
+        Self {
+            servers: DEFAULT_API_SERVERS.iter().map(|s| s.to_string()).collect(),
+            shuffle_servers: true,
+            fallback_servers: DEFAULT_FALLBACK_API_SERVERS
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            max_retries: 1,
+            backoff: Duration::ZERO,
+            query_template: DEFAULT_OVERPASS_QUERY.to_string(),
+        }
+    }
+}
+
+impl OverpassSettings {
+    /// Builds the settings from the command line arguments, falling back to the defaults
+    pub fn from_args(args: &Args) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut settings = Self::default();
+
+        if !args.overpass_url.is_empty() {
+            settings.servers = args.overpass_url.clone();
+            settings.shuffle_servers = false;
+            settings.fallback_servers = Vec::new();
+        }
+        settings.max_retries = args.overpass_retries;
+        settings.backoff = Duration::from_secs(args.overpass_backoff);
 
-                    println!("Request failed. Switching to fallback url...");
-                    url = fallback_api_servers
-                        .choose(&mut rand::thread_rng())
-                        .unwrap();
-                    attempt += 1;
+        if let Some(query_file) = &args.overpass_query {
+            settings.query_template = std::fs::read_to_string(query_file).map_err(|e| {
+                format!(
+                    "Failed to read Overpass query from {}: {e}",
+                    query_file.display()
+                )
+            })?;
+        }
+
+        Ok(settings)
+    }
+
+    /// Fills the bounding box into the query template
+    pub fn build_query(&self, bbox: &LLBBox) -> String {
+        let bbox_str = format!(
+            "{},{},{},{}",
+            bbox.min().lat(),
+            bbox.min().lng(),
+            bbox.max().lat(),
+            bbox.max().lng(),
+        );
+        self.query_template.replace("{bbox}", &bbox_str)
+    }
+
+    /// Picks the server for the given attempt (0 = first request)
+    fn server_for_attempt(&self, attempt: u32, first_choice: usize) -> &str {
+        if attempt > 0 && !self.fallback_servers.is_empty() {
+            let idx = (attempt as usize - 1) % self.fallback_servers.len();
+            return &self.fallback_servers[idx];
+        }
+        let idx = (first_choice + attempt as usize) % self.servers.len();
+        &self.servers[idx]
+    }
+}
+
+/// Sends a query to the Overpass API, retrying with the configured servers and backoff
+fn download_query(
+    query: &str,
+    download_method: &str,
+    settings: &OverpassSettings,
+) -> Result<String, Box<dyn std::error::Error>> {
+    if settings.servers.is_empty() {
+        return Err("No Overpass API server configured".into());
+    }
+
+    let first_choice: usize = if settings.shuffle_servers {
+        rand::thread_rng().gen_range(0..settings.servers.len())
+    } else {
+        0
+    };
+
+    let mut attempt: u32 = 0;
+    loop {
+        let url: &str = settings.server_for_attempt(attempt, first_choice);
+        println!("Downloading from {url} with method {download_method}...");
+        let result = match download_method {
+            "requests" => download_with_reqwest(url, query),
+            "curl" => download_with_curl(url, query).map_err(|e| e.into()),
+            "wget" => download_with_wget(url, query).map_err(|e| e.into()),
+            _ => download_with_reqwest(url, query), // Default to requests
+        };
+
+        match result {
+            Ok(response) => return Ok(response),
+            Err(error) => {
+                if attempt >= settings.max_retries {
+                    return Err(error);
+                }
+
+                attempt += 1;
+                let next_url: &str = settings.server_for_attempt(attempt, first_choice);
+                let delay: Duration = settings.backoff * 2u32.saturating_pow(attempt - 1);
+                if !delay.is_zero() {
+                    println!(
+                        "Request failed. Retrying with {next_url} in {}s...",
+                        delay.as_secs()
+                    );
+                    std::thread::sleep(delay);
+                } else {
+                    println!("Request failed. Retrying with {next_url}...");
                 }
             }
-        };
+        }
+    }
+}
+
+/// Loads the outline limiting the generated area, from `--polygon` or `--boundary-relation`
+pub fn fetch_area_polygon(args: &Args) -> Result<Option<LLPolygon>, Box<dyn std::error::Error>> {
+    if let Some(path) = &args.polygon {
+        let file: File = File::open(path)
+            .map_err(|e| format!("Failed to open polygon file {}: {e}", path.display()))?;
+        let geojson: Value = serde_json::from_reader(BufReader::new(file))?;
+        return Ok(Some(LLPolygon::from_geojson(&geojson)?));
+    }
+
+    if let Some(relation_id) = args.boundary_relation {
+        println!("Fetching outline of relation {relation_id}...");
+        let settings = OverpassSettings::from_args(args)?;
+        let query = format!("[out:json][timeout:360];rel({relation_id});out geom;");
+        let response = download_query(&query, args.downloader.as_str(), &settings)?;
+        let data: Value = serde_json::from_str(&response)?;
+        return Ok(Some(LLPolygon::from_relation_geometry(&data, relation_id)?));
+    }
+
+    Ok(None)
+}
+
+/// Main function to fetch data
+pub fn fetch_data_from_overpass(
+    bbox: LLBBox,
+    debug: bool,
+    download_method: &str,
+    save_file: Option<&str>,
+    settings: &OverpassSettings,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    println!("{} Fetching data...", "[1/7]".bold());
+    emit_gui_progress_update(1.0, "Fetching data...");
+
+    // Generate Overpass API query for bounding box
+    let query: String = settings.build_query(&bbox);
+
+    {
+        // Fetch data from Overpass API
+        let response: String = download_query(&query, download_method, settings)?;
 
         if let Some(save_file) = save_file {
             let mut file: File = File::create(save_file)?;
@@ -259,3 +401,116 @@ pub fn fetch_area_name(lat: f64, lon: f64) -> Result<Option<String>, Box<dyn std
 
     Ok(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_query_replaces_bbox() {
+        let bbox = LLBBox::new(54.627053, 9.927928, 54.634902, 9.937563).unwrap();
+
+        let settings = OverpassSettings::default();
+        let query = settings.build_query(&bbox);
+        assert!(query.contains("[bbox:54.627053,9.927928,54.634902,9.937563]"));
+        assert!(!query.contains("{bbox}"));
+
+        let settings = OverpassSettings {
+            query_template: "[out:json];nwr[\"shop\"]({bbox});out;".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.build_query(&bbox),
+            "[out:json];nwr[\"shop\"](54.627053,9.927928,54.634902,9.937563);out;"
+        );
+    }
+
+    #[test]
+    fn test_server_rotation() {
+        let settings = OverpassSettings {
+            servers: vec!["http://a".to_string(), "http://b".to_string()],
+            shuffle_servers: false,
+            fallback_servers: Vec::new(),
+            max_retries: 5,
+            ..Default::default()
+        };
+        assert_eq!(settings.server_for_attempt(0, 0), "http://a");
+        assert_eq!(settings.server_for_attempt(1, 0), "http://b");
+        assert_eq!(settings.server_for_attempt(2, 0), "http://a");
+        assert_eq!(settings.server_for_attempt(5, 0), "http://b");
+        assert_eq!(settings.server_for_attempt(4, 1), "http://b");
+
+        // Retries cycle through the fallback servers, beyond their count as well
+        let settings = OverpassSettings::default();
+        let fallbacks = DEFAULT_FALLBACK_API_SERVERS.len() as u32;
+        assert_eq!(
+            settings.server_for_attempt(1, 2),
+            DEFAULT_FALLBACK_API_SERVERS[0]
+        );
+        assert_eq!(
+            settings.server_for_attempt(fallbacks + 1, 2),
+            DEFAULT_FALLBACK_API_SERVERS[0]
+        );
+    }
+
+    /// Serves the given HTTP status codes to consecutive requests on a local port
+    fn stub_server(statuses: Vec<u16>) -> (String, std::thread::JoinHandle<()>) {
+        use std::io::Read;
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/api/interpreter", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            for status in statuses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let body = format!("served {status}");
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_download_retries_next_server() {
+        // The first server fails twice, the second server answers the retries in between
+        let (failing, failing_handle) = stub_server(vec![500, 503]);
+        let (working, working_handle) = stub_server(vec![502, 200]);
+        let settings = OverpassSettings {
+            servers: vec![failing, working],
+            shuffle_servers: false,
+            fallback_servers: Vec::new(),
+            max_retries: 3,
+            ..Default::default()
+        };
+
+        let response = download_query("[out:json];", "requests", &settings).unwrap();
+        assert_eq!(response, "served 200");
+        failing_handle.join().unwrap();
+        working_handle.join().unwrap();
+
+        // Without retries left the last error is returned
+        let (failing, failing_handle) = stub_server(vec![500]);
+        let settings = OverpassSettings {
+            servers: vec![failing],
+            shuffle_servers: false,
+            fallback_servers: Vec::new(),
+            max_retries: 0,
+            ..Default::default()
+        };
+        let error = download_query("[out:json];", "requests", &settings).unwrap_err();
+        assert!(error.to_string().contains("500"), "{error}");
+        failing_handle.join().unwrap();
+    }
+}
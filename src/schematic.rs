@@ -0,0 +1,451 @@
+//! Export of generated areas as schematics that can be pasted into existing worlds.
+//!
+//! Two formats are supported, chosen by the file extension: Sponge Schematic v3 (`.schem`),
+//! read by WorldEdit and FastAsyncWorldEdit, and Litematica (`.litematic`). Both are gzipped
+//! Java Edition NBT and store the blocks of a box in the same Y-Z-X order.
+
+use crate::mc_version::McVersion;
+use fastnbt::{IntArray, LongArray, Value};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SchematicFormat {
+    Sponge,
+    Litematica,
+}
+
+impl SchematicFormat {
+    /// Determines the format from the file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "schem" => Some(SchematicFormat::Sponge),
+            "litematic" => Some(SchematicFormat::Litematica),
+            _ => None,
+        }
+    }
+}
+
+/// A box of blocks with their block entities
+pub struct Schematic {
+    /// World coordinates of the lowest corner
+    pub origin: (i32, i32, i32),
+    /// Size along X, Y and Z
+    pub size: (usize, usize, usize),
+    /// Namespaced block names and properties, index 0 is air
+    pub palette: Vec<(String, Option<Value>)>,
+    /// Palette index of every block, ordered by Y, then Z, then X
+    pub blocks: Vec<u32>,
+    /// Block entities with world coordinates in `x`, `y` and `z`
+    pub block_entities: Vec<Value>,
+}
+
+impl Schematic {
+    /// Writes the schematic in the format given by the file extension
+    pub fn save(&self, path: &Path, version: McVersion) -> Result<(), String> {
+        let format = SchematicFormat::from_path(path)
+            .ok_or_else(|| format!("Unknown schematic format: {}", path.display()))?;
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("arnis")
+            .to_string();
+
+        let root = match format {
+            SchematicFormat::Sponge => self.to_sponge(version)?,
+            SchematicFormat::Litematica => self.to_litematica(&name, version)?,
+        };
+
+        let file = File::create(path).map_err(|e| format!("Failed to create {path:?}: {e}"))?;
+        let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+        let data = fastnbt::to_bytes(&root).map_err(|e| format!("Failed to encode NBT: {e}"))?;
+        encoder
+            .write_all(&data)
+            .and_then(|_| encoder.finish()?.flush())
+            .map_err(|e| format!("Failed to write {path:?}: {e}"))
+    }
+
+    fn non_air_count(&self) -> usize {
+        self.blocks.iter().filter(|&&index| index != 0).count()
+    }
+
+    /// Block entities with coordinates relative to the origin, without their position keys
+    fn relative_block_entities(
+        &self,
+    ) -> impl Iterator<Item = ([i32; 3], HashMap<String, Value>)> + '_ {
+        self.block_entities.iter().filter_map(|entity| {
+            let Value::Compound(entity) = entity else {
+                return None;
+            };
+            let mut entity = entity.clone();
+            let mut coordinate = |key: &str| match entity.remove(key) {
+                Some(Value::Int(value)) => Some(value),
+                _ => None,
+            };
+            let pos = [
+                coordinate("x")? - self.origin.0,
+                coordinate("y")? - self.origin.1,
+                coordinate("z")? - self.origin.2,
+            ];
+            Some((pos, entity))
+        })
+    }
+
+    /// Sponge Schematic v3, see https://github.com/SpongePowered/Schematic-Specification
+    fn to_sponge(&self, version: McVersion) -> Result<Value, String> {
+        let (width, height, length) = self.size;
+        let dimension = |size: usize| {
+            u16::try_from(size)
+                .map(|size| Value::Short(size as i16))
+                .map_err(|_| format!("Area too large for a Sponge schematic: {size} blocks"))
+        };
+
+        let palette = self
+            .palette
+            .iter()
+            .enumerate()
+            .map(|(index, (name, properties))| {
+                (
+                    block_state_string(name, properties.as_ref()),
+                    Value::Int(index as i32),
+                )
+            })
+            .collect();
+
+        let mut data = Vec::with_capacity(self.blocks.len());
+        for &index in &self.blocks {
+            put_varint(&mut data, index);
+        }
+
+        let block_entities = self
+            .relative_block_entities()
+            .map(|(pos, mut entity)| {
+                let id = entity
+                    .remove("id")
+                    .unwrap_or_else(|| Value::String(String::new()));
+                entity.remove("keepPacked");
+                Value::Compound(HashMap::from([
+                    (
+                        "Pos".to_string(),
+                        Value::IntArray(IntArray::new(pos.to_vec())),
+                    ),
+                    ("Id".to_string(), id),
+                    ("Data".to_string(), Value::Compound(entity)),
+                ]))
+            })
+            .collect();
+
+        let (x, y, z) = self.origin;
+        let metadata = HashMap::from([
+            ("Date".to_string(), Value::Long(now_millis())),
+            (
+                "WorldEdit".to_string(),
+                Value::Compound(HashMap::from([(
+                    "Origin".to_string(),
+                    Value::IntArray(IntArray::new(vec![x, y, z])),
+                )])),
+            ),
+        ]);
+
+        let schematic = HashMap::from([
+            ("Version".to_string(), Value::Int(3)),
+            (
+                "DataVersion".to_string(),
+                Value::Int(version.data_version()),
+            ),
+            ("Metadata".to_string(), Value::Compound(metadata)),
+            ("Width".to_string(), dimension(width)?),
+            ("Height".to_string(), dimension(height)?),
+            ("Length".to_string(), dimension(length)?),
+            (
+                "Offset".to_string(),
+                Value::IntArray(IntArray::new(vec![0, 0, 0])),
+            ),
+            (
+                "Blocks".to_string(),
+                Value::Compound(HashMap::from([
+                    ("Palette".to_string(), Value::Compound(palette)),
+                    (
+                        "Data".to_string(),
+                        Value::ByteArray(fastnbt::ByteArray::new(
+                            data.into_iter().map(|b| b as i8).collect(),
+                        )),
+                    ),
+                    ("BlockEntities".to_string(), Value::List(block_entities)),
+                ])),
+            ),
+        ]);
+
+        Ok(Value::Compound(HashMap::from([(
+            "Schematic".to_string(),
+            Value::Compound(schematic),
+        )])))
+    }
+
+    /// Litematica schematic with a single region
+    fn to_litematica(&self, name: &str, version: McVersion) -> Result<Value, String> {
+        let (size_x, size_y, size_z) = self.size;
+        // The metadata stores the volume as an int
+        let volume = size_x * size_y * size_z;
+        let total_volume = i32::try_from(volume)
+            .map_err(|_| format!("Area too large for a Litematica schematic: {volume} blocks"))?;
+        let vec3 = |x: usize, y: usize, z: usize| {
+            Value::Compound(HashMap::from([
+                ("x".to_string(), Value::Int(x as i32)),
+                ("y".to_string(), Value::Int(y as i32)),
+                ("z".to_string(), Value::Int(z as i32)),
+            ]))
+        };
+
+        let palette = self
+            .palette
+            .iter()
+            .map(|(name, properties)| {
+                let mut item = HashMap::from([("Name".to_string(), Value::String(name.clone()))]);
+                if let Some(properties) = properties {
+                    item.insert("Properties".to_string(), properties.clone());
+                }
+                Value::Compound(item)
+            })
+            .collect();
+
+        let bits = litematica_bits(self.palette.len());
+        let block_states = pack_spanning(&self.blocks, bits);
+
+        let tile_entities = self
+            .relative_block_entities()
+            .map(|([x, y, z], mut entity)| {
+                entity.insert("x".to_string(), Value::Int(x));
+                entity.insert("y".to_string(), Value::Int(y));
+                entity.insert("z".to_string(), Value::Int(z));
+                Value::Compound(entity)
+            })
+            .collect();
+
+        let region = HashMap::from([
+            ("Position".to_string(), vec3(0, 0, 0)),
+            ("Size".to_string(), vec3(size_x, size_y, size_z)),
+            ("BlockStatePalette".to_string(), Value::List(palette)),
+            (
+                "BlockStates".to_string(),
+                Value::LongArray(LongArray::new(block_states)),
+            ),
+            ("TileEntities".to_string(), Value::List(tile_entities)),
+            ("Entities".to_string(), Value::List(Vec::new())),
+            ("PendingBlockTicks".to_string(), Value::List(Vec::new())),
+            ("PendingFluidTicks".to_string(), Value::List(Vec::new())),
+        ]);
+
+        let now = now_millis();
+        let metadata = HashMap::from([
+            ("Name".to_string(), Value::String(name.to_string())),
+            ("Author".to_string(), Value::String("Arnis".to_string())),
+            ("Description".to_string(), Value::String(String::new())),
+            ("RegionCount".to_string(), Value::Int(1)),
+            ("TimeCreated".to_string(), Value::Long(now)),
+            ("TimeModified".to_string(), Value::Long(now)),
+            (
+                "TotalBlocks".to_string(),
+                Value::Int(self.non_air_count() as i32),
+            ),
+            ("TotalVolume".to_string(), Value::Int(total_volume)),
+            ("EnclosingSize".to_string(), vec3(size_x, size_y, size_z)),
+        ]);
+
+        // Version 6 is used by Litematica since Minecraft 1.20.5
+        let litematica_version = if version >= McVersion::V1_20_5 { 6 } else { 5 };
+
+        Ok(Value::Compound(HashMap::from([
+            ("Version".to_string(), Value::Int(litematica_version)),
+            (
+                "MinecraftDataVersion".to_string(),
+                Value::Int(version.data_version()),
+            ),
+            ("Metadata".to_string(), Value::Compound(metadata)),
+            (
+                "Regions".to_string(),
+                Value::Compound(HashMap::from([(name.to_string(), Value::Compound(region))])),
+            ),
+        ])))
+    }
+}
+
+/// Block state in the `name[key=value,...]` notation used by Sponge palettes
+fn block_state_string(name: &str, properties: Option<&Value>) -> String {
+    let Some(Value::Compound(properties)) = properties else {
+        return name.to_string();
+    };
+    let mut pairs: Vec<String> = properties
+        .iter()
+        .filter_map(|(key, value)| match value {
+            Value::String(value) => Some(format!("{key}={value}")),
+            _ => None,
+        })
+        .collect();
+    if pairs.is_empty() {
+        return name.to_string();
+    }
+    pairs.sort();
+    format!("{name}[{}]", pairs.join(","))
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Bits per block of a Litematica region, at least 2
+fn litematica_bits(palette_len: usize) -> usize {
+    let max_index = palette_len.saturating_sub(1).max(1);
+    (usize::BITS - max_index.leading_zeros()).max(2) as usize
+}
+
+/// Packs values tightly, unlike chunk sections values may span two longs
+fn pack_spanning(values: &[u32], bits: usize) -> Vec<i64> {
+    let mut longs = vec![0u64; (values.len() * bits).div_ceil(64)];
+    for (i, &value) in values.iter().enumerate() {
+        let value = u64::from(value);
+        let start = i * bits;
+        let (long, offset) = (start / 64, start % 64);
+        longs[long] |= value << offset;
+        if offset + bits > 64 {
+            longs[long + 1] |= value >> (64 - offset);
+        }
+    }
+    longs.into_iter().map(|long| long as i64).collect()
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    fn test_schematic() -> Schematic {
+        let stairs = Value::Compound(HashMap::from([
+            ("half".to_string(), Value::String("top".to_string())),
+            ("facing".to_string(), Value::String("north".to_string())),
+        ]));
+        let sign = Value::Compound(HashMap::from([
+            (
+                "id".to_string(),
+                Value::String("minecraft:sign".to_string()),
+            ),
+            ("x".to_string(), Value::Int(11)),
+            ("y".to_string(), Value::Int(-60)),
+            ("z".to_string(), Value::Int(21)),
+        ]));
+        Schematic {
+            origin: (10, -61, 20),
+            size: (2, 2, 2),
+            palette: vec![
+                ("minecraft:air".to_string(), None),
+                ("minecraft:stone".to_string(), None),
+                ("minecraft:oak_stairs".to_string(), Some(stairs)),
+            ],
+            blocks: vec![1, 1, 1, 1, 2, 0, 0, 0],
+            block_entities: vec![sign],
+        }
+    }
+
+    fn read(path: &Path) -> HashMap<String, Value> {
+        let mut data = Vec::new();
+        GzDecoder::new(File::open(path).unwrap())
+            .read_to_end(&mut data)
+            .unwrap();
+        match fastnbt::from_bytes(&data).unwrap() {
+            Value::Compound(root) => root,
+            _ => panic!("Root is not a compound"),
+        }
+    }
+
+    fn compound<'a>(value: &'a HashMap<String, Value>, key: &str) -> &'a HashMap<String, Value> {
+        match &value[key] {
+            Value::Compound(compound) => compound,
+            other => panic!("{key} is not a compound: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_sponge() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("area.schem");
+        test_schematic().save(&path, McVersion::Latest).unwrap();
+
+        let root = read(&path);
+        let schematic = compound(&root, "Schematic");
+        assert_eq!(schematic["Version"], Value::Int(3));
+        assert_eq!(schematic["Width"], Value::Short(2));
+
+        let blocks = compound(schematic, "Blocks");
+        let palette = compound(blocks, "Palette");
+        assert_eq!(
+            palette["minecraft:oak_stairs[facing=north,half=top]"],
+            Value::Int(2)
+        );
+        match &blocks["Data"] {
+            Value::ByteArray(data) => assert_eq!(data.iter().as_slice(), &[1, 1, 1, 1, 2, 0, 0, 0]),
+            other => panic!("Unexpected data: {other:?}"),
+        }
+        let Value::List(entities) = &blocks["BlockEntities"] else {
+            panic!("BlockEntities is not a list");
+        };
+        let Value::Compound(sign) = &entities[0] else {
+            panic!("Block entity is not a compound");
+        };
+        assert_eq!(sign["Pos"], Value::IntArray(IntArray::new(vec![1, 1, 1])));
+        assert_eq!(sign["Id"], Value::String("minecraft:sign".to_string()));
+    }
+
+    #[test]
+    fn test_litematica() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("area.litematic");
+        test_schematic().save(&path, McVersion::Latest).unwrap();
+
+        let root = read(&path);
+        assert_eq!(root["Version"], Value::Int(6));
+        let region = compound(compound(&root, "Regions"), "area");
+        let Value::LongArray(states) = &region["BlockStates"] else {
+            panic!("BlockStates is not a long array");
+        };
+        // Eight 2-bit indices in the lowest 16 bits
+        assert_eq!(states.iter().as_slice(), &[0b0000_0010_0101_0101]);
+    }
+
+    #[test]
+    fn test_litematica_too_large() {
+        let schematic = Schematic {
+            size: (65_536, 384, 65_536),
+            blocks: Vec::new(),
+            ..test_schematic()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let error = schematic
+            .save(&dir.path().join("area.litematic"), McVersion::Latest)
+            .unwrap_err();
+        assert!(error.contains("too large"), "{error}");
+    }
+
+    #[test]
+    fn test_pack_spanning() {
+        // The third 30-bit value starts in the first long and ends in the second
+        let longs = pack_spanning(&[1, 2, (1 << 30) - 1], 30);
+        let expected_first: u64 = 1 | 2 << 30 | 0b1111 << 60;
+        assert_eq!(longs, vec![expected_first as i64, (1 << 26) - 1]);
+    }
+}
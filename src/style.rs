@@ -0,0 +1,237 @@
+//! Data-driven mapping of OSM tags to blocks, heights, decoration densities and biomes.
+//!
+//! A style is a JSON object of categories (e.g. `landuse`, `highway_surface`), each holding a
+//! list of rules. The first rule whose tag selectors all match an element is used. A selector
+//! value may list alternatives separated by `|`, or be `*` to match any value of a present
+//! tag. A rule without selectors matches everything and serves as fallback. The `biome`
+//! category assigns biomes to the areas of landuse, natural, leisure and water elements.
+//!
+//! The built-in style (`assets/styles/default.json`) reproduces the classic Arnis look.
+//! A custom style passed with `--style` replaces the categories it defines and keeps the
+//! built-in rules for all others. Blocks that are not built in, including modded ones, can
+//! be referenced once they are registered with `--blocks`.
+
+use crate::biomes::Biome;
+use crate::block_definitions::Block;
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+const DEFAULT_STYLE: &str = include_str!("../assets/styles/default.json");
+
+static STYLE: OnceCell<Style> = OnceCell::new();
+
+#[derive(Deserialize, Debug)]
+pub struct StyleRule {
+    /// Tag selectors that must all match
+    #[serde(default)]
+    tags: HashMap<String, String>,
+    /// Block name, with or without namespace
+    #[serde(default)]
+    block: Option<String>,
+    /// Height of the placed blocks above ground level
+    #[serde(default)]
+    height: Option<i32>,
+    /// Probabilities per ground block of decorations such as `tree`, `flower` or `grass`
+    #[serde(default)]
+    densities: HashMap<String, f64>,
+    /// Biome name, with or without namespace
+    #[serde(default)]
+    biome: Option<String>,
+}
+
+impl StyleRule {
+    fn matches(&self, tags: &HashMap<String, String>) -> bool {
+        self.tags.iter().all(|(key, selector)| {
+            tags.get(key).is_some_and(|value| {
+                selector == "*" || selector.split('|').any(|option| option == value)
+            })
+        })
+    }
+
+    /// The block of this rule. Block names are validated when the style is loaded.
+    pub fn block(&self) -> Option<Block> {
+        self.block.as_deref().and_then(Block::from_name)
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height.unwrap_or(0)
+    }
+
+    pub fn density(&self, decoration: &str) -> f64 {
+        self.densities.get(decoration).copied().unwrap_or(0.0)
+    }
+
+    /// The biome of this rule. Biome names are validated when the style is loaded.
+    pub fn biome(&self) -> Option<Biome> {
+        self.biome.as_deref().and_then(Biome::from_name)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Style {
+    #[serde(flatten)]
+    categories: HashMap<String, Vec<StyleRule>>,
+}
+
+impl Style {
+    fn from_json(json: &str) -> Result<Self, String> {
+        let style: Style =
+            serde_json::from_str(json).map_err(|e| format!("Invalid style file: {e}"))?;
+
+        for (category, rules) in &style.categories {
+            for rule in rules {
+                if let Some(name) = &rule.block {
+                    if Block::from_name(name).is_none() {
+                        return Err(format!(
+                            "Unknown block '{name}' in style category '{category}'"
+                        ));
+                    }
+                }
+                if let Some(name) = &rule.biome {
+                    if Biome::from_name(name).is_none() {
+                        return Err(format!(
+                            "Unknown biome '{name}' in style category '{category}'"
+                        ));
+                    }
+                }
+                for (decoration, density) in &rule.densities {
+                    if !(0.0..=1.0).contains(density) {
+                        return Err(format!(
+                            "Density of '{decoration}' in style category '{category}' must be between 0 and 1"
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(style)
+    }
+
+    fn built_in() -> Self {
+        Self::from_json(DEFAULT_STYLE).expect("Built-in style is invalid")
+    }
+
+    /// Loads a custom style on top of the built-in one
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read style file {}: {e}", path.display()))?;
+        let custom = Self::from_json(&json)?;
+
+        let mut style = Self::built_in();
+        style.categories.extend(custom.categories);
+        Ok(style)
+    }
+
+    /// Returns the first rule of the category matching the tags
+    pub fn rule(&self, category: &str, tags: &HashMap<String, String>) -> Option<&StyleRule> {
+        self.categories
+            .get(category)?
+            .iter()
+            .find(|rule| rule.matches(tags))
+    }
+
+    /// Returns the block of the first matching rule, or the fallback if it has none
+    pub fn block(&self, category: &str, tags: &HashMap<String, String>, fallback: Block) -> Block {
+        self.rule(category, tags)
+            .and_then(StyleRule::block)
+            .unwrap_or(fallback)
+    }
+
+    /// Returns the biome of an area element from the `biome` category
+    pub fn biome(&self, tags: &HashMap<String, String>) -> Option<Biome> {
+        self.rule("biome", tags).and_then(StyleRule::biome)
+    }
+}
+
+/// Sets the style used for the generation. Without a path the built-in style is used.
+pub fn init(path: Option<&Path>) -> Result<(), String> {
+    let style = match path {
+        Some(path) => Style::load(path)?,
+        None => Style::built_in(),
+    };
+    STYLE
+        .set(style)
+        .map_err(|_| "Style was already initialized".to_string())
+}
+
+/// The style used for the generation, the built-in style unless `init` was called before
+pub fn style() -> &'static Style {
+    STYLE.get_or_init(Style::built_in)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_definitions::{BLACK_CONCRETE, DIRT, GRASS_BLOCK, STONE, STONE_BRICKS};
+
+    fn tags(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_built_in_style() {
+        let style = Style::built_in();
+
+        assert_eq!(
+            style.block("highway_surface", &tags(&[("surface", "asphalt")]), STONE),
+            BLACK_CONCRETE
+        );
+        assert_eq!(
+            style.block(
+                "highway_surface",
+                &tags(&[("surface", "unknown")]),
+                GRASS_BLOCK
+            ),
+            STONE
+        );
+
+        let residential = tags(&[("landuse", "residential")]);
+        let rural = tags(&[("landuse", "residential"), ("residential", "rural")]);
+        assert_eq!(style.block("landuse", &residential, STONE), STONE_BRICKS);
+        assert_eq!(style.block("landuse", &rural, STONE), GRASS_BLOCK);
+
+        let island = style.rule("landuse", &tags(&[("landuse", "traffic_island")]));
+        assert_eq!(island.map(StyleRule::height), Some(1));
+
+        assert_eq!(
+            style.biome(&tags(&[("natural", "wetland")])),
+            Biome::from_name("swamp")
+        );
+        assert_eq!(
+            style.biome(&tags(&[("natural", "water"), ("water", "lagoon")])),
+            Biome::from_name("ocean")
+        );
+        assert_eq!(style.biome(&residential), None);
+    }
+
+    #[test]
+    fn test_selectors() {
+        let style = Style::from_json(
+            r#"{"test": [
+                {"tags": {"a": "x|y", "b": "*"}, "block": "minecraft:stone"},
+                {"block": "grass_block", "densities": {"tree": 0.5}}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            style.block("test", &tags(&[("a", "y"), ("b", "1")]), DIRT),
+            STONE
+        );
+        assert_eq!(style.block("test", &tags(&[("a", "y")]), DIRT), GRASS_BLOCK);
+        assert_eq!(style.block("missing", &tags(&[]), DIRT), DIRT);
+        assert_eq!(style.rule("test", &tags(&[])).unwrap().density("tree"), 0.5);
+    }
+
+    #[test]
+    fn test_invalid_style() {
+        assert!(Style::from_json(r#"{"test": [{"block": "no_such_block"}]}"#).is_err());
+        assert!(Style::from_json(r#"{"test": [{"densities": {"tree": 2.0}}]}"#).is_err());
+        assert!(Style::from_json(r#"{"test": [{"biome": "no_such_biome"}]}"#).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::coordinate_system::cartesian::XZBBox;
 use crate::coordinate_system::geographic::LLBBox;
+use crate::coordinate_system::projection::Projection;
 use crate::osm_parser;
 use crate::osm_parser::ProcessedElement;
 use crate::retrieve_data;
@@ -7,12 +8,25 @@ use crate::retrieve_data;
 // this is copied from main.rs
 pub fn generate_example(llbbox: LLBBox) -> (XZBBox, Vec<ProcessedElement>) {
     // Fetch data
-    let raw_data: serde_json::Value =
-        retrieve_data::fetch_data_from_overpass(llbbox, false, "requests", None)
-            .expect("Failed to fetch data");
+    let raw_data: serde_json::Value = retrieve_data::fetch_data_from_overpass(
+        llbbox,
+        false,
+        "requests",
+        None,
+        &retrieve_data::OverpassSettings::default(),
+    )
+    .expect("Failed to fetch data");
 
     // Parse raw data
-    let (mut parsed_elements, xzbbox) = osm_parser::parse_osm_data(raw_data, llbbox, 1.0, false);
+    let (mut parsed_elements, xzbbox) = osm_parser::parse_osm_data(
+        raw_data,
+        llbbox,
+        None,
+        1.0,
+        Projection::default(),
+        None,
+        false,
+    );
     parsed_elements
         .sort_by_key(|element: &osm_parser::ProcessedElement| osm_parser::get_priority(element));
 
@@ -0,0 +1,170 @@
+//! Splitting of the generation area into tiles of whole regions.
+//!
+//! With tiled generation only the blocks of one tile are kept in memory at a time: the
+//! elements intersecting a tile are processed, the tile's regions are written to disk and
+//! freed before moving on to the next tile. Tiles are aligned to the region grid so that
+//! every region file is written exactly once.
+
+use crate::coordinate_system::cartesian::{XZBBox, XZPoint};
+use crate::osm_parser::ProcessedElement;
+
+/// Number of blocks along one side of a region file
+const REGION_SIZE: i32 = 512;
+
+/// Extra blocks around an element's nodes that it may still touch (tree crowns, wide roads,
+/// building overhangs). Elements within this distance of a tile are processed for it.
+const TILE_MARGIN: i32 = 16;
+
+/// Grid of tiles covering the generation area
+pub struct TileGrid {
+    xzbbox: XZBBox,
+    tile_size: i32,
+    min_tile_x: i32,
+    min_tile_z: i32,
+    columns: i32,
+    rows: i32,
+}
+
+impl TileGrid {
+    /// Splits the bbox into tiles of `regions_per_tile` x `regions_per_tile` regions
+    pub fn new(xzbbox: &XZBBox, regions_per_tile: u32) -> Self {
+        let tile_size = REGION_SIZE * regions_per_tile.max(1) as i32;
+        let min_tile_x = xzbbox.min_x().div_euclid(tile_size);
+        let min_tile_z = xzbbox.min_z().div_euclid(tile_size);
+
+        Self {
+            xzbbox: xzbbox.clone(),
+            tile_size,
+            min_tile_x,
+            min_tile_z,
+            columns: xzbbox.max_x().div_euclid(tile_size) - min_tile_x + 1,
+            rows: xzbbox.max_z().div_euclid(tile_size) - min_tile_z + 1,
+        }
+    }
+
+    /// Returns all tiles row by row, each clipped to the bbox
+    pub fn tiles(&self) -> Vec<XZBBox> {
+        let mut tiles = Vec::with_capacity((self.columns * self.rows) as usize);
+
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let tile_x = (self.min_tile_x + column) * self.tile_size;
+                let tile_z = (self.min_tile_z + row) * self.tile_size;
+
+                let min = XZPoint::new(
+                    tile_x.max(self.xzbbox.min_x()),
+                    tile_z.max(self.xzbbox.min_z()),
+                );
+                let max = XZPoint::new(
+                    (tile_x + self.tile_size - 1).min(self.xzbbox.max_x()),
+                    (tile_z + self.tile_size - 1).min(self.xzbbox.max_z()),
+                );
+                tiles.push(XZBBox::rect_from_min_max(min, max).expect("Tile is never empty"));
+            }
+        }
+
+        tiles
+    }
+
+    /// Builds a spatial index of the elements: for every tile (in the order of `tiles()`), the
+    /// indices of the elements whose extent plus a margin intersects it. The original element
+    /// order is preserved within each tile.
+    pub fn index_elements(&self, elements: &[ProcessedElement]) -> Vec<Vec<usize>> {
+        let mut tile_elements: Vec<Vec<usize>> =
+            vec![Vec::new(); (self.columns * self.rows) as usize];
+
+        for (index, element) in elements.iter().enumerate() {
+            let Some((min, max)) = element_extent(element) else {
+                continue;
+            };
+
+            let first_column = self.column_of(min.x - TILE_MARGIN);
+            let last_column = self.column_of(max.x + TILE_MARGIN);
+            let first_row = self.row_of(min.z - TILE_MARGIN);
+            let last_row = self.row_of(max.z + TILE_MARGIN);
+
+            for row in first_row..=last_row {
+                for column in first_column..=last_column {
+                    tile_elements[(row * self.columns + column) as usize].push(index);
+                }
+            }
+        }
+
+        tile_elements
+    }
+
+    fn column_of(&self, x: i32) -> i32 {
+        (x.div_euclid(self.tile_size) - self.min_tile_x).clamp(0, self.columns - 1)
+    }
+
+    fn row_of(&self, z: i32) -> i32 {
+        (z.div_euclid(self.tile_size) - self.min_tile_z).clamp(0, self.rows - 1)
+    }
+}
+
+/// Returns the min and max corner of all nodes of an element, including relation members
+fn element_extent(element: &ProcessedElement) -> Option<(XZPoint, XZPoint)> {
+    let points: Box<dyn Iterator<Item = XZPoint> + '_> = match element {
+        ProcessedElement::Relation(rel) => Box::new(
+            rel.members
+                .iter()
+                .flat_map(|member| member.way.nodes.iter().map(|node| node.xz())),
+        ),
+        _ => Box::new(element.nodes().map(|node| node.xz())),
+    };
+
+    points.fold(None, |extent, point| match extent {
+        None => Some((point, point)),
+        Some((min, max)) => Some((
+            XZPoint::new(min.x.min(point.x), min.z.min(point.z)),
+            XZPoint::new(max.x.max(point.x), max.z.max(point.z)),
+        )),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osm_parser::ProcessedNode;
+    use std::collections::HashMap;
+
+    fn node(x: i32, z: i32) -> ProcessedElement {
+        ProcessedElement::Node(ProcessedNode {
+            id: 1,
+            tags: HashMap::new(),
+            x,
+            z,
+        })
+    }
+
+    #[test]
+    fn test_tiles_are_region_aligned() {
+        let xzbbox = XZBBox::rect_from_xz_lengths(1100.0, 600.0).unwrap();
+        let tiles = TileGrid::new(&xzbbox, 1).tiles();
+
+        assert_eq!(tiles.len(), 3 * 2);
+        assert_eq!((tiles[0].min_x(), tiles[0].max_x()), (0, 511));
+        assert_eq!((tiles[2].min_x(), tiles[2].max_x()), (1024, 1100));
+        assert_eq!((tiles[5].min_z(), tiles[5].max_z()), (512, 600));
+
+        let total: u64 = tiles.iter().map(|t| t.bounding_rect().total_blocks()).sum();
+        assert_eq!(total, xzbbox.bounding_rect().total_blocks());
+    }
+
+    #[test]
+    fn test_index_elements() {
+        let xzbbox = XZBBox::rect_from_xz_lengths(1023.0, 511.0).unwrap();
+        let grid = TileGrid::new(&xzbbox, 1);
+        let elements = [node(10, 10), node(600, 10), node(515, 10), node(-900, 10)];
+
+        let index = grid.index_elements(&elements[..3]);
+
+        // The last node is within the margin of the first tile
+        assert_eq!(index[0], vec![0, 2]);
+        assert_eq!(index[1], vec![1, 2]);
+
+        // Elements outside the bbox are clamped to the border tiles
+        let far_away = grid.index_elements(&elements[3..]);
+        assert_eq!(far_away, vec![vec![0], vec![]]);
+    }
+}
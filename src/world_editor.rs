@@ -1,20 +1,31 @@
+use crate::bedrock::{self, BedrockChunk, BedrockSection, BedrockWriter};
+use crate::biomes::{self, Biome, ChunkBiomes};
 use crate::block_definitions::*;
 use crate::coordinate_system::cartesian::{XZBBox, XZPoint};
 use crate::coordinate_system::geographic::LLBBox;
+use crate::entities::{self, Entity};
 use crate::ground::Ground;
+use crate::lighting::{Heightmaps, LightCache, LightGrid, LightProps};
+use crate::mc_version::McVersion;
 use crate::progress::emit_gui_progress_update;
+use crate::render::{self, SurfaceBlock};
+use crate::report::Report;
+use crate::schematic::Schematic;
+use clap::ValueEnum;
 use colored::Colorize;
 use fastanvil::Region;
-use fastnbt::{LongArray, Value};
+use fastnbt::{IntArray, LongArray, Value};
 use fnv::FnvHashMap;
+use image::RgbaImage;
 use indicatif::{ProgressBar, ProgressStyle};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -40,6 +51,7 @@ struct Section {
 #[derive(Serialize, Deserialize)]
 struct Blockstates {
     palette: Vec<PaletteItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     data: Option<LongArray>,
     #[serde(flatten)]
     other: FnvHashMap<String, Value>,
@@ -49,7 +61,7 @@ struct Blockstates {
 struct PaletteItem {
     #[serde(rename = "Name")]
     name: String,
-    #[serde(rename = "Properties")]
+    #[serde(rename = "Properties", skip_serializing_if = "Option::is_none")]
     properties: Option<Value>,
 }
 
@@ -57,6 +69,8 @@ struct SectionToModify {
     blocks: [Block; 4096],
     // Store properties for blocks that have them, indexed by the same index as blocks array
     properties: FnvHashMap<usize, Value>,
+    // One bit per block that was set, so air placed on purpose is told apart from untouched air
+    written: [u64; 64],
 }
 
 impl SectionToModify {
@@ -70,7 +84,9 @@ impl SectionToModify {
     }
 
     fn set_block(&mut self, x: u8, y: u8, z: u8, block: Block) {
-        self.blocks[Self::index(x, y, z)] = block;
+        let index = Self::index(x, y, z);
+        self.blocks[index] = block;
+        self.mark_written(index);
     }
 
     fn set_block_with_properties(
@@ -82,6 +98,7 @@ impl SectionToModify {
     ) {
         let index = Self::index(x, y, z);
         self.blocks[index] = block_with_props.block;
+        self.mark_written(index);
 
         // Store properties if they exist
         if let Some(props) = block_with_props.properties {
@@ -96,6 +113,14 @@ impl SectionToModify {
         usize::from(y) % 16 * 256 + usize::from(z) * 16 + usize::from(x)
     }
 
+    fn mark_written(&mut self, index: usize) {
+        self.written[index / 64] |= 1 << (index % 64);
+    }
+
+    fn is_written(&self, index: usize) -> bool {
+        self.written[index / 64] & (1 << (index % 64)) != 0
+    }
+
     fn to_section(&self, y: i8) -> Section {
         // Create a map of unique block+properties combinations to palette indices
         let mut unique_blocks: Vec<(Block, Option<Value>)> = Vec::new();
@@ -116,39 +141,17 @@ impl SectionToModify {
             }
         }
 
-        let mut bits_per_block = 4; // minimum allowed
-        while (1 << bits_per_block) < unique_blocks.len() {
-            bits_per_block += 1;
-        }
-
-        let mut data = vec![];
-        let mut cur = 0;
-        let mut cur_idx = 0;
-
-        for (i, &block) in self.blocks.iter().enumerate() {
+        let indices = self.blocks.iter().enumerate().map(|(i, &block)| {
             let properties = self.properties.get(&i).cloned();
             let props_key = properties.as_ref().map(|p| format!("{p:?}"));
-            let lookup_key = (block, props_key);
-            let p = palette_lookup[&lookup_key] as i64;
-
-            if cur_idx + bits_per_block > 64 {
-                data.push(cur);
-                cur = 0;
-                cur_idx = 0;
-            }
-
-            cur |= p << cur_idx;
-            cur_idx += bits_per_block;
-        }
-
-        if cur_idx > 0 {
-            data.push(cur);
-        }
+            palette_lookup[&(block, props_key)]
+        });
+        let data = pack_palette_indices(indices, unique_blocks.len());
 
         let palette = unique_blocks
             .iter()
             .map(|(block, stored_props)| PaletteItem {
-                name: block.name().to_string(),
+                name: block.qualified_name(),
                 properties: stored_props.clone().or_else(|| block.properties()),
             })
             .collect();
@@ -163,6 +166,143 @@ impl SectionToModify {
             other: FnvHashMap::default(),
         }
     }
+
+    /// Overlays the blocks of this section onto a section read from an existing world.
+    /// Positions that were never set keep their existing block.
+    fn merge_into(&self, existing: &mut Section) {
+        let mut palette: Vec<PaletteItem> = std::mem::take(&mut existing.block_states.palette);
+        let mut indices: Vec<usize> =
+            unpack_palette_indices(existing.block_states.data.as_deref(), palette.len());
+
+        if palette.is_empty() {
+            palette.push(PaletteItem {
+                name: AIR.qualified_name(),
+                properties: None,
+            });
+        }
+
+        let mut palette_lookup: FnvHashMap<(String, Option<String>), usize> = palette
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (palette_key(&item.name, item.properties.as_ref()), i))
+            .collect();
+
+        // Palette index per block without stored properties, so names are built once per block
+        let mut block_indices: FnvHashMap<Block, usize> = FnvHashMap::default();
+
+        for (i, &block) in self.blocks.iter().enumerate() {
+            if !self.is_written(i) {
+                continue;
+            }
+            let stored_properties = self.properties.get(&i);
+            if stored_properties.is_none() {
+                if let Some(&index) = block_indices.get(&block) {
+                    indices[i] = index;
+                    continue;
+                }
+            }
+
+            let properties = stored_properties.cloned().or_else(|| block.properties());
+            let name = block.qualified_name();
+            let key = palette_key(&name, properties.as_ref());
+
+            let index = *palette_lookup.entry(key).or_insert_with(|| {
+                palette.push(PaletteItem { name, properties });
+                palette.len() - 1
+            });
+            if stored_properties.is_none() {
+                block_indices.insert(block, index);
+            }
+            indices[i] = index;
+        }
+
+        existing.block_states.data = if palette.len() > 1 {
+            Some(LongArray::new(pack_palette_indices(
+                indices.into_iter(),
+                palette.len(),
+            )))
+        } else {
+            None
+        };
+        existing.block_states.palette = palette;
+
+        // Stored light no longer matches the blocks, it is recomputed when the chunk is written
+        existing.other.remove("BlockLight");
+        existing.other.remove("SkyLight");
+    }
+}
+
+/// Number of bits used per block in the packed block state array (minimum 4)
+fn bits_per_block(palette_len: usize) -> usize {
+    let mut bits = 4;
+    while (1 << bits) < palette_len {
+        bits += 1;
+    }
+    bits
+}
+
+/// Packs palette indices into longs. Since 1.16 entries never span two longs.
+fn pack_palette_indices(indices: impl Iterator<Item = usize>, palette_len: usize) -> Vec<i64> {
+    let bits = bits_per_block(palette_len);
+
+    let mut data = vec![];
+    let mut cur: i64 = 0;
+    let mut cur_idx = 0;
+
+    for p in indices {
+        if cur_idx + bits > 64 {
+            data.push(cur);
+            cur = 0;
+            cur_idx = 0;
+        }
+
+        cur |= (p as i64) << cur_idx;
+        cur_idx += bits;
+    }
+
+    if cur_idx > 0 {
+        data.push(cur);
+    }
+
+    data
+}
+
+/// Unpacks the 4096 palette indices of a section, the inverse of `pack_palette_indices`
+fn unpack_palette_indices(data: Option<&[i64]>, palette_len: usize) -> Vec<usize> {
+    let mut indices = vec![0; 4096];
+    let Some(data) = data else {
+        return indices; // Single-entry palettes have no data array
+    };
+    if palette_len <= 1 {
+        return indices;
+    }
+
+    let bits = bits_per_block(palette_len);
+    let per_long = 64 / bits;
+    let mask: u64 = (1 << bits) - 1;
+
+    for (i, index) in indices.iter_mut().enumerate() {
+        let Some(&long) = data.get(i / per_long) else {
+            break;
+        };
+        let shift = (i % per_long) * bits;
+        *index = ((long as u64 >> shift) & mask) as usize;
+    }
+
+    indices
+}
+
+/// Palette lookup key that treats `grass_block` and `minecraft:grass_block` as the same block.
+/// Properties are sorted, as the order of a compound's entries is not stable.
+fn palette_key(name: &str, properties: Option<&Value>) -> (String, Option<String>) {
+    let properties = properties.map(|p| match p {
+        Value::Compound(map) => format!("{:?}", map.iter().collect::<BTreeMap<_, _>>()),
+        _ => format!("{p:?}"),
+    });
+    (
+        name.trim_start_matches("minecraft:").to_string(),
+        properties,
+    )
 }
 
 impl Default for SectionToModify {
@@ -170,6 +310,7 @@ impl Default for SectionToModify {
         Self {
             blocks: [AIR; 4096],
             properties: FnvHashMap::default(),
+            written: [0; 64],
         }
     }
 }
@@ -177,6 +318,8 @@ impl Default for SectionToModify {
 #[derive(Default)]
 struct ChunkToModify {
     sections: FnvHashMap<i8, SectionToModify>,
+    biomes: ChunkBiomes,
+    entities: Vec<Entity>,
     other: FnvHashMap<String, Value>,
 }
 
@@ -214,6 +357,35 @@ impl ChunkToModify {
     fn sections(&self) -> impl Iterator<Item = Section> + '_ {
         self.sections.iter().map(|(y, s)| s.to_section(*y))
     }
+
+    /// The topmost block of a column that is solid or contains fluid, with the depth of the
+    /// water it belongs to
+    fn surface_block(&self, x: u8, z: u8, cache: &mut LightCache) -> Option<SurfaceBlock> {
+        let mut section_ys: Vec<i8> = self.sections.keys().copied().collect();
+        section_ys.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut surface: Option<SurfaceBlock> = None;
+        for section_y in section_ys {
+            for y in (0..16).rev() {
+                let block = self.sections[&section_y].get_block(x, y, z);
+                match (&mut surface, block) {
+                    (Some(surface), Some(WATER)) if surface.block == WATER => {
+                        surface.water_depth += 1;
+                    }
+                    (Some(_), _) => return surface,
+                    (None, Some(block)) if cache.get(block).motion_blocking => {
+                        surface = Some(SurfaceBlock {
+                            block,
+                            y: (i32::from(section_y) << 4) + i32::from(y),
+                            water_depth: i32::from(block == WATER),
+                        });
+                    }
+                    (None, _) => {}
+                }
+            }
+        }
+        surface
+    }
 }
 
 #[derive(Default)]
@@ -245,6 +417,12 @@ impl WorldToModify {
         self.regions.get(&(x, z))
     }
 
+    /// Returns the chunk at the given absolute chunk coordinates
+    fn get_chunk(&self, chunk_x: i32, chunk_z: i32) -> Option<&ChunkToModify> {
+        self.get_region(chunk_x >> 5, chunk_z >> 5)?
+            .get_chunk(chunk_x & 31, chunk_z & 31)
+    }
+
     fn get_block(&self, x: i32, y: i32, z: i32) -> Option<Block> {
         let chunk_x: i32 = x >> 4;
         let chunk_z: i32 = z >> 4;
@@ -316,6 +494,16 @@ struct WorldMetadata {
     max_geo_lon: f64,
 }
 
+/// Edition whose world format is written
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+pub enum WorldFormat {
+    /// Java Edition Anvil region files
+    #[default]
+    Java,
+    /// Bedrock Edition LevelDB world
+    Bedrock,
+}
+
 // Notes for someone not familiar with lifetime parameter:
 // The follwing is like a C++ template:
 // template<lifetime A>
@@ -325,7 +513,13 @@ pub struct WorldEditor<'a> {
     world: WorldToModify,
     xzbbox: &'a XZBBox,
     llbbox: LLBBox,
-    ground: Option<Box<Ground>>,
+    ground: Option<&'a Ground>,
+    update_existing: bool,
+    tile: Option<XZBBox>,
+    version: McVersion,
+    /// Database of the Bedrock world, shared by all tile editors
+    bedrock: Option<Arc<Mutex<BedrockWriter>>>,
+    report: Mutex<Report>,
 }
 
 // template<lifetime A>
@@ -339,17 +533,89 @@ impl<'a> WorldEditor<'a> {
             xzbbox,
             llbbox,
             ground: None,
+            update_existing: false,
+            tile: None,
+            version: McVersion::default(),
+            bedrock: None,
+            report: Mutex::default(),
         }
     }
 
+    /// Restricts block placement to the given tile (tiled generation), `None` lifts the restriction
+    pub fn set_tile(&mut self, tile: Option<XZBBox>) {
+        self.tile = tile;
+    }
+
+    /// Creates an empty editor with the same settings, restricted to the given tile.
+    /// Used to generate tiles concurrently, see `merge`.
+    pub fn for_tile(&self, tile: XZBBox) -> Self {
+        Self {
+            world_dir: self.world_dir.clone(),
+            world: WorldToModify::default(),
+            xzbbox: self.xzbbox,
+            llbbox: self.llbbox,
+            ground: self.ground,
+            update_existing: self.update_existing,
+            tile: Some(tile),
+            version: self.version,
+            bedrock: self.bedrock.clone(),
+            report: Mutex::default(),
+        }
+    }
+
+    /// Moves all modifications of a tile editor into this editor. Tiles are aligned to the
+    /// region grid, so the regions of different tiles never overlap.
+    pub fn merge(&mut self, other: WorldEditor) {
+        self.world.regions.extend(other.world.regions);
+        self.report
+            .get_mut()
+            .unwrap()
+            .merge(other.report.into_inner().unwrap());
+    }
+
+    /// The report of this generation run, see `save`
+    pub fn report(&self) -> MutexGuard<'_, Report> {
+        self.report.lock().unwrap()
+    }
+
+    /// Checks whether blocks may be placed at the given coordinates
+    #[inline]
+    pub fn is_in_bounds(&self, x: i32, z: i32) -> bool {
+        let point = XZPoint::new(x, z);
+        self.xzbbox.contains(&point) && self.tile.as_ref().is_none_or(|tile| tile.contains(&point))
+    }
+
+    /// Sets the Minecraft version whose chunk format is written
+    pub fn set_version(&mut self, version: McVersion) {
+        self.version = version;
+    }
+
+    /// Selects the world format, creating the Bedrock database when needed
+    pub fn set_format(&mut self, format: WorldFormat) -> std::io::Result<()> {
+        self.bedrock = match format {
+            WorldFormat::Java => None,
+            WorldFormat::Bedrock => Some(Arc::new(Mutex::new(BedrockWriter::create(
+                &self.world_dir,
+            )?))),
+        };
+        Ok(())
+    }
+
+    /// Enables updating an existing world in place: region files are opened instead of being
+    /// recreated, modified sections are merged into the existing ones and untouched chunks
+    /// are left as they are.
+    pub fn set_update_existing(&mut self, update_existing: bool) {
+        self.update_existing = update_existing;
+    }
+
     /// Sets the ground reference for elevation-based block placement
-    pub fn set_ground(&mut self, ground: &Ground) {
-        self.ground = Some(Box::new(ground.clone()));
+    pub fn set_ground(&mut self, ground: &'a Ground) {
+        self.ground = Some(ground);
     }
 
     /// Gets a reference to the ground data if available
     pub fn get_ground(&self) -> Option<&Ground> {
-        self.ground.as_ref().map(|g| g.as_ref())
+        self.ground
     }
 
     /// Calculate the absolute Y position from a ground-relative offset
@@ -365,6 +631,39 @@ impl<'a> WorldEditor<'a> {
         }
     }
 
+    /// Opens the region file for the given region coordinates.
+    /// In update mode an existing region file is reused, otherwise it is recreated. Returns
+    /// `None` if the existing region file can't be read, so it is left as it is.
+    fn open_region(&self, region_x: i32, region_z: i32) -> Option<Region<File>> {
+        let path = self
+            .world_dir
+            .join(format!("region/r.{}.{}.mca", region_x, region_z));
+
+        let has_data = std::fs::metadata(&path)
+            .map(|m| m.len() > 0)
+            .unwrap_or(false);
+
+        if self.update_existing && has_data {
+            let region_file: File = File::options()
+                .read(true)
+                .write(true)
+                .open(&path)
+                .expect("Failed to open region file");
+            return match Region::from_stream(region_file) {
+                Ok(region) => Some(region),
+                Err(e) => {
+                    eprintln!(
+                        "Warning: Skipping unreadable region file {}: {e}",
+                        path.display()
+                    );
+                    None
+                }
+            };
+        }
+
+        Some(self.create_region(region_x, region_z))
+    }
+
     /// Creates a region for the given region coordinates.
     fn create_region(&self, region_x: i32, region_z: i32) -> Region<File> {
         let out_path = self
@@ -396,71 +695,120 @@ impl<'a> WorldEditor<'a> {
         (self.xzbbox.max_x(), self.xzbbox.max_z())
     }
 
-    #[allow(unused)]
     #[inline]
     pub fn block_at(&self, x: i32, y: i32, z: i32) -> bool {
         let absolute_y = self.get_absolute_y(x, y, z);
         self.world.get_block(x, absolute_y, z).is_some()
     }
 
-    #[allow(clippy::too_many_arguments, dead_code)]
-    pub fn set_sign(
-        &mut self,
-        line1: String,
-        line2: String,
-        line3: String,
-        line4: String,
-        x: i32,
-        y: i32,
-        z: i32,
-        _rotation: i8,
-    ) {
+    /// Places a standing sign with up to four lines of text on its front. Y value is
+    /// interpreted as an offset from ground level. The rotation counts in steps of 22.5
+    /// degrees clockwise from 0, which faces south. Nothing is placed if the block is taken.
+    pub fn set_sign(&mut self, lines: &[String; 4], x: i32, y: i32, z: i32, rotation: u8) {
+        if !self.is_in_bounds(x, z) || self.block_at(x, y, z) {
+            return;
+        }
+
         let absolute_y = self.get_absolute_y(x, y, z);
         let chunk_x = x >> 4;
         let chunk_z = z >> 4;
         let region_x = chunk_x >> 5;
         let region_z = chunk_z >> 5;
 
-        let mut block_entities = HashMap::new();
-
-        let messages = vec![
-            Value::String(format!("\"{line1}\"")),
-            Value::String(format!("\"{line2}\"")),
-            Value::String(format!("\"{line3}\"")),
-            Value::String(format!("\"{line4}\"")),
-        ];
-
-        let mut text_data = HashMap::new();
-        text_data.insert("messages".to_string(), Value::List(messages));
-        text_data.insert("color".to_string(), Value::String("black".to_string()));
-        text_data.insert("has_glowing_text".to_string(), Value::Byte(0));
+        // Every line is a JSON text component
+        let text = |lines: &[&str]| {
+            let messages = lines
+                .iter()
+                .map(|line| Value::String(serde_json::Value::from(*line).to_string()))
+                .collect();
+            Value::Compound(HashMap::from([
+                ("messages".to_string(), Value::List(messages)),
+                ("color".to_string(), Value::String("black".to_string())),
+                ("has_glowing_text".to_string(), Value::Byte(0)),
+            ]))
+        };
+        let front_lines: Vec<&str> = lines.iter().map(String::as_str).collect();
 
-        block_entities.insert("front_text".to_string(), Value::Compound(text_data));
-        block_entities.insert(
-            "id".to_string(),
-            Value::String("minecraft:sign".to_string()),
-        );
-        block_entities.insert("is_waxed".to_string(), Value::Byte(0));
-        block_entities.insert("keepPacked".to_string(), Value::Byte(0));
-        block_entities.insert("x".to_string(), Value::Int(x));
-        block_entities.insert("y".to_string(), Value::Int(absolute_y));
-        block_entities.insert("z".to_string(), Value::Int(z));
+        let block_entity = HashMap::from([
+            ("front_text".to_string(), text(&front_lines)),
+            ("back_text".to_string(), text(&[""; 4])),
+            (
+                "id".to_string(),
+                Value::String("minecraft:sign".to_string()),
+            ),
+            ("is_waxed".to_string(), Value::Byte(0)),
+            ("keepPacked".to_string(), Value::Byte(0)),
+            ("x".to_string(), Value::Int(x)),
+            ("y".to_string(), Value::Int(absolute_y)),
+            ("z".to_string(), Value::Int(z)),
+        ]);
 
         let region: &mut RegionToModify = self.world.get_or_create_region(region_x, region_z);
         let chunk: &mut ChunkToModify = region.get_or_create_chunk(chunk_x & 31, chunk_z & 31);
 
         if let Some(chunk_data) = chunk.other.get_mut("block_entities") {
             if let Value::List(entities) = chunk_data {
-                entities.push(Value::Compound(block_entities));
+                entities.push(Value::Compound(block_entity));
             }
         } else {
             chunk.other.insert(
                 "block_entities".to_string(),
-                Value::List(vec![Value::Compound(block_entities)]),
+                Value::List(vec![Value::Compound(block_entity)]),
             );
         }
 
-        self.set_block(SIGN, x, y, z, None, None);
+        let properties = Value::Compound(HashMap::from([
+            (
+                "rotation".to_string(),
+                Value::String((rotation % 16).to_string()),
+            ),
+            (
+                "waterlogged".to_string(),
+                Value::String("false".to_string()),
+            ),
+        ]));
+        self.world.set_block_with_properties(
+            x,
+            absolute_y,
+            z,
+            BlockWithProperties::new(SIGN, Some(properties)),
+        );
+    }
+
+    /// Places an entity at the center of the given block.
+    /// Y value is interpreted as an offset from ground level.
+    pub fn add_entity(&mut self, entity: Entity, x: i32, y: i32, z: i32) {
+        if !self.is_in_bounds(x, z) {
+            return;
+        }
+
+        let absolute_y = self.get_absolute_y(x, y, z);
+        let chunk_x: i32 = x >> 4;
+        let chunk_z: i32 = z >> 4;
+        let region: &mut RegionToModify =
+            self.world.get_or_create_region(chunk_x >> 5, chunk_z >> 5);
+        let chunk: &mut ChunkToModify = region.get_or_create_chunk(chunk_x & 31, chunk_z & 31);
+
+        chunk.entities.push(entity.at(
+            f64::from(x) + 0.5,
+            f64::from(absolute_y),
+            f64::from(z) + 0.5,
+        ));
+    }
+
+    /// Sets the biome of the 4x4 column containing the given coordinates
+    pub fn set_biome(&mut self, x: i32, z: i32, biome: Biome) {
+        if !self.is_in_bounds(x, z) {
+            return;
+        }
+
+        let chunk_x: i32 = x >> 4;
+        let chunk_z: i32 = z >> 4;
+        let region: &mut RegionToModify =
+            self.world.get_or_create_region(chunk_x >> 5, chunk_z >> 5);
+        let chunk: &mut ChunkToModify = region.get_or_create_chunk(chunk_x & 31, chunk_z & 31);
+
+        chunk.biomes[biomes::column_index((x & 15) as usize, (z & 15) as usize)] = Some(biome);
     }
 
     /// Sets a block of the specified type at the given coordinates.
@@ -476,7 +824,7 @@ impl<'a> WorldEditor<'a> {
         override_blacklist: Option<&[Block]>,
     ) {
         // Check if coordinates are within bounds
-        if !self.xzbbox.contains(&XZPoint::new(x, z)) {
+        if !self.is_in_bounds(x, z) {
             return;
         }
 
@@ -517,7 +865,7 @@ impl<'a> WorldEditor<'a> {
         override_blacklist: Option<&[Block]>,
     ) {
         // Check if coordinates are within bounds
-        if !self.xzbbox.contains(&XZPoint::new(x, z)) {
+        if !self.is_in_bounds(x, z) {
             return;
         }
 
@@ -555,7 +903,7 @@ impl<'a> WorldEditor<'a> {
         override_blacklist: Option<&[Block]>,
     ) {
         // Check if coordinates are within bounds
-        if !self.xzbbox.contains(&XZPoint::new(x, z)) {
+        if !self.is_in_bounds(x, z) {
             return;
         }
 
@@ -719,7 +1067,11 @@ impl<'a> WorldEditor<'a> {
     }
 
     /// Helper function to create a base chunk with grass blocks at Y -62
-    fn create_base_chunk(abs_chunk_x: i32, abs_chunk_z: i32) -> (Vec<u8>, bool) {
+    fn create_base_chunk(
+        abs_chunk_x: i32,
+        abs_chunk_z: i32,
+        version: McVersion,
+    ) -> (Vec<u8>, bool) {
         let mut chunk = ChunkToModify::default();
 
         // Fill the bottom layer with grass blocks at Y -62
@@ -738,12 +1090,13 @@ impl<'a> WorldEditor<'a> {
             other: chunk.other,
         };
 
-        // Create the Level wrapper
-        let level_data = create_level_wrapper(&chunk_data);
-
-        // Serialize the chunk with Level wrapper
+        // Serialize the chunk in the layout of the target version
         let mut ser_buffer = Vec::with_capacity(8192);
-        fastnbt::to_writer(&mut ser_buffer, &level_data).unwrap();
+        fastnbt::to_writer(
+            &mut ser_buffer,
+            &chunk_to_nbt(&chunk_data, &[None; 16], version),
+        )
+        .unwrap();
 
         (ser_buffer, true)
     }
@@ -779,131 +1132,484 @@ impl<'a> WorldEditor<'a> {
             .regions
             .par_iter()
             .for_each(|((region_x, region_z), region_to_modify)| {
-                let mut region = self.create_region(*region_x, *region_z);
-                let mut ser_buffer = Vec::with_capacity(8192);
-
-                for (&(chunk_x, chunk_z), chunk_to_modify) in &region_to_modify.chunks {
-                    if !chunk_to_modify.sections.is_empty() || !chunk_to_modify.other.is_empty() {
-                        // Read existing chunk data if it exists
-                        let existing_data = region
-                            .read_chunk(chunk_x as usize, chunk_z as usize)
-                            .unwrap()
-                            .unwrap_or_default();
-
-                        // Parse existing chunk or create new one
-                        let mut chunk: Chunk = if !existing_data.is_empty() {
-                            fastnbt::from_bytes(&existing_data).unwrap()
+                self.write_region(*region_x, *region_z, region_to_modify);
+
+                // Update progress
+                let regions_done = regions_processed.fetch_add(1, Ordering::SeqCst);
+                let new_progress = (90.0 + (regions_done as f64 * progress_increment_save)) * 10.0;
+                let prev_progress =
+                    current_progress.fetch_max(new_progress as u64, Ordering::SeqCst);
+
+                if new_progress as u64 - prev_progress > 1 {
+                    emit_gui_progress_update(new_progress / 10.0, "Saving world...");
+                }
+
+                save_pb.inc(1);
+            });
+
+        save_pb.finish();
+
+        if let Some(bedrock) = self.bedrock.take() {
+            let bedrock = Arc::into_inner(bedrock)
+                .expect("Bedrock world still in use")
+                .into_inner()
+                .unwrap();
+            let level_name = self
+                .world_dir
+                .file_name()
+                .map_or("Arnis World".into(), |name| name.to_string_lossy());
+            let spawn = (
+                (self.xzbbox.min_x() + self.xzbbox.max_x()) / 2,
+                (self.xzbbox.min_z() + self.xzbbox.max_z()) / 2,
+            );
+            if let Err(e) = bedrock.finish(&level_name, spawn) {
+                eprintln!("Error: Failed to finish Bedrock world: {}", e);
+            }
+        }
+
+        if let Err(e) = self.save_report() {
+            eprintln!("Warning: Failed to save generation report: {}", e);
+        }
+    }
+
+    /// Writes the modifications of one region to its region file
+    fn write_region(&self, region_x: i32, region_z: i32, region_to_modify: &RegionToModify) {
+        self.report_region(region_x, region_z, region_to_modify);
+
+        if let Some(bedrock) = &self.bedrock {
+            self.write_bedrock_region(bedrock, region_x, region_z, region_to_modify);
+            return;
+        }
+
+        let Some(mut region) = self.open_region(region_x, region_z) else {
+            return;
+        };
+        let mut ser_buffer = Vec::with_capacity(8192);
+        let mut light_cache = LightCache::default();
+
+        for (&(chunk_x, chunk_z), chunk_to_modify) in &region_to_modify.chunks {
+            if !chunk_to_modify.sections.is_empty()
+                || !chunk_to_modify.entities.is_empty()
+                || !chunk_to_modify.other.is_empty()
+            {
+                // Read existing chunk data if it exists
+                let existing_data = match region.read_chunk(chunk_x as usize, chunk_z as usize) {
+                    Ok(data) => data.unwrap_or_default(),
+                    Err(e) => {
+                        eprintln!(
+                            "Warning: Skipping unreadable chunk {chunk_x}, {chunk_z} in region {region_x}, {region_z}: {e}"
+                        );
+                        continue;
+                    }
+                };
+
+                // Parse existing chunk or create new one
+                let (mut chunk, level_wrapped): (Chunk, bool) = if !existing_data.is_empty() {
+                    match parse_chunk(&existing_data) {
+                        Ok(parsed) => parsed,
+                        Err(e) => {
+                            eprintln!(
+                                "Warning: Skipping unreadable chunk {chunk_x}, {chunk_z} in region {region_x}, {region_z}: {e}"
+                            );
+                            continue;
+                        }
+                    }
+                } else {
+                    (
+                        Chunk {
+                            sections: Vec::new(),
+                            x_pos: chunk_x + (region_x * 32),
+                            z_pos: chunk_z + (region_z * 32),
+                            is_light_on: 0,
+                            other: FnvHashMap::default(),
+                        },
+                        true,
+                    )
+                };
+
+                // Update sections while preserving existing data
+                for (&section_y, section_to_modify) in &chunk_to_modify.sections {
+                    if let Some(existing_section) =
+                        chunk.sections.iter_mut().find(|s| s.y == section_y)
+                    {
+                        if self.update_existing {
+                            // Keep blocks that were not touched by the generation
+                            section_to_modify.merge_into(existing_section);
                         } else {
-                            Chunk {
-                                sections: Vec::new(),
-                                x_pos: chunk_x + (region_x * 32),
-                                z_pos: chunk_z + (region_z * 32),
-                                is_light_on: 0,
-                                other: FnvHashMap::default(),
-                            }
-                        };
-
-                        // Update sections while preserving existing data
-                        let new_sections: Vec<Section> = chunk_to_modify.sections().collect();
-                        for new_section in new_sections {
-                            if let Some(existing_section) =
-                                chunk.sections.iter_mut().find(|s| s.y == new_section.y)
-                            {
-                                // Merge block states
-                                existing_section.block_states.palette =
-                                    new_section.block_states.palette;
-                                existing_section.block_states.data = new_section.block_states.data;
-                            } else {
-                                // Add new section if it doesn't exist
-                                chunk.sections.push(new_section);
-                            }
+                            // Merge block states
+                            let new_section = section_to_modify.to_section(section_y);
+                            existing_section.block_states.palette =
+                                new_section.block_states.palette;
+                            existing_section.block_states.data = new_section.block_states.data;
                         }
+                    } else {
+                        // Add new section if it doesn't exist
+                        chunk.sections.push(section_to_modify.to_section(section_y));
+                    }
+                }
+
+                // Chunks saved by the game keep the biomes of the cells no area assigned one
+                if !level_wrapped && chunk_to_modify.biomes.iter().any(Option::is_some) {
+                    for section in &mut chunk.sections {
+                        let biomes = biomes::merge_section_biomes(
+                            section.other.get("biomes"),
+                            &chunk_to_modify.biomes,
+                        );
+                        section.other.insert("biomes".to_string(), biomes);
+                    }
+                }
 
-                        // Preserve existing block entities and merge with new ones
-                        if let Some(existing_entities) = chunk.other.get_mut("block_entities") {
-                            if let Some(new_entities) = chunk_to_modify.other.get("block_entities")
-                            {
-                                if let (Value::List(existing), Value::List(new)) =
-                                    (existing_entities, new_entities)
-                                {
-                                    // Remove old entities that are replaced by new ones
-                                    existing.retain(|e| {
-                                        if let Value::Compound(map) = e {
-                                            let (x, y, z) = get_entity_coords(map);
-                                            !new.iter().any(|new_e| {
-                                                if let Value::Compound(new_map) = new_e {
-                                                    let (nx, ny, nz) = get_entity_coords(new_map);
-                                                    x == nx && y == ny && z == nz
-                                                } else {
-                                                    false
-                                                }
-                                            })
+                // Preserve existing block entities and merge with new ones
+                if let Some(existing_entities) = chunk.other.get_mut("block_entities") {
+                    if let Some(new_entities) = chunk_to_modify.other.get("block_entities") {
+                        if let (Value::List(existing), Value::List(new)) =
+                            (existing_entities, new_entities)
+                        {
+                            // Remove old entities that are replaced by new ones
+                            existing.retain(|e| {
+                                if let Value::Compound(map) = e {
+                                    let (x, y, z) = get_entity_coords(map);
+                                    !new.iter().any(|new_e| {
+                                        if let Value::Compound(new_map) = new_e {
+                                            let (nx, ny, nz) = get_entity_coords(new_map);
+                                            x == nx && y == ny && z == nz
                                         } else {
-                                            true
+                                            false
                                         }
-                                    });
-                                    // Add new entities
-                                    existing.extend(new.clone());
+                                    })
+                                } else {
+                                    true
                                 }
-                            }
-                        } else {
-                            // If no existing entities, just add the new ones
-                            if let Some(new_entities) = chunk_to_modify.other.get("block_entities")
-                            {
-                                chunk
-                                    .other
-                                    .insert("block_entities".to_string(), new_entities.clone());
-                            }
+                            });
+                            // Add new entities
+                            existing.extend(new.clone());
                         }
-
-                        // Update chunk coordinates and flags
-                        chunk.x_pos = chunk_x + (region_x * 32);
-                        chunk.z_pos = chunk_z + (region_z * 32);
-
-                        // Create Level wrapper and save
-                        let level_data = create_level_wrapper(&chunk);
-                        ser_buffer.clear();
-                        fastnbt::to_writer(&mut ser_buffer, &level_data).unwrap();
-                        region
-                            .write_chunk(chunk_x as usize, chunk_z as usize, &ser_buffer)
-                            .unwrap();
+                    }
+                } else {
+                    // If no existing entities, just add the new ones
+                    if let Some(new_entities) = chunk_to_modify.other.get("block_entities") {
+                        chunk
+                            .other
+                            .insert("block_entities".to_string(), new_entities.clone());
                     }
                 }
 
-                // Second pass: ensure all chunks exist
-                for chunk_x in 0..32 {
-                    for chunk_z in 0..32 {
-                        let abs_chunk_x = chunk_x + (region_x * 32);
-                        let abs_chunk_z = chunk_z + (region_z * 32);
-
-                        // Check if chunk exists in our modifications
-                        let chunk_exists =
-                            region_to_modify.chunks.contains_key(&(chunk_x, chunk_z));
-
-                        // If chunk doesn't exist, create it with base layer
-                        if !chunk_exists {
-                            let (ser_buffer, _) = Self::create_base_chunk(abs_chunk_x, abs_chunk_z);
-                            region
-                                .write_chunk(chunk_x as usize, chunk_z as usize, &ser_buffer)
-                                .unwrap();
+                // Entities of versions before 1.17 are part of the chunk
+                if !self.version.has_entity_regions() && !chunk_to_modify.entities.is_empty() {
+                    let new_entities = chunk_to_modify
+                        .entities
+                        .iter()
+                        .map(|entity| entity.to_nbt(self.version));
+                    match chunk.other.get_mut("Entities") {
+                        Some(Value::List(existing)) => existing.extend(new_entities),
+                        _ => {
+                            chunk.other.insert(
+                                "Entities".to_string(),
+                                Value::List(new_entities.collect()),
+                            );
                         }
                     }
                 }
 
-                // Update progress
-                let regions_done = regions_processed.fetch_add(1, Ordering::SeqCst);
-                let new_progress = (90.0 + (regions_done as f64 * progress_increment_save)) * 10.0;
-                let prev_progress =
-                    current_progress.fetch_max(new_progress as u64, Ordering::SeqCst);
+                // Update chunk coordinates and flags
+                chunk.x_pos = chunk_x + (region_x * 32);
+                chunk.z_pos = chunk_z + (region_z * 32);
+
+                ser_buffer.clear();
+                if level_wrapped {
+                    // Chunk written by Arnis: write it in the layout of the target version
+                    self.light_chunk(&mut chunk, &mut light_cache);
+                    let nbt = chunk_to_nbt(&chunk, &chunk_to_modify.biomes, self.version);
+                    fastnbt::to_writer(&mut ser_buffer, &nbt).unwrap();
+                } else {
+                    // Chunk written by the game itself: keep its layout and all other data,
+                    // only drop what has to be recomputed after the block changes. Its
+                    // neighbors are not known here, so the game relights it on load.
+                    chunk.is_light_on = 0;
+                    chunk.other.remove("Heightmaps");
+                    fastnbt::to_writer(&mut ser_buffer, &chunk).unwrap();
+                }
+                region
+                    .write_chunk(chunk_x as usize, chunk_z as usize, &ser_buffer)
+                    .unwrap();
+            }
+        }
 
-                if new_progress as u64 - prev_progress > 1 {
-                    emit_gui_progress_update(new_progress / 10.0, "Saving world...");
+        // Second pass: ensure all chunks exist
+        for chunk_x in 0..32 {
+            for chunk_z in 0..32 {
+                let abs_chunk_x = chunk_x + (region_x * 32);
+                let abs_chunk_z = chunk_z + (region_z * 32);
+
+                // Check if chunk exists in our modifications, or in update mode in the
+                // existing region file (those chunks are left untouched)
+                let chunk_exists = region_to_modify.chunks.contains_key(&(chunk_x, chunk_z))
+                    || (self.update_existing
+                        && matches!(
+                            region.read_chunk(chunk_x as usize, chunk_z as usize),
+                            Ok(Some(_))
+                        ));
+
+                // If chunk doesn't exist, create it with base layer
+                if !chunk_exists {
+                    let (ser_buffer, _) =
+                        Self::create_base_chunk(abs_chunk_x, abs_chunk_z, self.version);
+                    region
+                        .write_chunk(chunk_x as usize, chunk_z as usize, &ser_buffer)
+                        .unwrap();
                 }
+            }
+        }
 
-                save_pb.inc(1);
+        if self.version.has_entity_regions() {
+            self.write_entities_region(region_x, region_z, region_to_modify);
+        }
+    }
+
+    /// Writes the entities of one region to its region file in the `entities` folder. In update
+    /// mode they are added to the entities already saved by the game.
+    fn write_entities_region(
+        &self,
+        region_x: i32,
+        region_z: i32,
+        region_to_modify: &RegionToModify,
+    ) {
+        let path = self
+            .world_dir
+            .join(format!("entities/r.{}.{}.mca", region_x, region_z));
+
+        if region_to_modify
+            .chunks
+            .values()
+            .all(|chunk| chunk.entities.is_empty())
+        {
+            // The region file is recreated, so entities of an earlier generation must go too
+            if !self.update_existing {
+                let _ = std::fs::remove_file(&path);
+            }
+            return;
+        }
+
+        let has_data = std::fs::metadata(&path)
+            .map(|m| m.len() > 0)
+            .unwrap_or(false);
+        let keep_existing = self.update_existing && has_data;
+
+        std::fs::create_dir_all(self.world_dir.join("entities"))
+            .expect("Failed to create entities folder");
+        let region_file: File = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(!keep_existing)
+            .open(&path)
+            .expect("Failed to open entities region file");
+        let mut region = if keep_existing {
+            match Region::from_stream(region_file) {
+                Ok(region) => region,
+                Err(e) => {
+                    eprintln!(
+                        "Warning: Skipping unreadable entities region file {}: {e}",
+                        path.display()
+                    );
+                    return;
+                }
+            }
+        } else {
+            Region::create(region_file).expect("Failed to create entities region")
+        };
+
+        let mut ser_buffer = Vec::with_capacity(1024);
+        for (&(chunk_x, chunk_z), chunk_to_modify) in &region_to_modify.chunks {
+            if chunk_to_modify.entities.is_empty() {
+                continue;
+            }
+
+            let mut chunk_entities: Vec<Value> = Vec::new();
+            if keep_existing {
+                if let Ok(Some(data)) = region.read_chunk(chunk_x as usize, chunk_z as usize) {
+                    if let Ok(Value::Compound(mut existing)) = fastnbt::from_bytes(&data) {
+                        if let Some(Value::List(existing)) = existing.remove("Entities") {
+                            chunk_entities = existing;
+                        }
+                    }
+                }
+            }
+            chunk_entities.extend(
+                chunk_to_modify
+                    .entities
+                    .iter()
+                    .map(|entity| entity.to_nbt(self.version)),
+            );
+
+            let nbt = entities::entity_chunk_nbt(
+                chunk_x + (region_x * 32),
+                chunk_z + (region_z * 32),
+                chunk_entities,
+                self.version,
+            );
+            ser_buffer.clear();
+            fastnbt::to_writer(&mut ser_buffer, &nbt).unwrap();
+            region
+                .write_chunk(chunk_x as usize, chunk_z as usize, &ser_buffer)
+                .unwrap();
+        }
+    }
+
+    /// Computes the light and heightmaps of a chunk, so the game does not have to relight it
+    /// when it is first loaded. Light from neighboring chunks is taken from the generated
+    /// blocks, chunks of other tiles that were already written count as air.
+    fn light_chunk(&self, chunk: &mut Chunk, cache: &mut LightCache) {
+        let Some(min_section) = chunk.sections.iter().map(|section| section.y).min() else {
+            return;
+        };
+
+        let neighbors: Vec<(i32, i32, &ChunkToModify)> = (-1..=1)
+            .flat_map(|dz| (-1..=1).map(move |dx| (dx, dz)))
+            .filter(|&offset| offset != (0, 0))
+            .filter_map(|(dx, dz)| {
+                let neighbor = self.world.get_chunk(chunk.x_pos + dx, chunk.z_pos + dz)?;
+                Some((dx, dz, neighbor))
+            })
+            .collect();
+        let max_section = chunk
+            .sections
+            .iter()
+            .map(|section| section.y)
+            .chain(
+                neighbors
+                    .iter()
+                    .flat_map(|(_, _, neighbor)| neighbor.sections.keys().copied()),
+            )
+            .max()
+            .unwrap_or(min_section);
+
+        let mut grid = LightGrid::new(min_section, max_section);
+        let mut heightmaps = Heightmaps::default();
+
+        for section in &chunk.sections {
+            let palette: Vec<LightProps> = section
+                .block_states
+                .palette
+                .iter()
+                .map(|item| LightProps::of(&item.name))
+                .collect();
+            let indices =
+                unpack_palette_indices(section.block_states.data.as_deref(), palette.len());
+            let props = |i: usize| palette.get(indices[i]).copied().unwrap_or(LightProps::AIR);
+
+            grid.set_section(0, 0, section.y, props);
+            heightmaps.add_section(section.y, props);
+        }
+        for (dx, dz, neighbor) in neighbors {
+            for (&section_y, section) in &neighbor.sections {
+                grid.set_section(dx, dz, section_y, |i| cache.get(section.blocks[i]));
+            }
+        }
+
+        let light = grid.compute();
+        for section in &mut chunk.sections {
+            if let Some((sky_light, block_light)) = light.section_nbt(section.y) {
+                section.other.insert("SkyLight".to_string(), sky_light);
+                section.other.insert("BlockLight".to_string(), block_light);
+            }
+        }
+        chunk
+            .other
+            .insert("Heightmaps".to_string(), heightmaps.to_nbt());
+        chunk.is_light_on = 1;
+    }
+
+    /// Writes the chunks of one region to the Bedrock database
+    fn write_bedrock_region(
+        &self,
+        bedrock: &Mutex<BedrockWriter>,
+        region_x: i32,
+        region_z: i32,
+        region_to_modify: &RegionToModify,
+    ) {
+        for (&(chunk_x, chunk_z), chunk_to_modify) in &region_to_modify.chunks {
+            let sections = chunk_to_modify
+                .sections
+                .iter()
+                .map(|(&y, section_to_modify)| {
+                    let section = section_to_modify.to_section(y);
+                    let palette = section.block_states.palette;
+                    BedrockSection {
+                        y,
+                        indices: unpack_palette_indices(
+                            section.block_states.data.as_deref(),
+                            palette.len(),
+                        ),
+                        palette: palette
+                            .into_iter()
+                            .map(|item| (item.name, item.properties))
+                            .collect(),
+                    }
+                })
+                .collect();
+            let block_entities = match chunk_to_modify.other.get("block_entities") {
+                Some(Value::List(entities)) => entities.clone(),
+                _ => Vec::new(),
+            };
+
+            let entries = bedrock::encode_chunk(&BedrockChunk {
+                x: chunk_x + (region_x * 32),
+                z: chunk_z + (region_z * 32),
+                sections,
+                biomes: std::array::from_fn(|column| {
+                    biomes::column_biome(&chunk_to_modify.biomes, column).bedrock_id()
+                }),
+                block_entities,
             });
+            bedrock
+                .lock()
+                .unwrap()
+                .write_chunk(&entries)
+                .expect("Failed to write Bedrock chunk");
+        }
+    }
 
-        save_pb.finish();
+    /// Writes all regions modified so far to disk and frees them (tiled generation).
+    /// Every region must only be flushed once, as region files are recreated when written.
+    pub fn flush_regions(&mut self) {
+        // The world is only dropped after writing, so chunks of this tile still light their
+        // neighbors across chunk and region borders
+        self.world
+            .regions
+            .par_iter()
+            .for_each(|((region_x, region_z), region_to_modify)| {
+                self.write_region(*region_x, *region_z, region_to_modify);
+            });
+        self.world = WorldToModify::default();
+    }
+
+    /// Adds the region and the blocks placed in it to the report
+    fn report_region(&self, region_x: i32, region_z: i32, region_to_modify: &RegionToModify) {
+        let mut counts: FnvHashMap<Block, u64> = FnvHashMap::default();
+        let blocks = region_to_modify
+            .chunks
+            .values()
+            .flat_map(|chunk| chunk.sections.values())
+            .flat_map(|section| section.blocks.iter());
+        for &block in blocks.filter(|&&block| block != AIR) {
+            *counts.entry(block).or_default() += 1;
+        }
+
+        let mut report = self.report();
+        report.add_region(region_x, region_z);
+        for (block, count) in counts {
+            report.add_blocks(block.qualified_name(), count);
+        }
+    }
+
+    /// Writes the report of the run next to the metadata
+    fn save_report(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let report_path = self.world_dir.join("report.json");
+        let contents = serde_json::to_string_pretty(&self.report().to_json())?;
+        std::fs::write(&report_path, contents)
+            .map_err(|e| format!("Failed to write report to {}: {}", report_path.display(), e))?;
+        Ok(())
     }
 
     fn save_metadata(&mut self) -> Result<(), Box<dyn std::error::Error>> {
@@ -937,6 +1643,237 @@ impl<'a> WorldEditor<'a> {
 
         Ok(())
     }
+
+    /// Collects all placed blocks into a schematic spanning their bounding box, blocks that
+    /// do not exist in the target version are substituted like in region files.
+    /// Returns `None` if no blocks were placed.
+    pub fn to_schematic(&self) -> Option<Schematic> {
+        let sections = || {
+            self.world
+                .regions
+                .iter()
+                .flat_map(|(&(region_x, region_z), region)| {
+                    region
+                        .chunks
+                        .iter()
+                        .map(move |(&(chunk_x, chunk_z), chunk)| {
+                            ((region_x << 5) + chunk_x, (region_z << 5) + chunk_z, chunk)
+                        })
+                })
+                .flat_map(|(chunk_x, chunk_z, chunk)| {
+                    chunk.sections.iter().map(move |(&section_y, section)| {
+                        (
+                            (chunk_x << 4, i32::from(section_y) << 4, chunk_z << 4),
+                            section,
+                        )
+                    })
+                })
+        };
+
+        let mut min = (i32::MAX, i32::MAX, i32::MAX);
+        let mut max = (i32::MIN, i32::MIN, i32::MIN);
+        for ((base_x, base_y, base_z), section) in sections() {
+            for (i, _) in section.blocks.iter().enumerate().filter(|(_, &b)| b != AIR) {
+                let (x, y, z) = (
+                    base_x + (i & 15) as i32,
+                    base_y + (i >> 8) as i32,
+                    base_z + (i >> 4 & 15) as i32,
+                );
+                min = (min.0.min(x), min.1.min(y), min.2.min(z));
+                max = (max.0.max(x), max.1.max(y), max.2.max(z));
+            }
+        }
+        if min.0 > max.0 {
+            return None;
+        }
+
+        let size = (
+            (max.0 - min.0 + 1) as usize,
+            (max.1 - min.1 + 1) as usize,
+            (max.2 - min.2 + 1) as usize,
+        );
+        let mut blocks = vec![0u32; size.0 * size.1 * size.2];
+        let mut palette = vec![(AIR.qualified_name(), None)];
+        let mut palette_lookup: FnvHashMap<(Block, Option<String>), u32> = FnvHashMap::default();
+        palette_lookup.insert((AIR, None), 0);
+        // Palette index per block without stored properties, skipping the key of the lookup
+        let mut block_indices: FnvHashMap<Block, u32> = FnvHashMap::default();
+
+        for ((base_x, base_y, base_z), section) in sections() {
+            for (i, &block) in section.blocks.iter().enumerate() {
+                if block == AIR {
+                    continue;
+                }
+                let properties = section.properties.get(&i);
+                let cached = properties
+                    .is_none()
+                    .then(|| block_indices.get(&block).copied())
+                    .flatten();
+                let index = cached.unwrap_or_else(|| {
+                    let key = (block, properties.map(|p| format!("{p:?}")));
+                    let index = *palette_lookup.entry(key).or_insert_with(|| {
+                        let name = block.qualified_name();
+                        let name = self
+                            .version
+                            .substitute_block(&name)
+                            .map_or(name, str::to_string);
+                        palette.push((name, properties.cloned().or_else(|| block.properties())));
+                        (palette.len() - 1) as u32
+                    });
+                    if properties.is_none() {
+                        block_indices.insert(block, index);
+                    }
+                    index
+                });
+
+                let x = (base_x + (i & 15) as i32 - min.0) as usize;
+                let y = (base_y + (i >> 8) as i32 - min.1) as usize;
+                let z = (base_z + (i >> 4 & 15) as i32 - min.2) as usize;
+                blocks[(y * size.2 + z) * size.0 + x] = index;
+            }
+        }
+
+        let block_entities = self
+            .world
+            .regions
+            .values()
+            .flat_map(|region| region.chunks.values())
+            .filter_map(|chunk| match chunk.other.get("block_entities") {
+                Some(Value::List(entities)) => Some(entities),
+                _ => None,
+            })
+            .flatten()
+            .map(|entity| block_entity_to_nbt(entity, self.version))
+            .collect();
+
+        Some(Schematic {
+            // Block entities are shifted like in region files, so is the origin
+            origin: (min.0, min.1 + self.version.y_offset(), min.2),
+            size,
+            palette,
+            blocks,
+            block_entities,
+        })
+    }
+
+    /// Renders the placed blocks within the bounding box as seen from above, one pixel per
+    /// block with north at the top. Returns `None` if no blocks were placed.
+    pub fn render_top_down(&self) -> Option<RgbaImage> {
+        if self.world.regions.is_empty() {
+            return None;
+        }
+
+        let (min_x, min_z) = self.get_min_coords();
+        let (max_x, max_z) = self.get_max_coords();
+        let mut cache = LightCache::default();
+        let columns: Vec<Option<SurfaceBlock>> = (min_z..=max_z)
+            .flat_map(|z| (min_x..=max_x).map(move |x| (x, z)))
+            .map(|(x, z)| {
+                self.world.get_chunk(x >> 4, z >> 4)?.surface_block(
+                    (x & 15) as u8,
+                    (z & 15) as u8,
+                    &mut cache,
+                )
+            })
+            .collect();
+
+        Some(render::render_surface(
+            (max_x - min_x + 1) as u32,
+            (max_z - min_z + 1) as u32,
+            &columns,
+        ))
+    }
+}
+
+/// Parses a chunk read from a region file. Chunks written by Arnis use a legacy `Level`
+/// wrapper compound, chunks saved by the game (1.18+) store their data at the root.
+/// Returns the chunk and whether it was wrapped.
+fn parse_chunk(data: &[u8]) -> Result<(Chunk, bool), fastnbt::error::Error> {
+    let invalid = || <fastnbt::error::Error as serde::de::Error>::custom("Invalid chunk");
+
+    let Value::Compound(mut root) = fastnbt::from_bytes(data)? else {
+        return Err(invalid());
+    };
+    let (root, wrapped) = match root.remove("Level") {
+        Some(Value::Compound(level)) => (level, true),
+        _ => (root, false),
+    };
+    let chunk = chunk_from_nbt(root).ok_or_else(invalid)?;
+    Ok((chunk, wrapped))
+}
+
+/// Converts chunk NBT by hand, as deserializing the flattened `other` maps with serde loses
+/// the array types (light, heightmaps, biomes) that chunks saved by the game contain
+fn chunk_from_nbt(mut root: HashMap<String, Value>) -> Option<Chunk> {
+    let mut int = |key: &str| match root.remove(key) {
+        Some(Value::Int(value)) => Some(value),
+        _ => None,
+    };
+    let x_pos = int("xPos")?;
+    let z_pos = int("zPos")?;
+    let is_light_on = match root.remove("isLightOn") {
+        Some(Value::Byte(value)) => value as u8,
+        _ => 0,
+    };
+
+    let sections = match root.remove("sections") {
+        // Sections that only store light have no block states, the light is recomputed
+        Some(Value::List(sections)) => sections.into_iter().filter_map(section_from_nbt).collect(),
+        _ => Vec::new(),
+    };
+
+    Some(Chunk {
+        sections,
+        x_pos,
+        z_pos,
+        is_light_on,
+        other: root.into_iter().collect(),
+    })
+}
+
+fn section_from_nbt(section: Value) -> Option<Section> {
+    let Value::Compound(mut section) = section else {
+        return None;
+    };
+    let Some(Value::Byte(y)) = section.remove("Y") else {
+        return None;
+    };
+    let Some(Value::Compound(mut block_states)) = section.remove("block_states") else {
+        return None;
+    };
+
+    let palette = match block_states.remove("palette") {
+        Some(Value::List(palette)) => palette
+            .into_iter()
+            .filter_map(|item| {
+                let Value::Compound(mut item) = item else {
+                    return None;
+                };
+                let Some(Value::String(name)) = item.remove("Name") else {
+                    return None;
+                };
+                Some(PaletteItem {
+                    name,
+                    properties: item.remove("Properties"),
+                })
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    let data = match block_states.remove("data") {
+        Some(Value::LongArray(data)) => Some(data),
+        _ => None,
+    };
+
+    Some(Section {
+        block_states: Blockstates {
+            palette,
+            data,
+            other: block_states.into_iter().collect(),
+        },
+        y,
+        other: section.into_iter().collect(),
+    })
 }
 
 // Helper function to get entity coordinates
@@ -960,67 +1897,374 @@ fn get_entity_coords(entity: &HashMap<String, Value>) -> (i32, i32, i32) {
     (x, y, z)
 }
 
-#[inline]
-fn create_level_wrapper(chunk: &Chunk) -> HashMap<String, Value> {
-    HashMap::from([(
-        "Level".to_string(),
+/// Builds the NBT of a chunk written by Arnis in the layout of the target version.
+/// Legacy versions get the whole chunk shifted up, sections outside their height are dropped.
+fn chunk_to_nbt(chunk: &Chunk, biomes: &ChunkBiomes, version: McVersion) -> Value {
+    let section_offset = (version.y_offset() / 16) as i8;
+    let sections: Vec<Value> = chunk
+        .sections
+        .iter()
+        .filter_map(|section| {
+            let y = section.y.checked_add(section_offset)?;
+            version
+                .section_range()
+                .contains(&y)
+                .then(|| section_to_nbt(section, y, biomes, version))
+        })
+        .collect();
+
+    let block_entities: Vec<Value> = match chunk.other.get("block_entities") {
+        Some(Value::List(entities)) => entities
+            .iter()
+            .map(|entity| block_entity_to_nbt(entity, version))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let mut level = HashMap::from([
+        ("xPos".to_string(), Value::Int(chunk.x_pos)),
+        ("zPos".to_string(), Value::Int(chunk.z_pos)),
+        (
+            "isLightOn".to_string(),
+            Value::Byte(i8::try_from(chunk.is_light_on).unwrap()),
+        ),
+    ]);
+    if let Some(heightmaps) = chunk.other.get("Heightmaps") {
+        level.insert("Heightmaps".to_string(), heightmaps.clone());
+    }
+
+    if version.is_legacy() {
+        let entities = chunk
+            .other
+            .get("Entities")
+            .cloned()
+            .unwrap_or(Value::List(Vec::new()));
+        level.insert("Entities".to_string(), entities);
+        level.insert("Status".to_string(), Value::String("full".to_string()));
+        level.insert("Sections".to_string(), Value::List(sections));
+        level.insert("TileEntities".to_string(), Value::List(block_entities));
+        level.insert(
+            "Biomes".to_string(),
+            Value::IntArray(IntArray::new(biomes::legacy_biomes(biomes))),
+        );
+
         Value::Compound(HashMap::from([
-            ("xPos".to_string(), Value::Int(chunk.x_pos)),
-            ("zPos".to_string(), Value::Int(chunk.z_pos)),
             (
-                "isLightOn".to_string(),
-                Value::Byte(i8::try_from(chunk.is_light_on).unwrap()),
+                "DataVersion".to_string(),
+                Value::Int(version.data_version()),
             ),
-            (
-                "sections".to_string(),
-                Value::List(
-                    chunk
-                        .sections
-                        .iter()
-                        .map(|section| {
-                            let mut block_states = HashMap::from([(
-                                "palette".to_string(),
-                                Value::List(
-                                    section
-                                        .block_states
-                                        .palette
-                                        .iter()
-                                        .map(|item| {
-                                            let mut palette_item = HashMap::from([(
-                                                "Name".to_string(),
-                                                Value::String(item.name.clone()),
-                                            )]);
-                                            if let Some(props) = &item.properties {
-                                                palette_item.insert(
-                                                    "Properties".to_string(),
-                                                    props.clone(),
-                                                );
-                                            }
-                                            Value::Compound(palette_item)
-                                        })
-                                        .collect(),
-                                ),
-                            )]);
-
-                            // only add the `data` attribute if it's non-empty
-                            // some software (cough cough dynmap) chokes otherwise
-                            if let Some(data) = &section.block_states.data {
-                                if !data.is_empty() {
-                                    block_states.insert(
-                                        "data".to_string(),
-                                        Value::LongArray(data.to_owned()),
-                                    );
-                                }
-                            }
+            ("Level".to_string(), Value::Compound(level)),
+        ]))
+    } else {
+        level.insert(
+            "DataVersion".to_string(),
+            Value::Int(version.data_version()),
+        );
+        level.insert("yPos".to_string(), Value::Int(-4));
+        level.insert(
+            "Status".to_string(),
+            Value::String("minecraft:full".to_string()),
+        );
+        level.insert("sections".to_string(), Value::List(sections));
+        level.insert("block_entities".to_string(), Value::List(block_entities));
 
-                            Value::Compound(HashMap::from([
-                                ("Y".to_string(), Value::Byte(section.y)),
-                                ("block_states".to_string(), Value::Compound(block_states)),
-                            ]))
-                        })
-                        .collect(),
-                ),
+        Value::Compound(level)
+    }
+}
+
+fn section_to_nbt(section: &Section, y: i8, biomes: &ChunkBiomes, version: McVersion) -> Value {
+    let palette = Value::List(
+        section
+            .block_states
+            .palette
+            .iter()
+            .map(|item| {
+                let name = version.substitute_block(&item.name).unwrap_or(&item.name);
+                let mut palette_item =
+                    HashMap::from([("Name".to_string(), Value::String(name.to_string()))]);
+                if let Some(props) = &item.properties {
+                    palette_item.insert("Properties".to_string(), props.clone());
+                }
+                Value::Compound(palette_item)
+            })
+            .collect(),
+    );
+    let data = section
+        .block_states
+        .data
+        .as_ref()
+        .filter(|data| !data.is_empty());
+
+    let mut nbt = if version.is_legacy() {
+        // Legacy sections without block states are read as empty
+        let data = data.map_or_else(|| vec![0; 256], |data| data.to_vec());
+        HashMap::from([
+            ("Y".to_string(), Value::Byte(y)),
+            ("Palette".to_string(), palette),
+            (
+                "BlockStates".to_string(),
+                Value::LongArray(LongArray::new(data)),
             ),
-        ])),
-    )])
+        ])
+    } else {
+        let mut block_states = HashMap::from([("palette".to_string(), palette)]);
+
+        // only add the `data` attribute if it's non-empty
+        // some software (cough cough dynmap) chokes otherwise
+        if let Some(data) = data {
+            block_states.insert("data".to_string(), Value::LongArray(data.to_owned()));
+        }
+
+        HashMap::from([
+            ("Y".to_string(), Value::Byte(y)),
+            ("block_states".to_string(), Value::Compound(block_states)),
+            ("biomes".to_string(), biomes::section_biomes_nbt(biomes)),
+        ])
+    };
+
+    for key in ["SkyLight", "BlockLight"] {
+        if let Some(light) = section.other.get(key) {
+            nbt.insert(key.to_string(), light.clone());
+        }
+    }
+
+    Value::Compound(nbt)
+}
+
+/// Shifts a block entity like its chunk and converts signs to the pre-1.20 text format
+fn block_entity_to_nbt(entity: &Value, version: McVersion) -> Value {
+    let Value::Compound(entity) = entity else {
+        return entity.clone();
+    };
+    let mut entity = entity.clone();
+
+    if let Some(Value::Int(y)) = entity.get_mut("y") {
+        *y += version.y_offset();
+    }
+
+    if !version.has_sign_sides() {
+        if let Some(Value::Compound(front_text)) = entity.remove("front_text") {
+            if let Some(Value::List(messages)) = front_text.get("messages") {
+                for (i, message) in messages.iter().enumerate() {
+                    entity.insert(format!("Text{}", i + 1), message.clone());
+                }
+            }
+            if let Some(color) = front_text.get("color") {
+                entity.insert("Color".to_string(), color.clone());
+            }
+            entity.remove("back_text");
+            entity.remove("is_waxed");
+        }
+    }
+
+    Value::Compound(entity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_indices_roundtrip() {
+        for palette_len in [2, 16, 17, 100] {
+            let indices: Vec<usize> = (0..4096).map(|i| (i * 7) % palette_len).collect();
+            let data = pack_palette_indices(indices.iter().copied(), palette_len);
+            assert_eq!(unpack_palette_indices(Some(&data), palette_len), indices);
+        }
+    }
+
+    #[test]
+    fn test_merge_keeps_untouched_blocks() {
+        let mut existing = SectionToModify::default();
+        existing.blocks.fill(STONE);
+        existing.blocks[SectionToModify::index(0, 0, 0)] = DIRT;
+        let mut existing = existing.to_section(0);
+        // Game-written palettes carry the namespace
+        for item in &mut existing.block_states.palette {
+            item.name = format!("minecraft:{}", item.name);
+        }
+
+        let mut section = SectionToModify::default();
+        section.set_block(1, 0, 0, DIRT);
+        section.set_block(2, 0, 0, GRASS_BLOCK);
+        // Air placed on purpose replaces the existing block
+        section.set_block(4, 0, 0, AIR);
+        section.merge_into(&mut existing);
+
+        // The namespaced dirt entry is reused, only grass and air are added
+        assert_eq!(existing.block_states.palette.len(), 4);
+        let indices = unpack_palette_indices(existing.block_states.data.as_deref(), 4);
+        let name_at = |x: u8| {
+            let item = &existing.block_states.palette[indices[SectionToModify::index(x, 0, 0)]];
+            item.name.trim_start_matches("minecraft:").to_string()
+        };
+        assert_eq!(name_at(0), "dirt");
+        assert_eq!(name_at(1), "dirt");
+        assert_eq!(name_at(2), "grass_block");
+        assert_eq!(name_at(3), "stone");
+        assert_eq!(name_at(4), "air");
+        assert_eq!(name_at(5), "stone");
+    }
+
+    #[test]
+    fn test_palette_key_ignores_property_order() {
+        let properties = |entries: &[(&str, &str)]| {
+            Value::Compound(
+                entries
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), Value::String(v.to_string())))
+                    .collect(),
+            )
+        };
+        let entries = [
+            ("facing", "north"),
+            ("half", "bottom"),
+            ("shape", "straight"),
+            ("waterlogged", "false"),
+        ];
+        let mut reversed = entries;
+        reversed.reverse();
+
+        assert_eq!(
+            palette_key("minecraft:oak_stairs", Some(&properties(&entries))),
+            palette_key("oak_stairs", Some(&properties(&reversed)))
+        );
+        assert_ne!(
+            palette_key("oak_stairs", Some(&properties(&entries))),
+            palette_key("oak_stairs", Some(&properties(&entries[1..])))
+        );
+    }
+
+    #[test]
+    fn test_parse_chunk_keeps_arrays() {
+        let mut section = SectionToModify::default();
+        section.blocks.fill(STONE);
+        let mut section = section.to_section(-4);
+        section.other.insert(
+            "SkyLight".to_string(),
+            Value::ByteArray(fastnbt::ByteArray::new(vec![0x0f; 2048])),
+        );
+        let chunk = Chunk {
+            sections: vec![section],
+            x_pos: 3,
+            z_pos: -2,
+            is_light_on: 1,
+            other: FnvHashMap::from_iter([(
+                "Heightmaps".to_string(),
+                Value::Compound(HashMap::from([(
+                    "WORLD_SURFACE".to_string(),
+                    Value::LongArray(LongArray::new(vec![1; 37])),
+                )])),
+            )]),
+        };
+
+        let data = fastnbt::to_bytes(&chunk).unwrap();
+        let (parsed, wrapped) = parse_chunk(&data).unwrap();
+        assert!(!wrapped);
+        assert_eq!((parsed.x_pos, parsed.z_pos, parsed.is_light_on), (3, -2, 1));
+        assert_eq!(
+            parsed.sections[0].block_states.palette[0].name,
+            "minecraft:stone"
+        );
+        assert_eq!(
+            parsed.sections[0].other["SkyLight"],
+            chunk.sections[0].other["SkyLight"]
+        );
+        assert_eq!(parsed.other["Heightmaps"], chunk.other["Heightmaps"]);
+    }
+
+    #[test]
+    fn test_flush_lights_across_chunk_border() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tmpdir.path().join("region")).unwrap();
+        let xzbbox = XZBBox::rect_from_xz_lengths(31.0, 15.0).unwrap();
+        let llbbox = LLBBox::new(0.0, 0.0, 0.001, 0.001).unwrap();
+        let mut editor = WorldEditor::new(tmpdir.path().to_path_buf(), &xzbbox, llbbox);
+
+        // Stone floor at y 0 across both chunks and a roof at y 4 over x 8-23, so the columns
+        // at the chunk border are 8 blocks away from open sky
+        for x in 0..32 {
+            for z in 0..16 {
+                editor.set_block_absolute(STONE, x, 0, z, None, None);
+                if (8..24).contains(&x) {
+                    editor.set_block_absolute(STONE, x, 4, z, None, None);
+                }
+            }
+        }
+        editor.flush_regions();
+
+        let file = File::open(tmpdir.path().join("region/r.0.0.mca")).unwrap();
+        let mut region = Region::from_stream(file).unwrap();
+        let mut sky_light_at = |chunk_x: usize, x: usize, y: usize, z: usize| {
+            let data = region.read_chunk(chunk_x, 0).unwrap().unwrap();
+            let (chunk, _) = parse_chunk(&data).unwrap();
+            let section = chunk.sections.iter().find(|s| s.y == 0).unwrap();
+            let Value::ByteArray(light) = &section.other["SkyLight"] else {
+                unreachable!()
+            };
+            let i = y * 256 + z * 16 + x;
+            (light[i / 2] as u8 >> (4 * (i % 2))) & 15
+        };
+
+        // Open sky beside the roof
+        assert_eq!(sky_light_at(0, 4, 2, 8), 15);
+        // Under the roof on both sides of the border, the neighboring chunk is not air
+        assert!(sky_light_at(0, 15, 2, 8) <= 7);
+        assert!(sky_light_at(1, 0, 2, 8) <= 7);
+    }
+
+    #[test]
+    fn test_update_leaves_light_of_game_chunks_to_the_game() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tmpdir.path().join("region")).unwrap();
+        let xzbbox = XZBBox::rect_from_xz_lengths(15.0, 15.0).unwrap();
+        let llbbox = LLBBox::new(0.0, 0.0, 0.001, 0.001).unwrap();
+        let mut editor = WorldEditor::new(tmpdir.path().to_path_buf(), &xzbbox, llbbox);
+
+        // A lit chunk saved by the game, stored at the root without a Level wrapper
+        let mut section = SectionToModify::default();
+        section.blocks.fill(STONE);
+        let mut section = section.to_section(0);
+        section.other.insert(
+            "SkyLight".to_string(),
+            Value::ByteArray(fastnbt::ByteArray::new(vec![0x0f; 2048])),
+        );
+        let chunk = Chunk {
+            sections: vec![section],
+            x_pos: 0,
+            z_pos: 0,
+            is_light_on: 1,
+            other: FnvHashMap::from_iter([(
+                "Heightmaps".to_string(),
+                Value::Compound(HashMap::from([(
+                    "WORLD_SURFACE".to_string(),
+                    Value::LongArray(LongArray::new(vec![1; 37])),
+                )])),
+            )]),
+        };
+        let mut region = editor.create_region(0, 0);
+        region
+            .write_chunk(0, 0, &fastnbt::to_bytes(&chunk).unwrap())
+            .unwrap();
+        drop(region);
+
+        editor.set_update_existing(true);
+        editor.set_block_absolute(DIRT, 3, 2, 3, None, None);
+        editor.flush_regions();
+
+        let file = File::open(tmpdir.path().join("region/r.0.0.mca")).unwrap();
+        let mut region = Region::from_stream(file).unwrap();
+        let data = region.read_chunk(0, 0).unwrap().unwrap();
+        let (parsed, wrapped) = parse_chunk(&data).unwrap();
+        assert!(!wrapped);
+        assert_eq!(parsed.is_light_on, 0);
+        assert!(!parsed.other.contains_key("Heightmaps"));
+        let section = parsed.sections.iter().find(|s| s.y == 0).unwrap();
+        assert!(!section.other.contains_key("SkyLight"));
+        assert!(section
+            .block_states
+            .palette
+            .iter()
+            .any(|item| item.name.trim_start_matches("minecraft:") == "dirt"));
+    }
 }